@@ -13,6 +13,10 @@ pub struct AssistConfig {
     pub snippet_cap: Option<SnippetCap>,
     pub allowed: Option<Vec<AssistKind>>,
     pub insert_use: InsertUseConfig,
+    /// Whether inlining a function checks that the inlined code still resolves at the call site.
+    pub verify_inlining: bool,
+    /// Whether inlining a function folds the `if`s on parameters given a `bool` literal.
+    pub fold_inlined_conditions: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
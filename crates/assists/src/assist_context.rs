@@ -1,6 +1,6 @@
 //! See `AssistContext`
 
-use std::mem;
+use std::{iter, mem};
 
 use hir::Semantics;
 use ide_db::{
@@ -12,10 +12,12 @@ use ide_db::{
     source_change::{FileSystemEdit, SourceChange},
     RootDatabase,
 };
+use rustc_hash::FxHashSet;
 use syntax::{
     algo::{self, find_node_at_offset, SyntaxRewriter},
-    AstNode, AstToken, SourceFile, SyntaxElement, SyntaxKind, SyntaxToken, TextRange, TextSize,
-    TokenAtOffset,
+    ast::{self, make},
+    AstNode, AstToken, SourceFile, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
+    TextSize, TokenAtOffset,
 };
 use text_edit::{TextEdit, TextEditBuilder};
 
@@ -95,6 +97,29 @@ impl<'a> AssistContext<'a> {
     pub(crate) fn covering_element(&self) -> SyntaxElement {
         self.source_file.syntax().covering_element(self.frange.range)
     }
+    /// Returns `base`, or `base` with the first numeric suffix (`base1`, `base2`, ...) that makes
+    /// it a name not already in scope at `at`.
+    pub(crate) fn suggest_name(&self, base: &str, at: &SyntaxNode) -> ast::Name {
+        self.suggest_name_avoiding(base, at, &FxHashSet::default())
+    }
+    /// Like [`suggest_name`](Self::suggest_name), but also avoids the `taken` names, like the
+    /// ones of the code the new name is going to be used in.
+    pub(crate) fn suggest_name_avoiding(
+        &self,
+        base: &str,
+        at: &SyntaxNode,
+        taken: &FxHashSet<String>,
+    ) -> ast::Name {
+        let mut names_in_scope = FxHashSet::default();
+        self.sema.scope(at).process_all_names(&mut |name, _| {
+            names_in_scope.insert(name.to_string());
+        });
+        let name = iter::once(base.to_string())
+            .chain((1..).map(|idx| format!("{}{}", base, idx)))
+            .find(|name| !names_in_scope.contains(name) && !taken.contains(name))
+            .expect("there are infinitely many names");
+        make::name(&name)
+    }
     // FIXME: remove
     pub(crate) fn covering_node_for_range(&self, range: TextRange) -> SyntaxElement {
         self.source_file.syntax().covering_element(range)
@@ -134,7 +159,15 @@ impl Assists {
             return None;
         }
         let label = Label::new(label.into());
-        let assist = Assist { id, label, group: None, target, source_change: None };
+        let assist = Assist {
+            id,
+            label,
+            group: None,
+            target,
+            source_change: None,
+            is_preferred: false,
+            reason: None,
+        };
         self.add_impl(assist, f)
     }
 
@@ -150,7 +183,15 @@ impl Assists {
             return None;
         }
         let label = Label::new(label.into());
-        let assist = Assist { id, label, group: Some(group.clone()), target, source_change: None };
+        let assist = Assist {
+            id,
+            label,
+            group: Some(group.clone()),
+            target,
+            source_change: None,
+            is_preferred: false,
+            reason: None,
+        };
         self.add_impl(assist, f)
     }
 
@@ -158,6 +199,8 @@ impl Assists {
         let source_change = if self.resolve {
             let mut builder = AssistBuilder::new(self.file);
             f(&mut builder);
+            assist.is_preferred = builder.is_preferred;
+            assist.reason = builder.reason.take();
             Some(builder.finish())
         } else {
             None
@@ -180,11 +223,19 @@ pub(crate) struct AssistBuilder {
     edit: TextEditBuilder,
     file_id: FileId,
     source_change: SourceChange,
+    is_preferred: bool,
+    reason: Option<String>,
 }
 
 impl AssistBuilder {
     pub(crate) fn new(file_id: FileId) -> AssistBuilder {
-        AssistBuilder { edit: TextEdit::builder(), file_id, source_change: SourceChange::default() }
+        AssistBuilder {
+            edit: TextEdit::builder(),
+            file_id,
+            source_change: SourceChange::default(),
+            is_preferred: false,
+            reason: None,
+        }
     }
 
     pub(crate) fn edit_file(&mut self, file_id: FileId) {
@@ -240,6 +291,14 @@ impl AssistBuilder {
             algo::diff(&node, &new).into_text_edit(&mut self.edit);
         }
     }
+    /// Marks the assist as the one the user most likely wants, see `Assist::is_preferred`.
+    pub(crate) fn set_preferred(&mut self) {
+        self.is_preferred = true;
+    }
+    /// Tells the user what the change leaves to them, see `Assist::reason`.
+    pub(crate) fn set_reason(&mut self, reason: impl Into<String>) {
+        self.reason = Some(reason.into());
+    }
     pub(crate) fn create_file(&mut self, dst: AnchoredPathBuf, content: impl Into<String>) {
         let file_system_edit =
             FileSystemEdit::CreateFile { dst: dst.clone(), initial_contents: content.into() };
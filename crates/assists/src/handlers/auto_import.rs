@@ -962,6 +962,32 @@ fn main() {
 fn main() {
     FMT;
 }
+",
+        );
+    }
+    #[test]
+    fn prefers_std_over_core() {
+        check_assist(
+            auto_import,
+            r"
+//- /core.rs crate:core
+pub mod fmt {
+    pub struct Error;
+}
+
+//- /std.rs crate:std deps:core
+pub use core::fmt;
+
+//- /main.rs crate:main deps:core,std
+fn main() {
+    let _: Error$0;
+}
+",
+            r"use std::fmt::Error;
+
+fn main() {
+    let _: Error;
+}
 ",
         );
     }
@@ -145,6 +145,7 @@ pub(crate) fn convert_to_guarded_return(acc: &mut Assists, ctx: &AssistContext)
 
                     let let_stmt = make::let_stmt(
                         make::ident_pat(make::name(&bound_ident.syntax().to_string())).into(),
+                        None,
                         Some(match_expr),
                     );
                     let let_stmt = let_stmt.indent(if_indent_level);
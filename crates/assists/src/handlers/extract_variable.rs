@@ -54,7 +54,7 @@ pub(crate) fn extract_variable(acc: &mut Assists, ctx: &AssistContext) -> Option
 
             let var_name = match &field_shorthand {
                 Some(it) => it.to_string(),
-                None => "var_name".to_string(),
+                None => ctx.suggest_name("var_name", to_extract.syntax()).to_string(),
             };
             let expr_range = match &field_shorthand {
                 Some(it) => it.syntax().text_range().cover(to_extract.syntax().text_range()),
@@ -556,6 +556,26 @@ fn main() {
         )
     }
 
+    #[test]
+    fn test_extract_var_avoids_names_in_scope() {
+        check_assist(
+            extract_variable,
+            r#"
+fn main() {
+    let var_name = 1;
+    let x = $0var_name + 2$0;
+}
+"#,
+            r#"
+fn main() {
+    let var_name = 1;
+    let $0var_name1 = var_name + 2;
+    let x = var_name1;
+}
+"#,
+        );
+    }
+
     #[test]
     fn test_extract_var_for_return_not_applicable() {
         check_assist_not_applicable(extract_variable, "fn foo() { $0return$0; } ");
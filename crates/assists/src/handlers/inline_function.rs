@@ -1,13 +1,39 @@
+use std::iter;
+
+use either::Either;
+
 use ast::make;
-use hir::{HasSource, PathResolution};
+use hir::{
+    AsAssocItem, AssocItemContainer, HasAttrs, HasSource, HasVisibility, HirDisplay, InFile,
+    PathResolution, ScopeDef,
+};
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    helpers::{
+        insert_use::{insert_use, ImportScope},
+        mod_path_to_ast,
+    },
+    search::ReferenceKind,
+    RootDatabase,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
 use syntax::{
-    ast::{self, edit::AstNodeEdit, ArgListOwner},
-    AstNode,
+    algo::{find_node_at_range, SyntaxRewriter},
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        ArgListOwner, GenericParamsOwner, NameOwner, VisibilityOwner,
+    },
+    match_ast, AstNode, AstToken, Direction, SyntaxElement,
+    SyntaxKind::*,
+    SyntaxNode, SyntaxToken, T,
 };
 use test_utils::mark;
 
 use crate::{
-    assist_context::{AssistContext, Assists},
+    assist_context::{AssistBuilder, AssistContext, Assists},
+    utils::{extract_trivial_expression, unwrap_trivial_block},
     AssistId, AssistKind,
 };
 
@@ -25,93 +51,6205 @@ use crate::{
 // ```
 // fn add(a: u32, b: u32) -> u32 { a + b }
 // fn main() {
+//     let x = 1 + 2;
+// }
+// ```
+pub(crate) fn inline_function(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    inline(acc, ctx, false)
+}
+
+// Assist: inline_function_with_dbg
+//
+// Inlines a function body, wrapping the arguments in `dbg!` to print their values.
+//
+// ```
+// fn add(a: u32, b: u32) -> u32 { a + b }
+// fn main() {
+//     let x = add$0(1, 2);
+// }
+// ```
+// ->
+// ```
+// fn add(a: u32, b: u32) -> u32 { a + b }
+// fn main() {
 //     let x = {
-//         let a = 1;
-//         let b = 2;
+//         let a = dbg!(1);
+//         let b = dbg!(2);
 //         a + b
 //     };
 // }
 // ```
-pub(crate) fn inline_function(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
-    let path_expr: ast::PathExpr = ctx.find_node_at_offset()?;
-    let call = path_expr.syntax().parent().and_then(ast::CallExpr::cast)?;
-    let path = path_expr.path()?;
+pub(crate) fn inline_function_with_dbg(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    inline(acc, ctx, true)
+}
 
-    let function = match ctx.sema.resolve_path(&path)? {
-        PathResolution::Def(hir::ModuleDef::Function(f)) => f,
-        _ => return None,
+// Assist: inline_function_all
+//
+// Inlines a function at all of its call sites, and removes it.
+//
+// ```
+// fn add$0(a: u32, b: u32) -> u32 { a + b }
+// fn main() {
+//     let x = add(1, 2);
+//     let y = add(3, 4);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let x = 1 + 2;
+//     let y = 3 + 4;
+// }
+// ```
+pub(crate) fn inline_function_all(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let db = ctx.db();
+    let fn_ = ctx.find_node_at_offset::<ast::Fn>()?;
+    let name = fn_.name()?;
+    if !name.syntax().text_range().contains_inclusive(ctx.offset()) {
+        return None;
+    }
+    let function = ctx.sema.to_def(&fn_)?;
+    match function.as_assoc_item(db).map(|it| it.container(db)) {
+        // Trait items and their implementations can't be removed on their own.
+        Some(AssocItemContainer::Trait(_)) => return None,
+        Some(AssocItemContainer::Impl(impl_)) if impl_.target_trait(db).is_some() => return None,
+        _ => {}
+    }
+    acc.add(
+        AssistId("inline_function_all", AssistKind::RefactorInline),
+        format!("Inline `{}` at all call sites", name),
+        name.syntax().text_range(),
+        |builder| {
+            // The calls are only searched and checked once the assist is resolved, so it can
+            // still turn out that they can't all be inlined.
+            let inlinings = match all_call_inlinings(ctx, function, &name) {
+                Ok(it) => it,
+                Err(reason) => {
+                    builder.set_reason(reason);
+                    return;
+                }
+            };
+            // The calls in the same scope share its new imports.
+            let mut imports: Vec<(ImportScope, Vec<ast::Path>)> = Vec::new();
+            for inlining in inlinings {
+                (inlining.edit)(builder);
+                let (scope, paths) = match inlining.imports {
+                    Some(it) => it,
+                    None => continue,
+                };
+                let scope_paths = match imports
+                    .iter_mut()
+                    .find(|(it, _)| it.as_syntax_node() == scope.as_syntax_node())
+                {
+                    Some((_, it)) => it,
+                    None => {
+                        imports.push((scope, Vec::new()));
+                        &mut imports.last_mut().unwrap().1
+                    }
+                };
+                for path in paths {
+                    if !scope_paths.iter().any(|it| it.to_string() == path.to_string()) {
+                        scope_paths.push(path);
+                    }
+                }
+            }
+            for (scope, paths) in imports {
+                insert_imports(builder, ctx, scope, &paths);
+            }
+
+            let mut range = fn_.syntax().text_range();
+            if let Some(whitespace) = fn_
+                .syntax()
+                .next_sibling_or_token()
+                .and_then(|it| it.into_token())
+                .filter(|it| it.kind() == WHITESPACE)
+            {
+                range = range.cover(whitespace.text_range());
+            }
+            builder.delete(range);
+        },
+    )
+}
+
+/// Prepares the edits inlining every call of the function, or tells why the function can't be
+/// removed.
+fn all_call_inlinings<'a>(
+    ctx: &'a AssistContext,
+    function: hir::Function,
+    name: &ast::Name,
+) -> Result<Vec<InlinePlan<'a>>, String> {
+    if is_referenced_from_doc_tests(ctx, function) {
+        return Err(format!("`{}` is still called by doc tests", name));
+    }
+
+    let usages = Definition::ModuleDef(function.into()).usages(&ctx.sema).all();
+    let mut references = Vec::new();
+    for (file_id, file_references) in usages {
+        // The function can only be removed once all the calls are inlined.
+        if file_id != ctx.frange.file_id {
+            return Err(format!("`{}` is called from other files", name));
+        }
+        references.extend(file_references);
+    }
+    if references.is_empty() {
+        return Err(format!("`{}` is never called", name));
+    }
+    references.sort_by_key(|it| it.range.start());
+
+    let source_file = ctx.sema.parse(ctx.frange.file_id);
+    // The search only looks where the function is visible, but it can still be called from
+    // elsewhere in code that doesn't compile, which would then lose the function.
+    let reference_ranges = references.iter().map(|it| it.range).collect::<FxHashSet<_>>();
+    let has_calls_not_seeing_function = source_file
+        .syntax()
+        .descendants()
+        .filter_map(ast::PathSegment::cast)
+        .filter(|it| {
+            it.name_ref().map_or(false, |it| {
+                it.text() == name.text() && !reference_ranges.contains(&it.syntax().text_range())
+            })
+        })
+        .any(|it| {
+            matches!(
+                ctx.sema.resolve_path(&it.parent_path()),
+                Some(PathResolution::Def(hir::ModuleDef::Function(it)))
+                | Some(PathResolution::AssocItem(hir::AssocItem::Function(it))) if it == function
+            )
+        });
+    if has_calls_not_seeing_function {
+        mark::hit!(inline_function_all_not_visible);
+        return Err(format!("`{}` is called where it isn't visible", name));
+    }
+    let not_inlinable = || format!("Not all the uses of `{}` can be inlined", name);
+    let mut calls = Vec::new();
+    for reference in references {
+        let call = find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range)
+            .and_then(|name_ref| inlined_call_of(ctx, &name_ref))
+            .filter(|it| it.function == function)
+            .ok_or_else(not_inlinable)?;
+        calls.push(call);
+    }
+    // The edits inlining a call nested in the arguments of another one would overlap.
+    let is_nested = calls.windows(2).any(|pair| {
+        pair[0].expr.syntax().text_range().contains_range(pair[1].expr.syntax().text_range())
+    });
+    if is_nested {
+        return Err(format!("`{}` is called in the arguments of its own calls", name));
+    }
+    calls
+        .into_iter()
+        .map(|call| can_inline(ctx, call, false).map_err(|_| not_inlinable()))
+        .collect()
+}
+
+fn inline(acc: &mut Assists, ctx: &AssistContext, debug_arguments: bool) -> Option<()> {
+    let inlined_call = inlined_call(ctx)?;
+    let (assist_id, label) = if debug_arguments {
+        (
+            "inline_function_with_dbg",
+            format!("Inline `{}` with `dbg!` arguments", inlined_call.name),
+        )
+    } else {
+        ("inline_function", format!("Inline `{}`", inlined_call.name))
+    };
+    let target = inlined_call.expr.syntax().text_range();
+    let function = inlined_call.function;
+    let plan = can_inline(ctx, inlined_call, debug_arguments).ok()?;
+
+    acc.add(AssistId(assist_id, AssistKind::RefactorInline), label, target, |builder| {
+        // The searches for the other calls and for the doc tests are only done once the assist
+        // is resolved.
+        if is_only_usage(ctx, function) {
+            // Inlining the only call of a function is what is most likely wanted there.
+            if !debug_arguments {
+                builder.set_preferred();
+            }
+            // The function becomes dead code once this call is inlined, but removing it would
+            // break the doc tests that still call it.
+            if is_referenced_from_doc_tests(ctx, function) {
+                mark::hit!(inline_function_referenced_in_doc_tests);
+                builder.set_reason(format!(
+                    "`{}` is still called by doc tests",
+                    function.name(ctx.db())
+                ));
+            }
+        }
+        plan.apply(ctx, builder)
+    })
+}
+
+/// The edits inlining a call, see [`can_inline`].
+struct InlinePlan<'a> {
+    edit: Box<dyn FnOnce(&mut AssistBuilder) + 'a>,
+    /// The paths the inlined body needs to be imported in the scope of the call.
+    imports: Option<(ImportScope, Vec<ast::Path>)>,
+}
+
+impl InlinePlan<'_> {
+    fn apply(self, ctx: &AssistContext, builder: &mut AssistBuilder) {
+        (self.edit)(builder);
+        if let Some((scope, paths)) = self.imports {
+            insert_imports(builder, ctx, scope, &paths);
+        }
+    }
+}
+
+/// Why a call can't be inlined, see [`can_inline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InlineError {
+    /// The code of the function is incomplete, like a function without a body.
+    IncompleteCode,
+    /// The call is in the function itself.
+    RecursiveCallInItself,
+    /// The function calls itself.
+    RecursiveFunction,
+    /// The number of arguments doesn't match the number of parameters.
+    IncorrectNumberOfArguments,
+    /// An argument calls the function, or otherwise names it.
+    ArgumentCallsFunction,
+    /// The receiver of the method call can't be bound like the `self` parameter.
+    UnsupportedReceiver,
+    /// A borrow of a temporary argument is returned, the temporary would be dropped too early.
+    ReturnsBorrowOfTemporary,
+    /// The arguments don't satisfy the bounds of the function.
+    UnsatisfiedBounds,
+    /// A path of the body, like `Self` or a relative path, can't be named at the call site.
+    PathNotNameable,
+    /// The body returns before its end.
+    EarlyReturn,
+    /// The generic arguments of the body can't be named at the call site.
+    GenericArgsNotNameable,
+    /// The return type coerces the body in a way the inlined code can't.
+    ReturnCoercionNotReproducible,
+    /// The call is in a const context, which only takes single expressions of `const fn`s.
+    ConstContext,
+    /// An item the body needs can't be imported at the call site.
+    ImportNotPossible,
+    /// The body uses items or fields private to the module of the function.
+    BodyItemNotVisible,
+    /// The body needs an `unsafe` block where unsafe code is forbidden.
+    UnsafeCodeForbidden,
+    /// A path of the body doesn't resolve at the call site, see `AssistConfig::verify_inlining`.
+    UnresolvedPathAtCallSite,
+}
+
+/// Checks whether the call can be inlined, and prepares the edits replacing it with the body of
+/// the function, or tells why it can't be.
+fn can_inline<'a>(
+    ctx: &'a AssistContext,
+    inlined_call: InlinedCall,
+    debug_arguments: bool,
+) -> Result<InlinePlan<'a>, InlineError> {
+    let InlinedCall { expr: call, name: _, function, generic_args, receiver, arguments } =
+        inlined_call;
+
+    let function_source = function.source(ctx.db()).ok_or(InlineError::IncompleteCode)?;
+    check_recursion(ctx, &call, function, &function_source)?;
+    let (receiver, parameters) =
+        checked_arguments(ctx, function, &function_source, receiver, &arguments)?;
+    let promotion_anchor =
+        temporary_promotion(&call, &function_source, receiver.as_ref(), &arguments)?;
+    let (body, return_derefs) =
+        prepared_body(ctx, &call, function, &function_source, generic_args, &arguments)?;
+
+    // Some bodies can take the arguments in place of their parameters, without any bindings.
+    // The receiver of a method call would not be evaluated though.
+    let substituted_body = match debug_arguments || receiver.is_some() {
+        true => None,
+        false => spliced_method_chain(&call, &function_source.value, &body, &arguments)
+            .or_else(|| forwarded_call(&function_source.value, &body, &arguments)),
+    };
+
+    // The `dbg!` calls of the arguments need the bindings.
+    let substitutions = match debug_arguments {
+        true => vec![None; arguments.len()],
+        false => substitutable_arguments(ctx, &function_source, &body, &arguments),
+    };
+    let (parameters, body) = rename_shadowing_parameters(
+        ctx,
+        &call,
+        &function_source,
+        parameters,
+        &arguments,
+        &substitutions,
+        body,
+    );
+    let body = match ctx.config.fold_inlined_conditions {
+        true => fold_substituted_conditions(&parameters, &substitutions, body),
+        false => body,
+    };
+    let body = apply_closure_arguments(&parameters, &substitutions, body);
+    let body = substitute_arguments(&parameters, &substitutions, body);
+    let (receiver_binding, body) = match &receiver {
+        Some(receiver) => receiver.bind_in(ctx, &call, body),
+        None => (None, body),
+    };
+    let bound_names = parameters
+        .iter()
+        .chain(receiver_binding.as_ref().map(|(pattern, _)| pattern))
+        .flat_map(|it| it.syntax().descendants().filter_map(ast::Name::cast))
+        .map(|it| it.text().to_string())
+        .collect::<FxHashSet<_>>();
+
+    let is_identity =
+        receiver.is_none() && identity_argument(&function_source.value, &arguments).is_some();
+    let needs_bindings = !is_identity
+        && (substitutions.iter().any(Option::is_none)
+            || receiver_binding.is_some()
+            || body_statements(&body).next().is_some());
+    check_const_context(&call, &function_source.value, debug_arguments, needs_bindings)?;
+
+    let unused_parameters = unused_parameters(ctx, &function_source);
+    let moved_captures = arguments.iter().map(|it| moved_capture(ctx, &call, it));
+    let declared_types = declared_parameter_types(ctx, &function_source);
+    let new_bindings = parameters
+        .into_iter()
+        .zip(arguments.clone())
+        .zip(unused_parameters)
+        .zip(moved_captures.collect::<Vec<_>>())
+        .zip(
+            arguments
+                .iter()
+                .zip(declared_types)
+                .map(|(it, declared)| binding_annotation(ctx, &call, it, declared))
+                .collect::<Vec<_>>(),
+        )
+        .zip(substitutions.iter().map(Option::is_some).collect::<Vec<_>>())
+        .filter(|(_, is_substituted)| !is_substituted)
+        .map(|(binding, _)| binding);
+
+    let import_scope = ImportScope::find_insert_use_container(call.syntax(), &ctx.sema);
+    let hoisted_imports = match &import_scope {
+        Some(_) => hoistable_imports(ctx, &call, function, &body),
+        None => Vec::new(),
+    };
+
+    let required_imports = match &import_scope {
+        Some(_) => required_imports(ctx, &call, &function_source)?,
+        None => Vec::new(),
+    };
+    check_visible_at_call_site(
+        ctx,
+        &call,
+        &function_source,
+        &body,
+        bound_names,
+        &required_imports,
+    )?;
+    let needs_unsafe_block = needs_unsafe_block(ctx, &call, function)?;
+
+    // An identity function is replaced by its argument, there is nothing to bind.
+    let identity_argument = match debug_arguments || return_derefs > 0 || receiver.is_some() {
+        true => None,
+        false => identity_argument(&function_source.value, &arguments),
+    };
+    let imports = match import_scope {
+        Some(scope) if identity_argument.is_none() => {
+            let paths = hoisted_imports
+                .iter()
+                .filter_map(|it| it.use_tree()?.path())
+                .chain(required_imports)
+                .collect::<Vec<_>>();
+            Some((scope, paths))
+        }
+        _ => None,
     };
 
-    let function_source = function.source(ctx.db())?;
-    let arguments: Vec<_> = call.arg_list()?.args().collect();
-    let parameters = function_parameter_patterns(&function_source.value)?;
+    let edit = Box::new(move |builder: &mut AssistBuilder| {
+        if let Some(argument) = identity_argument {
+            let replacement = if needs_parens_in_place_of(call.syntax(), &argument) {
+                make::expr_paren(argument)
+            } else {
+                argument
+            };
+            builder.replace_ast(call, replacement);
+            return;
+        }
+
+        let mut statements: Vec<ast::Stmt> = Vec::new();
+        statements.extend(receiver_binding.map(|(pattern, value)| {
+            let value = if debug_arguments { make::expr_dbg(value) } else { value };
+            ast::Stmt::from(make::let_stmt(pattern, None, Some(value)))
+        }));
+
+        for ((((pattern, value), is_unused), moved_capture), annotation) in new_bindings {
+            // Unused parameters don't get a binding, that would only trigger warnings.
+            // Their arguments are still evaluated, unless that has no effect.
+            let pattern = match (is_unused, moved_capture) {
+                // Without a named binding the closure around the call would no longer take
+                // ownership of the value, changing how it is captured.
+                (true, Some(name)) => make::ident_pat(make::name(&format!("_{}", name))).into(),
+                (true, None) if is_side_effect_free(&value) && !debug_arguments => continue,
+                (true, None) => make::wildcard_pat().into(),
+                (false, _) => pattern,
+            };
+            let value = if debug_arguments { make::expr_dbg(value) } else { value };
+            statements.push(make::let_stmt(pattern, annotation, Some(value)).into());
+        }
+
+        if let Some(anchor) = &promotion_anchor {
+            hoist_statements(builder, anchor, statements.drain(..));
+        }
+
+        statements.extend(body_statements(&body).filter(|stmt| match stmt {
+            ast::Stmt::Item(ast::Item::Use(use_)) => !hoisted_imports.contains(use_),
+            _ => true,
+        }));
+
+        if let Some(expr) = substituted_body.filter(|_| !needs_unsafe_block) {
+            let expr = expr.reset_indent().indent(call.indent_level());
+            builder.replace_ast(call, expr);
+            return;
+        }
+
+        // A block can't be the scrutinee of a `match`, so the statements go before the
+        // `match` instead.
+        if let Some(anchor) = scrutinee_statement(&call).filter(|_| !needs_unsafe_block) {
+            hoist_statements(builder, &anchor, statements);
+            let replacement = match body.tail_expr() {
+                Some(expr) => expr.reset_indent().indent(call.indent_level()),
+                None => make::expr_unit(),
+            };
+            let replacement = if needs_parens_in_place_of(call.syntax(), &replacement) {
+                make::expr_paren(replacement)
+            } else {
+                replacement
+            };
+            builder.replace_ast(call, replacement);
+            return;
+        }
+
+        let original_indentation = call.indent_level();
+
+        // The value of a call returning `()` that is a statement of its own is dropped, the
+        // statements of the body can take the place of the call's statement.
+        let unit_statement = call
+            .syntax()
+            .parent()
+            .and_then(ast::ExprStmt::cast)
+            .filter(|_| function.ret_type(ctx.db()).is_unit() && !needs_unsafe_block);
+        if let Some(stmt) = unit_statement.filter(|_| !statements.is_empty()) {
+            let replacement =
+                block_statements_in_place_of(&stmt, &body, statements, body.tail_expr());
+            builder.replace(stmt.syntax().text_range(), replacement);
+            return;
+        }
+
+        let block = block_with_comments(&body, statements, body.tail_expr())
+            .flatten_nested_block()
+            .reset_indent()
+            .indent(original_indentation);
+
+        // Arms with a block body don't need a comma to be separated from the next.
+        // The comma is part of the arm list rather than of the arm.
+        let delete_arm_comma = |builder: &mut AssistBuilder| {
+            let arm_comma = call
+                .syntax()
+                .parent()
+                .and_then(ast::MatchArm::cast)
+                .and_then(|arm| {
+                    arm.syntax()
+                        .siblings_with_tokens(Direction::Next)
+                        .skip(1)
+                        .find(|it| !it.kind().is_trivia())
+                })
+                .filter(|it| it.kind() == T![,]);
+            if let Some(comma) = arm_comma {
+                builder.delete(comma.text_range());
+            }
+        };
+        if needs_unsafe_block {
+            delete_arm_comma(builder);
+            builder.replace_ast(call, make::expr_unsafe(block));
+            return;
+        }
+
+        // A body consisting of a single expression doesn't need its own block.
+        let replacement = match unwrap_trivial_block(block) {
+            ast::Expr::BlockExpr(block) => {
+                delete_arm_comma(builder);
+                // A block holding nothing but the call takes the body in place of its own.
+                if let Some(outer) = call.syntax().parent().and_then(ast::BlockExpr::cast) {
+                    let with_body = outer.replace_descendant(call.clone(), block.clone().into());
+                    let flattened = with_body.flatten_nested_block();
+                    if flattened.syntax().text() != with_body.syntax().text() {
+                        builder.replace_ast(outer, flattened);
+                        return;
+                    }
+                }
+                ast::Expr::BlockExpr(block)
+            }
+            expr if needs_parens_in_place_of(call.syntax(), &expr) => make::expr_paren(expr),
+            expr => expr,
+        };
+
+        builder.replace_ast(call, replacement);
+    });
+    Ok(InlinePlan { edit, imports })
+}
+
+/// Checks that inlining the call doesn't only unroll a recursion.
+fn check_recursion(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+) -> Result<(), InlineError> {
+    let enclosing_function = call.syntax().ancestors().find_map(ast::Fn::cast);
+    if enclosing_function.and_then(|it| ctx.sema.to_def(&it)) == Some(function) {
+        // Inlining a recursive call into the function itself only unrolls it, with a copy of the
+        // body that keeps growing every time the assist is applied.
+        mark::hit!(inline_function_recursive_call_in_itself);
+        return Err(InlineError::RecursiveCallInItself);
+    }
+    if calls_itself(ctx, function, function_source) {
+        // The inlined body would still call the function, inlining only unrolls the recursion
+        // by one level.
+        mark::hit!(inline_function_recursive_function);
+        return Err(InlineError::RecursiveFunction);
+    }
+    Ok(())
+}
+
+/// Checks that the receiver and the arguments can take the place of the parameters, and returns
+/// the receiver to bind along with the patterns of the parameters.
+fn checked_arguments(
+    ctx: &AssistContext,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+    receiver: Option<ast::Expr>,
+    arguments: &[ast::Expr],
+) -> Result<(Option<InlinedReceiver>, Vec<ast::Pat>), InlineError> {
+    let receiver = match receiver {
+        Some(it) => {
+            Some(InlinedReceiver::new(ctx, function, it).ok_or(InlineError::UnsupportedReceiver)?)
+        }
+        None => None,
+    };
+    let parameters =
+        function_parameter_patterns(&function_source.value).ok_or(InlineError::IncompleteCode)?;
 
     if arguments.len() != parameters.len() {
         // Can't inline the function because they've passed the wrong number of
         // arguments to this function
         mark::hit!(inline_function_incorrect_number_of_arguments);
+        return Err(InlineError::IncorrectNumberOfArguments);
+    }
+    if receiver
+        .iter()
+        .map(|it| &it.expr)
+        .chain(arguments)
+        .any(|it| refers_to_function(ctx, it.syntax(), function))
+    {
+        // The call in the arguments would be left calling the function, which inlining all of its
+        // calls removes.
+        mark::hit!(inline_function_argument_calls_function);
+        return Err(InlineError::ArgumentCallsFunction);
+    }
+    if !argument_types_satisfy_bounds(ctx, function, &function_source.value, arguments) {
+        // The body may rely on the bounds, so it wouldn't compile with these arguments. This
+        // mostly happens with code that doesn't compile yet anyway.
+        mark::hit!(inline_function_unsatisfied_bounds);
+        return Err(InlineError::UnsatisfiedBounds);
+    }
+    Ok((receiver, parameters))
+}
+
+/// Finds the statement the bindings of the arguments go before when the function returns a
+/// borrow of a temporary argument, if any.
+fn temporary_promotion(
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+    receiver: Option<&InlinedReceiver>,
+    arguments: &[ast::Expr],
+) -> Result<Option<SyntaxNode>, InlineError> {
+    let borrows_temporary = returns_borrow_of_temporary_argument(&function_source.value, arguments)
+        || receiver.map_or(false, |it| it.is_returned_borrow_of_temporary(function_source));
+    if !borrows_temporary || scrutinee_statement(call).is_some() {
+        return Ok(None);
+    }
+    // The signature ties the returned reference to the lifetime of the call's statement, but the
+    // `let` generated for the argument would drop the temporary at the end of the inlined block.
+    // Binding the arguments right before the statement extends the temporary to the enclosing
+    // block instead, as long as that keeps the evaluation order.
+    match temporary_promotion_anchor(call) {
+        Some(anchor) => Ok(Some(anchor)),
+        None => {
+            mark::hit!(inline_function_returns_borrow_of_temporary);
+            Err(InlineError::ReturnsBorrowOfTemporary)
+        }
+    }
+}
+
+/// The body of the function with its paths and generic arguments rewritten for the call site,
+/// along with the number of derefs the return type applied to its tail.
+fn prepared_body(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+    generic_args: Option<ast::GenericArgList>,
+    arguments: &[ast::Expr],
+) -> Result<(ast::BlockExpr, usize), InlineError> {
+    let body = function_source.value.body().ok_or(InlineError::IncompleteCode)?;
+    // A body that is nothing but another block doesn't need two levels of nesting.
+    let body = match extract_trivial_expression(&body) {
+        Some(ast::Expr::BlockExpr(inner)) => inner,
+        _ => body,
+    };
+    let body = rewrite_relative_paths(ctx, call, function, function_source, body)
+        .ok_or(InlineError::PathNotNameable)?;
+    let body = replace_self_paths(ctx, call, function, function_source, body)
+        .ok_or(InlineError::PathNotNameable)?;
+    let body = collapse_tail_return(body);
+    if has_early_return(&body) {
+        // The `return` would exit the function around the call instead of the inlined block.
+        mark::hit!(inline_function_early_return);
+        return Err(InlineError::EarlyReturn);
+    }
+    let caller_type_params = match generic_args {
+        Some(_) => FxHashMap::default(),
+        None => caller_type_params(ctx, &function_source.value, arguments)
+            .ok_or(InlineError::GenericArgsNotNameable)?,
+    };
+    let body =
+        substitute_generic_args(&function_source.value, generic_args, caller_type_params, body)
+            .ok_or(InlineError::GenericArgsNotNameable)?;
+    let return_derefs = match return_coercion_derefs(ctx, function, function_source) {
+        Some(it) => it,
+        None => {
+            // The inlined body has the type of its tail expression, which the return type of the
+            // function used to coerce into something else.
+            mark::hit!(inline_function_return_coercion_not_reproducible);
+            return Err(InlineError::ReturnCoercionNotReproducible);
+        }
+    };
+    let body = apply_derefs_to_tail(
+        body,
+        return_derefs,
+        function.ret_type(ctx.db()).is_mutable_reference(),
+    );
+    Ok((rename_colliding_labels(call, body), return_derefs))
+}
+
+/// Checks that the inlined code is a valid const expression when the call is in a const context.
+/// `needs_bindings` tells whether the inlined code needs `let` statements.
+fn check_const_context(
+    call: &ast::Expr,
+    function: &ast::Fn,
+    debug_arguments: bool,
+    needs_bindings: bool,
+) -> Result<(), InlineError> {
+    if !is_in_const_context(call.syntax()) {
+        return Ok(());
+    }
+    if debug_arguments {
+        return Err(InlineError::ConstContext);
+    }
+    if function.const_token().is_none() {
+        // Only calls to `const fn`s are allowed here, and their bodies are the only ones
+        // guaranteed to be valid const expressions.
+        mark::hit!(inline_function_const_context_requires_const_fn);
+        return Err(InlineError::ConstContext);
+    }
+    if needs_bindings {
+        // The inlined body has to be a single const expression, there is no block to hold the
+        // `let` statements of the arguments and the body.
+        mark::hit!(inline_function_const_context_requires_single_expression);
+        return Err(InlineError::ConstContext);
+    }
+    Ok(())
+}
+
+/// The paths of the traits, macros and items the body needs to be imported at the call site.
+fn required_imports(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+) -> Result<Vec<ast::Path>, InlineError> {
+    let mut res = Vec::new();
+    res.extend(
+        required_trait_imports(ctx, call, function_source).ok_or(InlineError::ImportNotPossible)?,
+    );
+    res.extend(
+        required_macro_imports(ctx, call, function_source).ok_or(InlineError::ImportNotPossible)?,
+    );
+    res.extend(
+        required_item_imports(ctx, call, function_source).ok_or(InlineError::ImportNotPossible)?,
+    );
+    Ok(res)
+}
+
+/// Checks that the items and fields the body uses are visible from the call site, and, with
+/// `AssistConfig::verify_inlining`, that its paths resolve there.
+fn check_visible_at_call_site(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+    body: &ast::BlockExpr,
+    bound_names: FxHashSet<String>,
+    required_imports: &[ast::Path],
+) -> Result<(), InlineError> {
+    if !body_items_visible_at_call_site(ctx, call, function_source) {
+        // The inlined code would access items or fields that are private to the module of the
+        // function.
+        mark::hit!(inline_function_body_item_not_visible);
+        return Err(InlineError::BodyItemNotVisible);
+    }
+    if ctx.config.verify_inlining
+        && !paths_resolve_at_call_site(ctx, call, body, bound_names, required_imports)
+    {
+        // Some item the body refers to isn't there, or isn't visible, at the call site.
+        mark::hit!(inline_function_unresolved_path_at_call_site);
+        return Err(InlineError::UnresolvedPathAtCallSite);
+    }
+    Ok(())
+}
+
+/// Checks whether the inlined code needs an `unsafe` block, which the unsafe operations of an
+/// `unsafe fn` body do where the call isn't in an unsafe context already.
+fn needs_unsafe_block(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+) -> Result<bool, InlineError> {
+    let needs_unsafe_block =
+        function.has_unsafe_operations(ctx.db()) && !is_in_unsafe_context(call.syntax());
+    if needs_unsafe_block && forbids_unsafe_code(ctx, call.syntax()) {
+        mark::hit!(inline_function_unsafe_code_forbidden);
+        return Err(InlineError::UnsafeCodeForbidden);
+    }
+    Ok(needs_unsafe_block)
+}
+
+/// Checks that the paths of the body resolve at the call site, which catches the inlined code
+/// referring to items that are only visible from the function. The names bound by the `let`s of
+/// the inlined code, declared in the body itself, or brought into scope by `imports` are assumed
+/// to resolve. Only the paths are checked, the inlined code isn't type checked again.
+fn paths_resolve_at_call_site(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    body: &ast::BlockExpr,
+    mut local_names: FxHashSet<String>,
+    imports: &[ast::Path],
+) -> bool {
+    let body_syntax = body.syntax();
+    local_names.extend(
+        body_syntax.descendants().filter_map(ast::Name::cast).map(|it| it.text().to_string()),
+    );
+    local_names.extend(
+        body_syntax
+            .descendants()
+            .filter_map(ast::UseTree::cast)
+            .filter_map(|it| it.path()?.segment()?.name_ref())
+            .chain(imports.iter().filter_map(|it| it.segment()?.name_ref()))
+            .map(|it| it.text().to_string()),
+    );
+
+    let scope = ctx.sema.scope(call.syntax());
+    let resolves = |path: &ast::Path| scope.speculative_resolve(path).is_some();
+    body_syntax
+        .descendants()
+        .filter_map(ast::Path::cast)
+        .filter(|path| {
+            // Qualifiers are checked along with their paths, the paths of `use`s, attributes and
+            // macro calls resolve differently, and nested items have scopes of their own.
+            let parent_kind = path.syntax().parent().map(|it| it.kind());
+            !matches!(parent_kind, Some(PATH) | Some(USE_TREE) | Some(ATTR) | Some(MACRO_CALL))
+                && path
+                    .syntax()
+                    .ancestors()
+                    .take_while(|it| it != body_syntax)
+                    .all(|it| !ast::Item::can_cast(it.kind()))
+        })
+        .all(|path| {
+            let first_segment = iter::successors(Some(path.clone()), |it| it.qualifier())
+                .last()
+                .and_then(|it| it.segment()?.name_ref());
+            if first_segment.map_or(false, |it| local_names.contains(&it.text().to_string())) {
+                return true;
+            }
+            // The paths to associated items only resolve up to their type.
+            resolves(&path) || path.qualifier().map_or(false, |it| resolves(&it))
+        })
+}
+
+/// Renders the statements and the tail expression of a body returning `()`, to replace the
+/// statement `stmt` of the call. Bindings and items would leak into the enclosing block, so the
+/// statements stay in a block then, only the semicolon after it goes.
+fn block_statements_in_place_of(
+    stmt: &ast::ExprStmt,
+    body: &ast::BlockExpr,
+    statements: Vec<ast::Stmt>,
+    tail_expr: Option<ast::Expr>,
+) -> String {
+    let indent = IndentLevel::from_node(stmt.syntax());
+    let has_declarations = statements.iter().any(|stmt| match stmt {
+        ast::Stmt::LetStmt(_) => true,
+        ast::Stmt::Item(ast::Item::MacroCall(_)) | ast::Stmt::ExprStmt(_) => false,
+        ast::Stmt::Item(_) => true,
+    });
+    let block = block_with_comments(body, statements, tail_expr).reset_indent();
+    if has_declarations {
+        return block.indent(indent).to_string();
+    }
+    // Everything between the braces, with the tail expression as a statement of its own.
+    let block = block.dedent(IndentLevel(1)).indent(indent);
+    let tail_expr = block.tail_expr();
+    let mut elements = block
+        .syntax()
+        .children_with_tokens()
+        .filter(|it| !matches!(it.kind(), T!['{'] | T!['}']))
+        .collect::<Vec<_>>();
+    elements.remove(0);
+    elements.pop();
+    elements
+        .into_iter()
+        .map(|element| match (&element, &tail_expr) {
+            (SyntaxElement::Node(node), Some(tail_expr)) if node == tail_expr.syntax() => {
+                make::expr_stmt(tail_expr.clone()).to_string()
+            }
+            _ => element.to_string(),
+        })
+        .collect()
+}
+
+/// Builds the block of `statements` and `tail_expr` like `make::block_expr`, keeping the comments
+/// around them in `body`, the block they were taken from, and those before its closing brace.
+fn block_with_comments(
+    body: &ast::BlockExpr,
+    statements: Vec<ast::Stmt>,
+    tail_expr: Option<ast::Expr>,
+) -> ast::BlockExpr {
+    // The tail of a collapsed `return` is inside the statement the comments are around.
+    let originals = statements
+        .iter()
+        .map(|it| it.syntax().clone())
+        .chain(tail_expr.iter().map(|it| it.syntax().clone()))
+        .map(|it| it.ancestors().find(|it| it.parent().as_ref() == Some(body.syntax())))
+        .collect::<Vec<_>>();
+    let block = make::block_expr(statements, tail_expr);
+    let elements = body_statements(&block)
+        .map(|it| it.syntax().clone())
+        .chain(block.tail_expr().map(|it| it.syntax().clone()))
+        .collect::<Vec<_>>();
+    if originals.len() != elements.len() {
+        return block;
+    }
+
+    let mut rewriter = SyntaxRewriter::default();
+    let mut after_last = Vec::new();
+    for (i, (original, element)) in originals.iter().zip(&elements).enumerate() {
+        let original = match original {
+            Some(it) => SyntaxElement::from(it.clone()),
+            None => continue,
+        };
+        let mut leading = Vec::new();
+        for comment in leading_comments(&original) {
+            leading.push(SyntaxElement::from(comment));
+            leading.push(make::tokens::whitespace("\n    ").into());
+        }
+        rewriter.insert_many_before(element, leading);
+        let mut trailing = Vec::new();
+        if let Some(comment) = trailing_comment(&original) {
+            trailing.push(make::tokens::single_space().into());
+            trailing.push(SyntaxElement::from(comment));
+        }
+        if i + 1 == elements.len() {
+            after_last = trailing;
+        } else {
+            rewriter.insert_many_after(element, trailing);
+        }
+    }
+    for comment in body.r_curly_token().map(|it| leading_comments(&it.into())).unwrap_or_default() {
+        after_last.push(make::tokens::whitespace("\n    ").into());
+        after_last.push(SyntaxElement::from(comment));
+    }
+    let last = match elements.last() {
+        Some(it) => Some(SyntaxElement::from(it.clone())),
+        None => block.l_curly_token().map(SyntaxElement::from),
+    };
+    if let Some(last) = last {
+        rewriter.insert_many_after(&last, after_last);
+    }
+    rewriter.rewrite_ast(&block)
+}
+
+/// Returns the comments on the lines before `element`, up to the previous element, the comment at
+/// the end of the line of that element belongs to it.
+fn leading_comments(element: &SyntaxElement) -> Vec<SyntaxToken> {
+    let trivia = iter::successors(element.prev_sibling_or_token(), |it| it.prev_sibling_or_token())
+        .take_while(|it| it.kind().is_trivia())
+        .collect::<Vec<_>>();
+    let previous = trivia.last().unwrap_or(element).prev_sibling_or_token();
+    let previous_trailing =
+        previous.filter(|it| it.kind() != T!['{']).and_then(|it| trailing_comment(&it));
+    trivia
+        .into_iter()
+        .rev()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == COMMENT && Some(it) != previous_trailing.as_ref())
+        .collect()
+}
+
+/// Returns the comment after `element` on the same line.
+fn trailing_comment(element: &SyntaxElement) -> Option<SyntaxToken> {
+    let mut next = element.next_sibling_or_token()?;
+    if next.kind() == WHITESPACE {
+        if next.to_string().contains('\n') {
+            return None;
+        }
+        next = next.next_sibling_or_token()?;
+    }
+    next.into_token().filter(|it| it.kind() == COMMENT)
+}
+
+/// A call of the function to inline, either through a path, like `foo(x)`, or a method call,
+/// like `x.foo()`, which passes the receiver as the `self` parameter.
+struct InlinedCall {
+    expr: ast::Expr,
+    name: String,
+    function: hir::Function,
+    /// The turbofish of the call, like the `::<u32>` of `parse::<u32>(s)`.
+    generic_args: Option<ast::GenericArgList>,
+    receiver: Option<ast::Expr>,
+    arguments: Vec<ast::Expr>,
+}
+
+fn inlined_call(ctx: &AssistContext) -> Option<InlinedCall> {
+    let path_call = ctx
+        .find_node_at_offset::<ast::PathExpr>()
+        .and_then(|it| Some((it.path()?, it.syntax().parent().and_then(ast::CallExpr::cast)?)));
+    if let Some((path, call)) = path_call {
+        return inlined_path_call(ctx, path, call);
+    }
+
+    let method_call = ctx.find_node_at_offset::<ast::MethodCallExpr>()?;
+    let name_ref = method_call.name_ref()?;
+    // Only offer it on the name of the method, not on the receiver or the arguments.
+    if !name_ref.syntax().text_range().contains_inclusive(ctx.offset()) {
         return None;
     }
+    inlined_method_call(ctx, method_call)
+}
 
-    let new_bindings = parameters.into_iter().zip(arguments);
+/// Returns the call `name_ref` is the name of the called function of, if any.
+fn inlined_call_of(ctx: &AssistContext, name_ref: &ast::NameRef) -> Option<InlinedCall> {
+    let parent = name_ref.syntax().parent()?;
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return inlined_method_call(ctx, method_call);
+    }
+    let path = ast::PathSegment::cast(parent)?.parent_path();
+    let call = path.syntax().parent().and_then(ast::PathExpr::cast)?.syntax().parent()?;
+    inlined_path_call(ctx, path, ast::CallExpr::cast(call)?)
+}
 
-    let body = function_source.value.body()?;
+fn inlined_path_call(
+    ctx: &AssistContext,
+    path: ast::Path,
+    call: ast::CallExpr,
+) -> Option<InlinedCall> {
+    let db = ctx.db();
+    let function = match ctx.sema.resolve_path(&path)? {
+        PathResolution::Def(hir::ModuleDef::Function(f)) => f,
+        PathResolution::AssocItem(hir::AssocItem::Function(f)) if f.self_param(db).is_none() => f,
+        _ => return None,
+    };
+    let qualifier = match path.qualifier().and_then(|it| ctx.sema.resolve_path(&it)) {
+        Some(PathResolution::Def(hir::ModuleDef::Adt(it))) => Some(it),
+        _ => None,
+    };
+    let function = qualifier
+        .and_then(|adt| trait_impl_function(ctx, call.syntax(), adt, function))
+        .unwrap_or(function);
+    Some(InlinedCall {
+        name: path.to_string(),
+        function,
+        generic_args: path.segment()?.generic_arg_list(),
+        receiver: None,
+        arguments: call.arg_list()?.args().collect(),
+        expr: call.into(),
+    })
+}
 
-    acc.add(
-        AssistId("inline_function", AssistKind::RefactorInline),
-        format!("Inline `{}`", path),
-        call.syntax().text_range(),
-        |builder| {
-            let mut statements: Vec<ast::Stmt> = Vec::new();
+fn inlined_method_call(
+    ctx: &AssistContext,
+    method_call: ast::MethodCallExpr,
+) -> Option<InlinedCall> {
+    let name_ref = method_call.name_ref()?;
+    let function = ctx.sema.resolve_method_call(&method_call)?;
+    let receiver = method_call.receiver()?;
+    let receiver_ty = ctx.sema.type_of_expr(&receiver)?;
+    let function = receiver_ty
+        .remove_ref()
+        .unwrap_or(receiver_ty)
+        .as_adt()
+        .and_then(|adt| trait_impl_function(ctx, method_call.syntax(), adt, function))
+        .unwrap_or(function);
+    Some(InlinedCall {
+        name: name_ref.to_string(),
+        function,
+        generic_args: method_call.generic_arg_list(),
+        receiver: Some(receiver),
+        arguments: method_call.arg_list()?.args().collect(),
+        expr: method_call.into(),
+    })
+}
+
+/// Returns the function implementing the trait function `function` for `adt`, for calls like
+/// `Counter::first()` or `counter.first()`, which resolve to the trait's declaration.
+fn trait_impl_function(
+    ctx: &AssistContext,
+    call: &SyntaxNode,
+    adt: hir::Adt,
+    function: hir::Function,
+) -> Option<hir::Function> {
+    let db = ctx.db();
+    let trait_ = match function.as_assoc_item(db)?.container(db) {
+        AssocItemContainer::Trait(it) => it,
+        AssocItemContainer::Impl(_) => return None,
+    };
+    let krate = ctx.sema.scope(call).module()?.krate();
+    let name = function.name(db);
+    hir::Impl::for_trait(db, krate, trait_)
+        .into_iter()
+        .filter(|impl_| impl_.target_ty(db).as_adt() == Some(adt))
+        .flat_map(|impl_| impl_.items(db))
+        .find_map(|item| match item {
+            hir::AssocItem::Function(it) if it.name(db) == name => Some(it),
+            _ => None,
+        })
+}
+
+/// The receiver of an inlined method call, which is passed to the body in place of `self`.
+struct InlinedReceiver {
+    /// The receiver as written at the call site.
+    expr: ast::Expr,
+    /// The receiver, explicitly borrowed like the `self` parameter.
+    value: ast::Expr,
+    access: hir::Access,
+}
+
+impl InlinedReceiver {
+    fn new(ctx: &AssistContext, function: hir::Function, expr: ast::Expr) -> Option<Self> {
+        let db = ctx.db();
+        let access = function.self_param(db)?.access(db);
+        let self_ty = match function.as_assoc_item(db)?.container(db) {
+            AssocItemContainer::Impl(it) => it.target_ty(db),
+            AssocItemContainer::Trait(_) => {
+                // The body of a provided method is generic over the implementing type.
+                mark::hit!(inline_function_generic_self);
+                return None;
+            }
+        };
+        let self_adt = self_ty.as_adt()?;
+        let receiver_ty = ctx.sema.type_of_expr(&expr)?;
+        let is_self = |ty: &hir::Type| ty.as_adt() == Some(self_adt);
+        let is_reference_to_self = receiver_ty.remove_ref().map_or(false, |it| is_self(&it));
+        let value = match access {
+            hir::Access::Owned if is_self(&receiver_ty) => expr.clone(),
+            hir::Access::Shared if is_self(&receiver_ty) => make::expr_ref(expr.clone(), false),
+            hir::Access::Shared if is_reference_to_self => expr.clone(),
+            hir::Access::Exclusive if is_self(&receiver_ty) => make::expr_ref(expr.clone(), true),
+            // Reborrowing keeps the receiver usable after the call, like the method call did.
+            hir::Access::Exclusive
+                if is_reference_to_self && receiver_ty.is_mutable_reference() =>
+            {
+                make::expr_ref(make::expr_prefix(T![*], expr.clone()), true)
+            }
+            _ => {
+                // Smart pointers and references to references are dereferenced by the method
+                // call, which can't be spelled out in general.
+                mark::hit!(inline_function_receiver_needs_autoderef);
+                return None;
+            }
+        };
+        Some(InlinedReceiver { expr, value, access })
+    }
+
+    /// Checks whether the function returns a reference while the receiver is a borrow of a
+    /// temporary value, like the arguments in [`returns_borrow_of_temporary_argument`].
+    fn is_returned_borrow_of_temporary(&self, function_source: &InFile<ast::Fn>) -> bool {
+        let returns_reference = function_source
+            .value
+            .ret_type()
+            .and_then(|ret_type| ret_type.ty())
+            .map_or(false, |ty| ty.syntax().descendants().any(|it| it.kind() == REF_TYPE));
+        returns_reference
+            && !matches!(self.access, hir::Access::Owned)
+            && !matches!(
+                self.expr,
+                ast::Expr::PathExpr(_)
+                    | ast::Expr::FieldExpr(_)
+                    | ast::Expr::IndexExpr(_)
+                    | ast::Expr::Literal(_)
+            )
+    }
+
+    /// Replaces the usages of `self` in the body, returning the binding of the receiver if it
+    /// needs one.
+    ///
+    /// Borrowed places, like locals, are used in place of `self` directly as long as they are
+    /// only used to access fields and to call methods, which borrow them automatically.
+    fn bind_in(
+        &self,
+        ctx: &AssistContext,
+        call: &ast::Expr,
+        body: ast::BlockExpr,
+    ) -> (Option<(ast::Pat, ast::Expr)>, ast::BlockExpr) {
+        let self_paths = body
+            .syntax()
+            .descendants()
+            .filter_map(ast::PathExpr::cast)
+            .filter(|it| {
+                it.path().map_or(false, |path| {
+                    path.qualifier().is_none()
+                        && path.segment().and_then(|it| it.kind())
+                            == Some(ast::PathSegmentKind::SelfKw)
+                })
+            })
+            .collect::<Vec<_>>();
+        let is_place = matches!(self.expr, ast::Expr::PathExpr(_) | ast::Expr::FieldExpr(_))
+            && is_side_effect_free(&self.expr);
+        if self_paths.is_empty() {
+            // The receiver is still evaluated.
+            let binding = match is_place {
+                true => None,
+                false => Some((make::wildcard_pat().into(), self.value.clone())),
+            };
+            return (binding, body);
+        }
+
+        let is_autoref_receiver = |path: &ast::PathExpr| {
+            path.syntax().parent().map_or(false, |parent| {
+                match_ast! {
+                    match parent {
+                        ast::MethodCallExpr(it) => it.receiver().map_or(false, |it| it.syntax() == path.syntax()),
+                        ast::FieldExpr(_it) => true,
+                        _ => false,
+                    }
+                }
+            })
+        };
+        // The names of the receiver must not be shadowed by the bindings of the body.
+        let body_bindings = body
+            .syntax()
+            .descendants()
+            .filter_map(ast::IdentPat::cast)
+            .filter_map(|it| it.name())
+            .map(|it| it.text().to_string())
+            .collect::<FxHashSet<_>>();
+        let is_shadowed = self
+            .expr
+            .syntax()
+            .descendants()
+            .filter_map(ast::NameRef::cast)
+            .any(|it| body_bindings.contains(it.text().as_str()));
+        let (binding, replacement) = if !matches!(self.access, hir::Access::Owned)
+            && is_place
+            && !is_shadowed
+            && self_paths.iter().all(is_autoref_receiver)
+        {
+            (None, self.expr.clone())
+        } else {
+            let name = ctx.suggest_name_avoiding("this", call.syntax(), &identifiers(&body));
+            let pattern = make::ident_pat(name.clone()).into();
+            let path = make::expr_path(make::path_unqualified(make::path_segment(make::name_ref(
+                &name.text(),
+            ))));
+            (Some((pattern, self.value.clone())), path)
+        };
+        let mut rewriter = SyntaxRewriter::default();
+        for path in self_paths {
+            rewriter.replace(path.syntax(), replacement.syntax());
+        }
+        (binding, rewriter.rewrite_ast(&body))
+    }
+}
+
+/// The identifiers used in the body, which a name introduced around it must not shadow.
+fn identifiers(body: &ast::BlockExpr) -> FxHashSet<String> {
+    body.syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == IDENT)
+        .map(|it| it.text().to_string())
+        .collect()
+}
+
+/// Rewrites the `self::` and `super::` paths of the body, which are relative to the module of
+/// the function, into paths to the same items from the module of the call. Paths starting with
+/// `crate::` are valid anywhere in the crate, so they are left alone.
+///
+/// Returns `None` if some of the items aren't visible from the call site.
+fn rewrite_relative_paths(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+    body: ast::BlockExpr,
+) -> Option<ast::BlockExpr> {
+    let is_relative = |path: &ast::Path| {
+        let mut first = path.clone();
+        while let Some(qualifier) = first.qualifier() {
+            first = qualifier;
+        }
+        // A lone `self` is the receiver, not a module.
+        first != *path
+            && matches!(
+                first.segment().and_then(|it| it.kind()),
+                Some(ast::PathSegmentKind::SelfKw) | Some(ast::PathSegmentKind::SuperKw)
+            )
+    };
+    let relative_paths: Vec<ast::Path> = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Path::cast)
+        .filter(|path| path.syntax().parent().and_then(ast::Path::cast).is_none())
+        .filter(is_relative)
+        .collect();
+    if relative_paths.is_empty() {
+        return Some(body);
+    }
+
+    let db = ctx.db();
+    let module = ctx.sema.scope(call.syntax()).module()?;
+    if function.module(db) == module {
+        return Some(body);
+    }
+
+    let analyzed_function = analyzed_function(ctx, function_source)?;
+    let mut rewriter = SyntaxRewriter::default();
+    for path in relative_paths {
+        let analyzed_path: ast::Path =
+            find_node_at_range(analyzed_function.syntax(), path.syntax().text_range())?;
+        // Only the module prefix of the path is replaced, the rest may hold generic arguments.
+        let (prefix, target_module) = iter::successors(Some(analyzed_path.clone()), |it| {
+            it.qualifier()
+        })
+        .find_map(|it| match ctx.sema.resolve_path(&it)? {
+            PathResolution::Def(hir::ModuleDef::Module(module)) => Some((it, module)),
+            _ => None,
+        })?;
+        let is_visible = match ctx.sema.resolve_path(&analyzed_path) {
+            Some(PathResolution::Def(def)) if prefix != analyzed_path => target_module
+                .visibility_of(db, &def)
+                .map_or(true, |vis| vis.is_visible_from(db, module.into())),
+            _ => true,
+        };
+        let new_prefix = match module.find_use_path(db, hir::ModuleDef::Module(target_module)) {
+            Some(it) if is_visible => mod_path_to_ast(&it),
+            _ => {
+                mark::hit!(inline_function_relative_path_not_visible);
+                return None;
+            }
+        };
+        let prefix: ast::Path = find_node_at_range(body.syntax(), prefix.syntax().text_range())?;
+        rewriter.replace(prefix.syntax(), new_prefix.syntax());
+    }
+    Some(rewriter.rewrite_ast(&body))
+}
+
+/// Checks that the items, fields and methods the body uses are visible from the module of the
+/// call, including the private fields of a struct. The items declared in the body are inlined
+/// along with it, so they are always accessible.
+fn body_items_visible_at_call_site(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+) -> bool {
+    let db = ctx.db();
+    let module = match ctx.sema.scope(call.syntax()).module() {
+        Some(it) => it,
+        None => return true,
+    };
+    let body = match analyzed_function(ctx, function_source).and_then(|it| it.body()) {
+        Some(it) => it,
+        None => return true,
+    };
+    let body_items = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Item::cast)
+        .filter_map(|it| it.syntax().children().find_map(ast::Name::cast))
+        .map(|it| it.text().to_string())
+        .collect::<FxHashSet<_>>();
+    let is_visible = |visibility: Option<hir::Visibility>| {
+        visibility.map_or(true, |it| it.is_visible_from(db, module.into()))
+    };
+    body.syntax()
+        .descendants()
+        .filter(|node| {
+            // Nested items can only be used in the inlined code through their public interface.
+            !node
+                .ancestors()
+                .take_while(|it| it != body.syntax())
+                .any(|it| ast::Item::can_cast(it.kind()))
+        })
+        .all(|node| {
+            match_ast! {
+                match node {
+                    ast::Path(path) => {
+                        let is_body_item = path.qualifier().is_none()
+                            && path
+                                .segment()
+                                .and_then(|it| it.name_ref())
+                                .map_or(false, |it| body_items.contains(it.text().as_str()));
+                        is_body_item || match ctx.sema.resolve_path(&path) {
+                            Some(PathResolution::Def(def)) => {
+                                is_visible(def.definition_visibility(db))
+                            }
+                            Some(PathResolution::AssocItem(assoc)) => {
+                                assoc.is_visible_from(db, module)
+                            }
+                            _ => true,
+                        }
+                    },
+                    ast::FieldExpr(it) => ctx
+                        .sema
+                        .resolve_field(&it)
+                        .map_or(true, |field| field.is_visible_from(db, module)),
+                    ast::RecordExprField(it) => ctx
+                        .sema
+                        .resolve_record_field(&it)
+                        .map_or(true, |(field, _)| field.is_visible_from(db, module)),
+                    ast::RecordPatField(it) => ctx
+                        .sema
+                        .resolve_record_pat_field(&it)
+                        .map_or(true, |field| field.is_visible_from(db, module)),
+                    ast::MethodCallExpr(it) => ctx
+                        .sema
+                        .resolve_method_call(&it)
+                        .map_or(true, |function| function.is_visible_from(db, module)),
+                    _ => true,
+                }
+            }
+        })
+}
+
+/// Replaces the `Self` paths of an associated function's body with paths to the implementing
+/// type that resolve at the call site.
+///
+/// Returns `None` if that isn't possible, or if some of the items accessed through `Self` are
+/// not visible at the call site.
+fn replace_self_paths(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+    body: ast::BlockExpr,
+) -> Option<ast::BlockExpr> {
+    let is_self = |path: &ast::Path| {
+        path.qualifier().is_none()
+            && path.segment().and_then(|it| it.name_ref()).map_or(false, |it| it.text() == "Self")
+    };
+    let self_paths: Vec<ast::Path> =
+        body.syntax().descendants().filter_map(ast::Path::cast).filter(is_self).collect();
+    if self_paths.is_empty() {
+        return Some(body);
+    }
+
+    let db = ctx.db();
+    let impl_ = match function.as_assoc_item(db)?.container(db) {
+        AssocItemContainer::Impl(it) => it,
+        AssocItemContainer::Trait(_) => return None,
+    };
+    if !hir::GenericDef::from(impl_).type_params(db).is_empty() {
+        // `Self` stands for the type with the arguments of the impl, which the call site may not
+        // be able to name.
+        mark::hit!(inline_function_self_of_generic_impl);
+        return None;
+    }
+    let adt = impl_.target_ty(db).as_adt()?;
+    let module = ctx.sema.scope(call.syntax()).module()?;
+    let adt_path = module.find_use_path(db, hir::ModuleDef::Adt(adt))?;
+    let adt_path = mod_path_to_ast(&adt_path);
+
+    // `Adt::Assoc` doesn't name an associated type of a trait implementation, so paths to those
+    // are replaced by the concrete types, keyed by the position of their `Self` path. The body
+    // may have been rewritten already, but without adding or removing `Self` paths.
+    let mut assoc_types = FxHashMap::default();
+    let function = analyzed_function(ctx, function_source)?;
+    let analyzed_paths = function.body()?.syntax().descendants().filter_map(ast::Path::cast);
+    for (idx, path) in analyzed_paths.filter(is_self).enumerate() {
+        let parent_path = match path.syntax().parent().and_then(ast::Path::cast) {
+            Some(it) => it,
+            None => continue,
+        };
+        if let Some(PathResolution::AssocItem(assoc)) = ctx.sema.resolve_path(&parent_path) {
+            if !assoc.is_visible_from(db, module) {
+                mark::hit!(inline_function_self_item_not_visible);
+                return None;
+            }
+            continue;
+        }
+
+        // Paths relative to a type aren't resolved, so look the associated type up in the impl.
+        let name = match parent_path.segment().and_then(|it| it.name_ref()) {
+            Some(it) => it,
+            None => continue,
+        };
+        let alias = impl_.items(db).into_iter().find_map(|item| match item {
+            hir::AssocItem::TypeAlias(it) if it.name(db).to_string() == name.text().as_str() => {
+                Some(it)
+            }
+            _ => None,
+        });
+        if let Some(alias) = alias {
+            let is_type = parent_path.syntax().parent().map_or(false, |it| it.kind() == PATH_TYPE);
+            let ty = match alias.ty(db).display_source_code(db, module.into()) {
+                Ok(ty) if is_type => make::ty(&ty),
+                _ => {
+                    mark::hit!(inline_function_assoc_type_not_nameable);
+                    return None;
+                }
+            };
+            assoc_types.insert(idx, ty);
+        }
+    }
+
+    let mut rewriter = SyntaxRewriter::default();
+    for (idx, path) in self_paths.into_iter().enumerate() {
+        let parent_path = path.syntax().parent().and_then(ast::Path::cast);
+        let assoc_type = parent_path.and_then(|it| {
+            let ty = assoc_types.get(&idx)?;
+            Some((it.syntax().parent()?, ty))
+        });
+        match assoc_type {
+            Some((path_type, ty)) => rewriter.replace(&path_type, ty.syntax()),
+            None => rewriter.replace(path.syntax(), adt_path.syntax()),
+        }
+    }
+    Some(rewriter.rewrite_ast(&body))
+}
+
+fn function_parameter_patterns(value: &ast::Fn) -> Option<Vec<ast::Pat>> {
+    let mut patterns = Vec::new();
+
+    for param in value.param_list()?.params() {
+        let pattern = param.pat()?;
+        patterns.push(pattern);
+    }
+
+    Some(patterns)
+}
+
+/// Turns a `return` at the end of the body into its tail expression, it would return from the
+/// function around the call once inlined.
+fn collapse_tail_return(body: ast::BlockExpr) -> ast::BlockExpr {
+    let tail_return = match body.tail_expr() {
+        Some(ast::Expr::ReturnExpr(it)) => it,
+        Some(_) => return body,
+        None => match body_statements(&body).last() {
+            Some(ast::Stmt::ExprStmt(stmt)) => match stmt.expr() {
+                Some(ast::Expr::ReturnExpr(it)) => it,
+                _ => return body,
+            },
+            _ => return body,
+        },
+    };
+    let statements = body_statements(&body)
+        .filter(|stmt| {
+            !stmt.syntax().text_range().contains_range(tail_return.syntax().text_range())
+        })
+        .collect();
+    block_with_comments(&body, statements, tail_return.expr())
+}
+
+/// Replaces the type and const parameters of the function mentioned in the body with the generic
+/// arguments of the call, like the `T` of `s.parse::<T>()` with the `u32` of `parse::<u32>(s)`.
+///
+/// Without a turbofish, the parameters the caller passes its own type parameters for are replaced
+/// with those, as found by `caller_type_params`.
+///
+/// Returns `None` if the body mentions parameters the call doesn't specify, the inlined body would
+/// leave them to inference at the call site.
+fn substitute_generic_args(
+    function: &ast::Fn,
+    generic_args: Option<ast::GenericArgList>,
+    caller_type_params: FxHashMap<String, ast::Type>,
+    body: ast::BlockExpr,
+) -> Option<ast::BlockExpr> {
+    let params = function
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|it| it.generic_params())
+        .filter_map(|param| match param {
+            ast::GenericParam::TypeParam(it) => it.name(),
+            ast::GenericParam::ConstParam(it) => it.name(),
+            ast::GenericParam::LifetimeParam(_) => None,
+        })
+        .map(|it| it.text().to_string())
+        .collect::<Vec<_>>();
+    let mentions = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Path::cast)
+        .filter_map(|path| {
+            let segment = path.segment()?;
+            let name = segment.name_ref()?.text().to_string();
+            let is_param = path.qualifier().is_none()
+                && segment.generic_arg_list().is_none()
+                && params.contains(&name);
+            // Nested items can't use the parameters of the function.
+            let is_in_item = path
+                .syntax()
+                .ancestors()
+                .take_while(|it| it != body.syntax())
+                .any(|it| ast::Item::can_cast(it.kind()));
+            if is_param && !is_in_item {
+                Some((path, name))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if mentions.is_empty() {
+        return Some(body);
+    }
+
+    let args = match generic_args {
+        Some(generic_args) => {
+            let args = generic_args
+                .generic_args()
+                .filter_map(|arg| match arg {
+                    ast::GenericArg::TypeArg(it) => it.ty().map(Either::Left),
+                    ast::GenericArg::ConstArg(it) => it.expr().map(Either::Right),
+                    ast::GenericArg::LifetimeArg(_) | ast::GenericArg::AssocTypeArg(_) => None,
+                })
+                .collect::<Vec<_>>();
+            let is_pinned = |arg: &Either<ast::Type, ast::Expr>| {
+                !matches!(arg, Either::Left(ast::Type::InferType(_)))
+            };
+            if args.len() != params.len() || !args.iter().all(is_pinned) {
+                mark::hit!(inline_function_generic_args_not_pinned);
+                return None;
+            }
+            params.into_iter().zip(args).collect::<FxHashMap<_, _>>()
+        }
+        None => {
+            if !mentions.iter().all(|(_, name)| caller_type_params.contains_key(name)) {
+                mark::hit!(inline_function_generic_args_not_pinned);
+                return None;
+            }
+            caller_type_params.into_iter().map(|(name, ty)| (name, Either::Left(ty))).collect()
+        }
+    };
 
-            for (pattern, value) in new_bindings {
-                statements.push(make::let_stmt(pattern, Some(value)).into());
+    let mut rewriter = SyntaxRewriter::default();
+    for (path, name) in mentions {
+        let parent = path.syntax().parent()?;
+        match (&args[&name], parent.kind()) {
+            (Either::Left(ty), PATH_TYPE) => {
+                rewriter.replace(&parent, make::ty(&ty.to_string()).syntax())
+            }
+            // Associated items of the type, like `T::default()`.
+            (Either::Left(ty), PATH) => {
+                let path = ast::Path::cast(parent)?;
+                let qualifier = match ty {
+                    ast::Type::PathType(it)
+                        if it.path()?.segment()?.generic_arg_list().is_none() =>
+                    {
+                        ty.to_string()
+                    }
+                    _ => format!("<{}>", ty),
+                };
+                let new_path = make::path_from_text(&format!("{}::{}", qualifier, path.segment()?));
+                rewriter.replace(path.syntax(), new_path.syntax());
+            }
+            (Either::Right(expr), PATH_EXPR) => rewriter.replace(&parent, expr.syntax()),
+            _ => return None,
+        }
+    }
+    Some(rewriter.rewrite_ast(&body))
+}
+
+/// Maps the type parameters of the function to the type parameters of the caller passed for them,
+/// like the `T` of `fn wrap<T>(x: T)` to the `U` of `wrap(u)` inside a `fn caller<U>(u: U)`.
+///
+/// Returns `None` if a parameter is passed different type parameters of the caller.
+fn caller_type_params(
+    ctx: &AssistContext,
+    function: &ast::Fn,
+    arguments: &[ast::Expr],
+) -> Option<FxHashMap<String, ast::Type>> {
+    let type_params = function
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|it| it.type_params())
+        .filter_map(|it| it.name())
+        .map(|it| it.text().to_string())
+        .collect::<Vec<_>>();
+    let mut mapping = FxHashMap::default();
+    if type_params.is_empty() {
+        return Some(mapping);
+    }
+    let params = function.param_list().into_iter().flat_map(|it| it.params());
+    for (param, argument) in params.zip(arguments) {
+        let (mut param_ty, mut argument_ty) = match (param.ty(), ctx.sema.type_of_expr(argument)) {
+            (Some(param_ty), Some(argument_ty)) => (param_ty, argument_ty),
+            _ => continue,
+        };
+        // Look through the references of both, so that `&U` is passed for a `&T`.
+        while let ast::Type::RefType(ref_type) = &param_ty {
+            match (ref_type.ty(), argument_ty.remove_ref()) {
+                (Some(inner), Some(argument_inner)) => {
+                    param_ty = inner;
+                    argument_ty = argument_inner;
+                }
+                _ => break,
+            }
+        }
+        let name = match &param_ty {
+            ast::Type::PathType(it) => it
+                .path()
+                .filter(|path| path.qualifier().is_none())
+                .and_then(|path| path.segment())
+                .filter(|segment| segment.generic_arg_list().is_none())
+                .and_then(|segment| segment.name_ref())
+                .map(|it| it.text().to_string()),
+            _ => None,
+        };
+        let name = match name {
+            Some(it) if type_params.contains(&it) => it,
+            _ => continue,
+        };
+        let caller_param = match argument_ty.as_type_param() {
+            // The parameters of `impl Trait` arguments can't be named.
+            Some(it) if !it.is_implicit(ctx.db()) => it.name(ctx.db()).to_string(),
+            _ => continue,
+        };
+        match mapping.get(&name) {
+            Some(mapped) if mapped.to_string() != caller_param => {
+                mark::hit!(inline_function_caller_type_params_ambiguous);
+                return None;
+            }
+            Some(_) => {}
+            None => {
+                mapping.insert(name, make::ty(&caller_param));
             }
+        }
+    }
+    Some(mapping)
+}
+
+/// Checks whether the body has a `return` other than those of its closures, async blocks and
+/// nested items.
+fn has_early_return(body: &ast::BlockExpr) -> bool {
+    body.syntax().descendants().filter_map(ast::ReturnExpr::cast).any(|return_expr| {
+        !return_expr.syntax().ancestors().take_while(|it| it != body.syntax()).any(|it| {
+            ast::ClosureExpr::can_cast(it.kind())
+                || ast::Item::can_cast(it.kind())
+                || ast::EffectExpr::cast(it).map_or(false, |it| it.async_token().is_some())
+        })
+    })
+}
+
+/// Returns the statements of the block, without the tail expression.
+///
+/// A macro call in tail position is both an item and an expression, so it would be listed as a
+/// statement as well.
+fn body_statements(body: &ast::BlockExpr) -> impl Iterator<Item = ast::Stmt> {
+    let tail_expr = body.tail_expr();
+    body.statements().filter(move |stmt| {
+        tail_expr.as_ref().map_or(true, |tail_expr| stmt.syntax() != tail_expr.syntax())
+    })
+}
+
+/// Returns the argument of the call if the function just returns its sole parameter.
+fn identity_argument(function: &ast::Fn, arguments: &[ast::Expr]) -> Option<ast::Expr> {
+    let mut params = function.param_list()?.params();
+    let param = params.next()?;
+    if params.next().is_some() {
+        return None;
+    }
+    let param_name = match param.pat()? {
+        ast::Pat::IdentPat(pat)
+            if pat.ref_token().is_none() && pat.mut_token().is_none() && pat.pat().is_none() =>
+        {
+            pat.name()?
+        }
+        _ => return None,
+    };
+
+    let body = function.body()?;
+    if body_statements(&body).next().is_some() {
+        return None;
+    }
+    let path = match body.tail_expr()? {
+        ast::Expr::PathExpr(path_expr) => path_expr.path()?,
+        _ => return None,
+    };
+    if path.qualifier().is_some() || path.segment()?.generic_arg_list().is_some() {
+        return None;
+    }
+    if path.segment()?.name_ref()?.text() != param_name.text() {
+        return None;
+    }
+
+    arguments.first().cloned()
+}
+
+/// Checks that the types of the arguments passed for parameters typed with a generic parameter of
+/// the function implement the traits that parameter is bound by.
+///
+/// Bounds on generic traits are not checked, as their arguments are not known here.
+fn argument_types_satisfy_bounds(
+    ctx: &AssistContext,
+    function: hir::Function,
+    function_source: &ast::Fn,
+    arguments: &[ast::Expr],
+) -> bool {
+    let db = ctx.db();
+    let type_params = hir::GenericDef::Function(function).type_params(db);
+    let params = match function_source.param_list() {
+        Some(it) => it.params(),
+        None => return true,
+    };
+    params.zip(arguments).all(|(param, argument)| {
+        let param_ty = match param.ty() {
+            Some(ast::Type::PathType(it)) => it.path(),
+            _ => None,
+        };
+        let type_param = param_ty
+            .filter(|path| path.qualifier().is_none())
+            .and_then(|path| path.segment()?.name_ref())
+            .and_then(|name_ref| {
+                type_params.iter().find(|it| it.name(db).to_string() == name_ref.text().as_str())
+            });
+        let (type_param, argument_ty) = match (type_param, ctx.sema.type_of_expr(argument)) {
+            (Some(type_param), Some(ty)) if !ty.is_unknown() => (type_param, ty),
+            _ => return true,
+        };
+        type_param
+            .trait_bounds(db)
+            .into_iter()
+            .filter(|trait_| {
+                hir::GenericDef::Trait(*trait_).type_params(db).iter().all(|it| it.is_implicit(db))
+            })
+            .all(|trait_| argument_ty.impls_trait(db, trait_, &[]))
+    })
+}
+
+/// Checks whether the function returns a reference while some of its reference parameters are
+/// bound to a borrow of a temporary value.
+fn returns_borrow_of_temporary_argument(function: &ast::Fn, arguments: &[ast::Expr]) -> bool {
+    let returns_reference = function
+        .ret_type()
+        .and_then(|ret_type| ret_type.ty())
+        .map_or(false, |ty| ty.syntax().descendants().any(|it| it.kind() == REF_TYPE));
+    if !returns_reference {
+        return false;
+    }
+
+    let params = match function.param_list() {
+        Some(it) => it.params(),
+        None => return false,
+    };
+    params.zip(arguments).any(|(param, argument)| {
+        let is_ref_param = matches!(param.ty(), Some(ast::Type::RefType(_)));
+        let borrows_temporary = match argument {
+            ast::Expr::RefExpr(ref_expr) => !matches!(
+                ref_expr.expr(),
+                Some(ast::Expr::PathExpr(_))
+                    | Some(ast::Expr::FieldExpr(_))
+                    | Some(ast::Expr::IndexExpr(_))
+                    | Some(ast::Expr::Literal(_))
+            ),
+            _ => false,
+        };
+        is_ref_param && borrows_temporary
+    })
+}
+
+/// Whether `node` is in an `unsafe` block or in the body of an `unsafe fn`. Closures take the
+/// context they are written in.
+fn is_in_unsafe_context(node: &SyntaxNode) -> bool {
+    for ancestor in node.ancestors().skip(1) {
+        match_ast! {
+            match ancestor {
+                ast::EffectExpr(it) => if it.unsafe_token().is_some() {
+                    return true;
+                },
+                ast::Fn(it) => return it.unsafe_token().is_some(),
+                ast::Item(_it) => return false,
+                _ => (),
+            }
+        }
+    }
+    false
+}
+
+/// Whether the `unsafe_code` lint is denied for the function containing `node`, or for one of
+/// its modules.
+fn forbids_unsafe_code(ctx: &AssistContext, node: &SyntaxNode) -> bool {
+    let db = ctx.db();
+    let function = node.ancestors().find_map(ast::Fn::cast).and_then(|it| ctx.sema.to_def(&it));
+    if function.map_or(false, |it| it.attrs(db).forbids_unsafe_code()) {
+        return true;
+    }
+    let module = match ctx.sema.scope(node).module() {
+        Some(it) => it,
+        None => return false,
+    };
+    module.path_to_root(db).into_iter().any(|it| it.attrs(db).forbids_unsafe_code())
+}
+
+/// Checks whether `node` is part of an expression that is evaluated at compile time, like an array
+/// length, a const generic argument, or the initializer of a `const` or `static` item.
+fn is_in_const_context(node: &SyntaxNode) -> bool {
+    let range = node.text_range();
+    let contains = |expr: Option<ast::Expr>| {
+        expr.map_or(false, |expr| expr.syntax().text_range().contains_range(range))
+    };
+    for ancestor in node.ancestors().skip(1) {
+        let in_const_context = match_ast! {
+            match ancestor {
+                ast::ArrayType(it) => contains(it.expr()),
+                ast::ArrayExpr(it) => it.semicolon_token().is_some() && contains(it.exprs().nth(1)),
+                ast::ConstArg(_it) => true,
+                ast::Const(it) => contains(it.body()),
+                ast::Static(it) => contains(it.body()),
+                ast::Variant(it) => contains(it.expr()),
+                ast::Fn(_it) => return false,
+                ast::ClosureExpr(_it) => return false,
+                _ => false,
+            }
+        };
+        if in_const_context {
+            return true;
+        }
+    }
+    false
+}
+
+/// If the call is the scrutinee of a `match` that is a statement, or the tail expression of a
+/// block, returns that statement or tail expression.
+fn scrutinee_statement(call: &ast::Expr) -> Option<SyntaxNode> {
+    let match_expr = call.syntax().parent().and_then(ast::MatchExpr::cast)?;
+    if match_expr.expr()?.syntax() != call.syntax() {
+        return None;
+    }
+    let parent = match_expr.syntax().parent()?;
+    match parent.kind() {
+        EXPR_STMT | LET_STMT => Some(parent),
+        BLOCK_EXPR => Some(match_expr.syntax().clone()),
+        _ => None,
+    }
+}
+
+/// Returns the method chain of the body with the argument in place of the parameter, if the body
+/// is nothing but a chain starting at its sole parameter and the call is itself the receiver of a
+/// method call, like in `build_iter(xs).collect()`. The caller's methods then continue the chain
+/// instead of being called on a block.
+fn spliced_method_chain(
+    call: &ast::Expr,
+    function: &ast::Fn,
+    body: &ast::BlockExpr,
+    arguments: &[ast::Expr],
+) -> Option<ast::Expr> {
+    let method_call = call.syntax().parent().and_then(ast::MethodCallExpr::cast)?;
+    if method_call.receiver()?.syntax() != call.syntax() {
+        return None;
+    }
+    let (argument, param_name) = match (arguments, function.param_list()?.params().next()) {
+        ([argument], Some(param)) => match param.pat()? {
+            ast::Pat::IdentPat(pat)
+                if pat.ref_token().is_none()
+                    && pat.mut_token().is_none()
+                    && pat.pat().is_none() =>
+            {
+                (argument, pat.name()?)
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if body_statements(body).next().is_some() {
+        return None;
+    }
+
+    let chain = match body.tail_expr()? {
+        ast::Expr::MethodCallExpr(it) => it,
+        _ => return None,
+    };
+    let mut base = chain.receiver()?;
+    while let ast::Expr::MethodCallExpr(receiver) = &base {
+        base = receiver.receiver()?;
+    }
+    let is_param = match &base {
+        ast::Expr::PathExpr(it) => {
+            let path = it.path()?;
+            path.qualifier().is_none() && path.segment()?.name_ref()?.text() == param_name.text()
+        }
+        _ => false,
+    };
+    // The closures of the chain could use the parameter too, which the argument can't replace.
+    let mentions = chain
+        .syntax()
+        .descendants()
+        .filter_map(ast::NameRef::cast)
+        .filter(|it| it.text() == param_name.text())
+        .count();
+    if !is_param || mentions != 1 {
+        return None;
+    }
+
+    let argument = if needs_parens_in_place_of(base.syntax(), argument) {
+        make::expr_paren(argument.clone())
+    } else {
+        argument.clone()
+    };
+    let mut rewriter = SyntaxRewriter::default();
+    rewriter.replace(base.syntax(), argument.syntax());
+    Some(rewriter.rewrite_ast(&ast::Expr::MethodCallExpr(chain)))
+}
+
+/// Checks whether the body of the function calls the function itself, directly or through a
+/// method call.
+fn calls_itself(
+    ctx: &AssistContext,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+) -> bool {
+    let body = match analyzed_function(ctx, function_source).and_then(|it| it.body()) {
+        Some(it) => it,
+        None => return false,
+    };
+    refers_to_function(ctx, body.syntax(), function)
+}
+
+/// Checks whether the node calls the function, or otherwise names it.
+fn refers_to_function(ctx: &AssistContext, node: &SyntaxNode, function: hir::Function) -> bool {
+    node.descendants().any(|node| {
+        match_ast! {
+            match node {
+                ast::PathExpr(it) => matches!(
+                    it.path().and_then(|path| ctx.sema.resolve_path(&path)),
+                    Some(PathResolution::Def(hir::ModuleDef::Function(it)))
+                        | Some(PathResolution::AssocItem(hir::AssocItem::Function(it)))
+                        if it == function
+                ),
+                ast::MethodCallExpr(it) => ctx.sema.resolve_method_call(&it) == Some(function),
+                _ => false,
+            }
+        }
+    })
+}
+
+/// Renames the labels of the body's loops and blocks that are already used by the labeled
+/// expressions around the call, so that inlining doesn't shadow them. The `break`s and
+/// `continue`s in the body can only refer to labels of the body itself.
+fn rename_colliding_labels(call: &ast::Expr, body: ast::BlockExpr) -> ast::BlockExpr {
+    let label_name = |node: &SyntaxNode| -> Option<String> {
+        let label = node.children().find_map(ast::Label::cast)?;
+        Some(label.lifetime()?.syntax().text().to_string())
+    };
+    let outer_labels =
+        call.syntax().ancestors().filter_map(|it| label_name(&it)).collect::<FxHashSet<_>>();
+    let body_labels = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Label::cast)
+        .filter_map(|it| Some(it.lifetime()?.syntax().text().to_string()))
+        .collect::<FxHashSet<_>>();
+
+    let mut renames = FxHashMap::default();
+    for label in body_labels.iter().filter(|it| outer_labels.contains(*it)) {
+        let new_label = (1..)
+            .map(|idx| format!("{}_{}", label, idx))
+            .find(|it| !outer_labels.contains(it) && !body_labels.contains(it))
+            .unwrap();
+        renames.insert(label.clone(), make::lifetime(&new_label));
+    }
+    if renames.is_empty() {
+        return body;
+    }
+
+    let mut rewriter = SyntaxRewriter::default();
+    for lifetime in body.syntax().descendants().filter_map(ast::Lifetime::cast) {
+        let is_label = lifetime
+            .syntax()
+            .parent()
+            .map_or(false, |it| matches!(it.kind(), LABEL | BREAK_EXPR | CONTINUE_EXPR));
+        if let Some(new_lifetime) = renames.get(&lifetime.syntax().text().to_string()) {
+            if is_label {
+                rewriter.replace(lifetime.syntax(), new_lifetime.syntax());
+            }
+        }
+    }
+    rewriter.rewrite_ast(&body)
+}
+
+/// Returns the number of dereferences that turn the tail expression of the body into the declared
+/// return type, like the two of `&**` for a `&String` returned as `&str`.
+///
+/// The return type coerces the tail expression, but the inlined body gets the type of the tail
+/// expression itself. Returns `None` for coercions other than deref coercions.
+fn return_coercion_derefs(
+    ctx: &AssistContext,
+    function: hir::Function,
+    function_source: &InFile<ast::Fn>,
+) -> Option<usize> {
+    let db = ctx.db();
+    let tail_expr = match analyzed_function(ctx, function_source)?.body()?.tail_expr() {
+        Some(it) => it,
+        None => return Some(0),
+    };
+    let tail_ty = match ctx.sema.type_of_expr(&tail_expr) {
+        Some(it) => it,
+        None => return Some(0),
+    };
+    let ret_ty = function.ret_type(db);
+    if tail_ty.contains_unknown()
+        || ret_ty.contains_unknown()
+        || tail_ty.display(db).to_string() == ret_ty.display(db).to_string()
+    {
+        return Some(0);
+    }
+
+    let target = match (ret_ty.remove_ref(), tail_ty.remove_ref()) {
+        (Some(target), Some(_))
+            if ret_ty.is_mutable_reference() == tail_ty.is_mutable_reference() =>
+        {
+            target.display(db).to_string()
+        }
+        (Some(_), _) => return None,
+        // Other coercions, like the ones into `impl Trait`, are not a concern here.
+        (None, _) => return Some(0),
+    };
+    let mut autoderef = tail_ty.autoderef(db);
+    autoderef.position(|ty| ty.display(db).to_string() == target)
+}
+
+/// Dereferences the tail expression of `body` `derefs` times and borrows the result, turning the
+/// tail `&p.name` into `&*p.name` for a single dereference.
+fn apply_derefs_to_tail(body: ast::BlockExpr, derefs: usize, exclusive: bool) -> ast::BlockExpr {
+    let tail_expr = match body.tail_expr() {
+        Some(it) if derefs > 0 => it,
+        _ => return body,
+    };
+    let is_atomic = |expr: &ast::Expr| {
+        matches!(
+            expr,
+            ast::Expr::CallExpr(_)
+                | ast::Expr::FieldExpr(_)
+                | ast::Expr::IndexExpr(_)
+                | ast::Expr::MethodCallExpr(_)
+                | ast::Expr::ParenExpr(_)
+                | ast::Expr::PathExpr(_)
+                | ast::Expr::PrefixExpr(_)
+        )
+    };
+    // A borrow in the tail is reused, it stands for the first dereference.
+    let (mut expr, derefs) = match &tail_expr {
+        ast::Expr::RefExpr(it) if it.raw_token().is_none() && it.expr().is_some() => {
+            (it.expr().unwrap(), derefs - 1)
+        }
+        _ => (tail_expr.clone(), derefs),
+    };
+    for _ in 0..derefs {
+        if !is_atomic(&expr) {
+            expr = make::expr_paren(expr);
+        }
+        expr = make::expr_prefix(T![*], expr);
+    }
+    let replacement = make::expr_ref(expr, exclusive);
+
+    let mut rewriter = SyntaxRewriter::default();
+    rewriter.replace(tail_expr.syntax(), replacement.syntax());
+    rewriter.rewrite_ast(&body)
+}
+
+/// Returns the call the body forwards to with the arguments in place of the parameters, if the
+/// function is a wrapper that only supplies some arguments of another function, like
+/// `fn f(x: u32) { g(x, 10) }`. Each parameter has to be passed on exactly once, as is.
+fn forwarded_call(
+    function: &ast::Fn,
+    body: &ast::BlockExpr,
+    arguments: &[ast::Expr],
+) -> Option<ast::Expr> {
+    if body_statements(body).next().is_some() {
+        return None;
+    }
+    let inner_call = match body.tail_expr()? {
+        ast::Expr::CallExpr(it) => it,
+        _ => return None,
+    };
+    let param_names = function
+        .param_list()?
+        .params()
+        .map(|param| match param.pat()? {
+            ast::Pat::IdentPat(pat)
+                if pat.ref_token().is_none()
+                    && pat.mut_token().is_none()
+                    && pat.pat().is_none() =>
+            {
+                Some(pat.name()?.text().to_string())
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let mentions_param = |node: &SyntaxNode| {
+        node.descendants()
+            .filter_map(ast::NameRef::cast)
+            .any(|name_ref| param_names.iter().any(|it| name_ref.text() == it.as_str()))
+    };
+    if mentions_param(inner_call.expr()?.syntax()) {
+        return None;
+    }
+
+    // For each argument of the inner call, the index of the parameter it passes on.
+    let mut forwarded = Vec::new();
+    let inner_arguments = inner_call.arg_list()?.args().collect::<Vec<_>>();
+    for argument in &inner_arguments {
+        let param = match argument {
+            ast::Expr::PathExpr(it) => it.path().and_then(|path| {
+                let name_ref = path.segment()?.name_ref()?;
+                if path.qualifier().is_some() {
+                    return None;
+                }
+                param_names.iter().position(|it| name_ref.text() == it.as_str())
+            }),
+            _ => None,
+        };
+        if param.is_none() && mentions_param(argument.syntax()) {
+            return None;
+        }
+        forwarded.push(param);
+    }
+    let forwarded_params = forwarded.iter().flatten().collect::<Vec<_>>();
+    if forwarded_params.len() != param_names.len()
+        || (0..param_names.len()).any(|idx| !forwarded_params.contains(&&idx))
+    {
+        return None;
+    }
+
+    // The arguments used to be evaluated in order and before the supplied ones.
+    let keeps_order = forwarded_params.windows(2).all(|it| it[0] < it[1])
+        && inner_arguments
+            .iter()
+            .zip(&forwarded)
+            .all(|(argument, param)| param.is_some() || is_side_effect_free(argument));
+    if !keeps_order && !arguments.iter().all(is_side_effect_free) {
+        return None;
+    }
+
+    let mut rewriter = SyntaxRewriter::default();
+    for (inner_argument, param) in inner_arguments.iter().zip(forwarded) {
+        if let Some(param) = param {
+            rewriter.replace(inner_argument.syntax(), arguments[param].syntax());
+        }
+    }
+    Some(rewriter.rewrite_ast(&ast::Expr::CallExpr(inner_call)))
+}
+
+/// Returns the statement containing the call if nothing else in it is evaluated before the call,
+/// so that the arguments can be bound right before the statement without reordering side effects.
+fn temporary_promotion_anchor(call: &ast::Expr) -> Option<SyntaxNode> {
+    let mut node = call.syntax().clone();
+    loop {
+        let parent = node.parent()?;
+        let is_evaluated_first = match parent.kind() {
+            EXPR_STMT | LET_STMT => return Some(parent),
+            CALL_EXPR => {
+                let callee = ast::CallExpr::cast(parent.clone())?.expr();
+                node.kind() == ARG_LIST && matches!(callee, Some(ast::Expr::PathExpr(_)))
+            }
+            ARG_LIST | AWAIT_EXPR | BIN_EXPR | FIELD_EXPR | INDEX_EXPR | METHOD_CALL_EXPR
+            | PAREN_EXPR | REF_EXPR | TRY_EXPR => parent.first_child().as_ref() == Some(&node),
+            _ => false,
+        };
+        if !is_evaluated_first {
+            return None;
+        }
+        node = parent;
+    }
+}
+
+/// Inserts `statements` before `anchor`, at its indentation.
+fn hoist_statements(
+    builder: &mut AssistBuilder,
+    anchor: &SyntaxNode,
+    statements: impl IntoIterator<Item = ast::Stmt>,
+) {
+    let indentation = IndentLevel::from_node(anchor);
+    let hoisted = make::block_expr(statements, None)
+        .reset_indent()
+        .indent(indentation)
+        .statements()
+        .map(|stmt| format!("{}\n{}", stmt, indentation))
+        .collect::<String>();
+    if !hoisted.is_empty() {
+        builder.insert(anchor.text_range().start(), hoisted);
+    }
+}
+
+/// Checks whether `expr` has to be parenthesized to keep its meaning when it replaces `node`.
+fn needs_parens_in_place_of(node: &SyntaxNode, expr: &ast::Expr) -> bool {
+    let is_scrutinee = node
+        .parent()
+        .and_then(ast::MatchExpr::cast)
+        .map_or(false, |it| it.expr().map_or(false, |scrutinee| scrutinee.syntax() == node));
+    if is_scrutinee {
+        // The braces of a struct literal would be taken for the match arms.
+        return matches!(expr, ast::Expr::RecordExpr(_));
+    }
+
+    let is_atomic = matches!(
+        expr,
+        ast::Expr::CallExpr(_)
+            | ast::Expr::IndexExpr(_)
+            | ast::Expr::MethodCallExpr(_)
+            | ast::Expr::FieldExpr(_)
+            | ast::Expr::TryExpr(_)
+            | ast::Expr::Literal(_)
+            | ast::Expr::TupleExpr(_)
+            | ast::Expr::ArrayExpr(_)
+            | ast::Expr::ParenExpr(_)
+            | ast::Expr::PathExpr(_)
+            | ast::Expr::BlockExpr(_)
+            | ast::Expr::EffectExpr(_)
+            | ast::Expr::MacroCall(_)
+            | ast::Expr::RecordExpr(_)
+    );
+    if is_atomic {
+        return false;
+    }
+    match node.parent() {
+        Some(parent) => !matches!(
+            parent.kind(),
+            LET_STMT
+                | ARG_LIST
+                | EXPR_STMT
+                | BLOCK_EXPR
+                | PAREN_EXPR
+                | RETURN_EXPR
+                | MATCH_ARM
+                | RECORD_EXPR_FIELD
+                | ARRAY_EXPR
+                | ARRAY_TYPE
+                | TUPLE_EXPR
+                | CONST
+                | STATIC
+                | VARIANT
+        ),
+        None => false,
+    }
+}
+
+/// Returns the single-path `use` items of the body that can be moved to the import scope of the
+/// call site, where they are merged with the imports already present there.
+///
+/// This is only done when the function lives in the same module as the call, as otherwise the
+/// `use` paths may resolve differently.
+fn hoistable_imports(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function: hir::Function,
+    body: &ast::BlockExpr,
+) -> Vec<ast::Use> {
+    let scope = ctx.sema.scope(call.syntax());
+    if scope.module() != Some(function.module(ctx.db())) {
+        return Vec::new();
+    }
+
+    body.statements()
+        .filter_map(|stmt| match stmt {
+            ast::Stmt::Item(ast::Item::Use(use_)) => Some(use_),
+            _ => None,
+        })
+        .filter(|use_| {
+            use_.visibility().is_none()
+                && use_.use_tree().map_or(false, |tree| {
+                    tree.path().is_some()
+                        && tree.use_tree_list().is_none()
+                        && tree.star_token().is_none()
+                        && tree.rename().is_none()
+                })
+        })
+        .collect()
+}
+
+/// Finds the function in the tree known to `Semantics`, so that its body can be analyzed.
+///
+/// Returns `None` for functions coming from macro expansions.
+fn analyzed_function(ctx: &AssistContext, function_source: &InFile<ast::Fn>) -> Option<ast::Fn> {
+    let db = ctx.db();
+    if function_source.file_id.call_node(db).is_some() {
+        return None;
+    }
+    let source_file = ctx.sema.parse(function_source.file_id.original_file(db));
+    find_node_at_range(source_file.syntax(), function_source.value.syntax().text_range())
+}
+
+/// Returns the declared type of each parameter of the function, in the tree known to `Semantics`.
+fn declared_parameter_types(
+    ctx: &AssistContext,
+    function_source: &InFile<ast::Fn>,
+) -> Vec<Option<ast::Type>> {
+    let params = match function_source.value.param_list() {
+        Some(it) => it.params().count(),
+        None => 0,
+    };
+    match analyzed_function(ctx, function_source).and_then(|it| it.param_list()) {
+        Some(param_list) => param_list.params().map(|it| it.ty()).collect(),
+        None => vec![None; params],
+    }
+}
+
+/// Checks whether `ty` can be written as is at `node`: every path in it resolves to the same item
+/// there, and it has neither lifetimes nor `impl Trait`.
+fn is_nameable_at(ctx: &AssistContext, node: &SyntaxNode, ty: &ast::Type) -> bool {
+    let scope = ctx.sema.scope(node);
+    ty.syntax().descendants().all(|it| {
+        if ast::Lifetime::can_cast(it.kind()) || ast::ImplTraitType::can_cast(it.kind()) {
+            return false;
+        }
+        match ast::Path::cast(it) {
+            Some(path) => {
+                let resolution = ctx.sema.resolve_path(&path);
+                resolution.is_some() && resolution == scope.speculative_resolve(&path)
+            }
+            None => true,
+        }
+    })
+}
+
+/// Checks, for each parameter of the function, whether the body never uses the bindings of its
+/// pattern.
+fn unused_parameters(ctx: &AssistContext, function_source: &InFile<ast::Fn>) -> Vec<bool> {
+    let params = match function_source.value.param_list() {
+        Some(it) => it.params().count(),
+        None => 0,
+    };
+    let function = match analyzed_function(ctx, function_source) {
+        Some(it) => it,
+        None => return vec![false; params],
+    };
+    let param_list = match function.param_list() {
+        Some(it) => it,
+        None => return vec![false; params],
+    };
+    param_list
+        .params()
+        .map(|param| match param.pat() {
+            // Patterns like `_` or `(_, _)` bind nothing, the argument is only dropped.
+            Some(pat)
+                if !pat.syntax().descendants().any(|it| ast::IdentPat::can_cast(it.kind())) =>
+            {
+                true
+            }
+            Some(ast::Pat::IdentPat(pat)) if pat.pat().is_none() => {
+                let name = match pat.name() {
+                    Some(it) => it,
+                    None => return false,
+                };
+                // Usages in the arguments of macros that fail to expand can't be found, so
+                // conservatively treat any mention in a macro call as a usage.
+                !mentioned_in_macro_call(function.syntax(), &name.text())
+                    && ctx.sema.to_def(&pat).map_or(false, |local| {
+                        !Definition::Local(local).usages(&ctx.sema).at_least_one()
+                    })
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+/// Returns the name of the local passed as `argument` if the closure around the call captures it
+/// by value, that is, if it isn't `Copy` and isn't declared in the closure itself. Dropping such an
+/// argument instead of evaluating it would change what the closure moves.
+fn moved_capture(ctx: &AssistContext, call: &ast::Expr, argument: &ast::Expr) -> Option<hir::Name> {
+    let closure = call.syntax().ancestors().find_map(ast::ClosureExpr::cast)?;
+    if closure.move_token().is_some() {
+        return None;
+    }
+    let path = match argument {
+        ast::Expr::PathExpr(it) => it.path()?,
+        _ => return None,
+    };
+    let local = match ctx.sema.resolve_path(&path)? {
+        PathResolution::Local(it) => it,
+        _ => return None,
+    };
+    let db = ctx.db();
+    let source = local.source(db);
+    if source.file_id != ctx.frange.file_id.into() {
+        return None;
+    }
+    let declaration =
+        source.value.either(|it| it.syntax().text_range(), |it| it.syntax().text_range());
+    if closure.syntax().text_range().contains_range(declaration) || local.ty(db).is_copy(db) {
+        return None;
+    }
+    local.name(db)
+}
+
+/// Returns the type to annotate the binding of `argument` with, if the argument alone doesn't
+/// determine its type. The parameter type did that before, the binding would leave it to the
+/// usages in the body.
+///
+/// The declared type of the parameter is kept when it means the same at the call site, otherwise
+/// the inferred type of the argument is spelled out.
+fn binding_annotation(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    argument: &ast::Expr,
+    declared: Option<ast::Type>,
+) -> Option<ast::Type> {
+    if !is_inferred_from_context(ctx, argument) {
+        return None;
+    }
+    if let Some(declared) = declared {
+        if is_nameable_at(ctx, call.syntax(), &declared) {
+            return Some(make::ty(&declared.to_string()));
+        }
+    }
+    let ty = ctx.sema.type_of_expr(argument)?;
+    if ty.contains_unknown() {
+        return None;
+    }
+    let module = ctx.sema.scope(call.syntax()).module()?;
+    let ty = ty.display_source_code(ctx.db(), module.into()).ok()?;
+    Some(make::ty(&ty))
+}
+
+fn is_inferred_from_context(ctx: &AssistContext, expr: &ast::Expr) -> bool {
+    let db = ctx.db();
+    match expr {
+        ast::Expr::ParenExpr(it) => {
+            it.expr().map_or(false, |it| is_inferred_from_context(ctx, &it))
+        }
+        // `None` and the like.
+        ast::Expr::PathExpr(path) => match path.path().and_then(|it| ctx.sema.resolve_path(&it)) {
+            Some(PathResolution::Def(hir::ModuleDef::Variant(variant))) => {
+                let enum_ = variant.parent_enum(db);
+                !hir::GenericDef::from(hir::Adt::from(enum_)).type_params(db).is_empty()
+            }
+            _ => false,
+        },
+        ast::Expr::CallExpr(call) => {
+            let path = match call.expr() {
+                Some(ast::Expr::PathExpr(it)) => it.path(),
+                _ => None,
+            };
+            match path.and_then(|it| ctx.sema.resolve_path(&it)) {
+                Some(PathResolution::Def(hir::ModuleDef::Function(function)))
+                | Some(PathResolution::AssocItem(hir::AssocItem::Function(function))) => {
+                    returns_unconstrained_type_param(db, function)
+                }
+                _ => false,
+            }
+        }
+        ast::Expr::MethodCallExpr(call) => ctx
+            .sema
+            .resolve_method_call(call)
+            .map_or(false, |function| returns_unconstrained_type_param(db, function)),
+        _ => false,
+    }
+}
+
+/// Checks whether the return type of `function` mentions a type parameter, `Self` included, that
+/// none of its parameters mention, like `Default::default`, `Iterator::collect` or `Vec::new`.
+fn returns_unconstrained_type_param(db: &RootDatabase, function: hir::Function) -> bool {
+    let source = match function.source(db) {
+        Some(it) => it.value,
+        None => return false,
+    };
+    let type_param_names = |def: hir::GenericDef| {
+        def.type_params(db).into_iter().map(|it| it.name(db).to_string()).collect::<Vec<_>>()
+    };
+    // The parameters of the trait or impl, which a `self` parameter determines, except for the
+    // parameters of a trait.
+    let (container_params, self_params) =
+        match function.as_assoc_item(db).map(|it| it.container(db)) {
+            Some(AssocItemContainer::Trait(trait_)) => {
+                (type_param_names(trait_.into()), vec!["Self".to_string()])
+            }
+            Some(AssocItemContainer::Impl(impl_)) => {
+                let mut params = type_param_names(impl_.into());
+                if !params.is_empty() {
+                    params.push("Self".to_string());
+                }
+                (Vec::new(), params)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+    let mentioned_names = |ty: Option<ast::Type>| {
+        ty.into_iter()
+            .flat_map(|it| it.syntax().descendants().filter_map(ast::NameRef::cast))
+            .map(|it| it.text().to_string())
+            .collect::<FxHashSet<_>>()
+    };
+    let params = source.param_list();
+    let mut param_names = params
+        .iter()
+        .flat_map(|it| it.params())
+        .flat_map(|it| mentioned_names(it.ty()))
+        .collect::<FxHashSet<_>>();
+    if params.and_then(|it| it.self_param()).is_some() {
+        param_names.extend(self_params.iter().cloned());
+    }
+    let return_names = mentioned_names(source.ret_type().and_then(|it| it.ty()));
+    type_param_names(function.into())
+        .into_iter()
+        .chain(container_params)
+        .chain(self_params)
+        .any(|name| return_names.contains(&name) && !param_names.contains(&name))
+}
+
+/// Returns, for each parameter, the argument to substitute for the usages of the parameter in
+/// the body, instead of binding it with a `let`. Only literals and `Copy` locals are substituted,
+/// they are cheap and can be evaluated any number of times, as well as closures called once.
+/// Other arguments are substituted for a parameter used once, where evaluating them there keeps
+/// the order of the side effects.
+fn substitutable_arguments(
+    ctx: &AssistContext,
+    function_source: &InFile<ast::Fn>,
+    body: &ast::BlockExpr,
+    arguments: &[ast::Expr],
+) -> Vec<Option<ast::Expr>> {
+    let analyzed_params =
+        match analyzed_function(ctx, function_source).and_then(|it| it.param_list()) {
+            Some(it) => it.params().collect::<Vec<_>>(),
+            None => return vec![None; arguments.len()],
+        };
+    let param_names = analyzed_params
+        .iter()
+        .map(|param| match param.pat() {
+            Some(ast::Pat::IdentPat(pat)) => pat.name().map(|it| it.text().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let body_bindings = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter_map(|it| it.name())
+        .map(|it| it.text().to_string())
+        .collect::<FxHashSet<_>>();
+
+    let is_pure = |argument: &ast::Expr| {
+        is_side_effect_free(argument) || matches!(argument, ast::Expr::ClosureExpr(_))
+    };
+    let mut substitutions = analyzed_params
+        .iter()
+        .zip(arguments)
+        .enumerate()
+        .map(|(idx, (param, argument))| {
+            // The other arguments would be evaluated before this one, bound by their `let`s.
+            let others_are_pure =
+                arguments.iter().enumerate().all(|(other, it)| other == idx || is_pure(it));
+            substitutable_argument(ctx, body, param, argument, others_are_pure)
+        })
+        .collect::<Vec<_>>();
+
+    // The arguments of a call are all evaluated, left to right, before the body. An argument
+    // substituted into the body would be evaluated after the side effects of the arguments bound
+    // before it, so every argument up to the last side-effecting one is bound, in source order.
+    let last_bound_effect = arguments
+        .iter()
+        .zip(&substitutions)
+        .rposition(|(argument, substitution)| substitution.is_none() && !is_pure(argument));
+    if let Some(last) = last_bound_effect {
+        substitutions.iter_mut().take(last).for_each(|it| *it = None);
+    }
+
+    // A local named like a parameter that is bound by a `let` would refer to that binding, as
+    // would a local named like a binding of the body.
+    loop {
+        let bound_names = param_names
+            .iter()
+            .zip(&substitutions)
+            .filter(|(_, substitution)| substitution.is_none())
+            .filter_map(|(name, _)| name.as_deref())
+            .collect::<FxHashSet<_>>();
+        let is_shadowed = |name: &str| bound_names.contains(name) || body_bindings.contains(name);
+        // The body of a closure takes the place of its call, in the scope of the body too.
+        let shadowed = substitutions.iter().position(|substitution| match substitution {
+            Some(substitution) => {
+                let own_bindings = substitution
+                    .syntax()
+                    .descendants()
+                    .filter_map(ast::IdentPat::cast)
+                    .filter_map(|it| it.name())
+                    .map(|it| it.text().to_string())
+                    .collect::<FxHashSet<_>>();
+                substitution
+                    .syntax()
+                    .descendants()
+                    .filter_map(ast::PathExpr::cast)
+                    .filter_map(|it| it.path())
+                    .filter(|it| it.qualifier().is_none())
+                    .filter_map(|it| it.segment()?.name_ref())
+                    .map(|it| it.text().to_string())
+                    .any(|name| !own_bindings.contains(&name) && is_shadowed(&name))
+            }
+            None => false,
+        });
+        match shadowed {
+            Some(idx) => substitutions[idx] = None,
+            None => break,
+        }
+    }
+    substitutions
+}
+
+fn substitutable_argument(
+    ctx: &AssistContext,
+    body: &ast::BlockExpr,
+    param: &ast::Param,
+    argument: &ast::Expr,
+    others_are_pure: bool,
+) -> Option<ast::Expr> {
+    let pat = match param.pat()? {
+        ast::Pat::IdentPat(pat)
+            if pat.ref_token().is_none() && pat.mut_token().is_none() && pat.pat().is_none() =>
+        {
+            pat
+        }
+        _ => return None,
+    };
+    let is_trivial = match argument {
+        ast::Expr::Literal(_) => true,
+        ast::Expr::PathExpr(it) => matches!(
+            ctx.sema.resolve_path(&it.path()?)?,
+            PathResolution::Local(local) if local.ty(ctx.db()).is_copy(ctx.db())
+        ),
+        ast::Expr::ClosureExpr(closure) => return substitutable_closure(ctx, body, &pat, closure),
+        _ => false,
+    };
+    if !is_trivial && !others_are_pure {
+        return None;
+    }
+    // The argument would not be coerced to the type of the parameter anymore.
+    let param_ty = ctx.sema.type_of_pat(&ast::Pat::IdentPat(pat.clone()))?;
+    if !ctx.sema.type_of_expr(argument)?.is_same_ty(&param_ty) {
+        return None;
+    }
+
+    let paths = usage_paths(ctx, body, &pat)?;
+    if !is_trivial {
+        return match paths.as_slice() {
+            [path] if is_evaluated_first(body, path) => Some(argument.clone()),
+            _ => None,
+        };
+    }
+    // The concrete type of a literal is inferred from how it is used, a method call on it would
+    // be ambiguous.
+    let is_receiver = |path: &ast::PathExpr| {
+        path.syntax().parent().map_or(false, |parent| {
+            ast::MethodCallExpr::can_cast(parent.kind()) || ast::FieldExpr::can_cast(parent.kind())
+        })
+    };
+    if matches!(argument, ast::Expr::Literal(_)) && paths.iter().any(is_receiver) {
+        return None;
+    }
+    Some(argument.clone())
+}
+
+/// Checks whether the single usage of a parameter is evaluated once, by value, and before
+/// anything in the body that could have side effects, so that the argument can be evaluated
+/// there instead of before the body.
+fn is_evaluated_first(body: &ast::BlockExpr, usage: &ast::PathExpr) -> bool {
+    let start = usage.syntax().text_range().start();
+    let has_earlier_effects = body
+        .syntax()
+        .descendants()
+        .take_while(|it| it.text_range().start() < start)
+        .filter(|it| it.text_range().end() <= start)
+        .any(|it| {
+            match_ast! {
+                match it {
+                    ast::CallExpr(_it) => true,
+                    ast::MethodCallExpr(_it) => true,
+                    ast::MacroCall(_it) => true,
+                    ast::AwaitExpr(_it) => true,
+                    ast::TryExpr(_it) => true,
+                    ast::IndexExpr(_it) => true,
+                    ast::BinExpr(it) => it.op_kind().map_or(true, |op| op.is_assignment()),
+                    _ => false,
+                }
+            }
+        });
+    if has_earlier_effects {
+        return false;
+    }
+
+    // By value: the temporary the argument makes would be dropped at the end of the statement
+    // instead of the end of the block.
+    let is_by_value = usage.syntax().parent().map_or(true, |parent| {
+        !ast::RefExpr::can_cast(parent.kind())
+            && !ast::MethodCallExpr::can_cast(parent.kind())
+            && !ast::FieldExpr::can_cast(parent.kind())
+            && !ast::IndexExpr::can_cast(parent.kind())
+    });
+    if !is_by_value {
+        return false;
+    }
+
+    // Once: not in a loop, a closure or a branch that may not be taken.
+    let mut child = usage.syntax().clone();
+    for ancestor in usage.syntax().ancestors().skip(1) {
+        if &ancestor == body.syntax() {
+            return true;
+        }
+        let is_evaluated_once = match_ast! {
+            match ancestor {
+                ast::IfExpr(it) => it.condition().map_or(false, |it| it.syntax() == &child),
+                ast::MatchExpr(it) => it.expr().map_or(false, |it| it.syntax() == &child),
+                ast::BinExpr(it) => match it.op_kind() {
+                    Some(ast::BinOp::BooleanAnd) | Some(ast::BinOp::BooleanOr) => {
+                        it.lhs().map_or(false, |it| it.syntax() == &child)
+                    },
+                    _ => true,
+                },
+                ast::LoopExpr(_it) => false,
+                ast::WhileExpr(_it) => false,
+                ast::ForExpr(_it) => false,
+                ast::ClosureExpr(_it) => false,
+                ast::EffectExpr(_it) => false,
+                _ => true,
+            }
+        };
+        if !is_evaluated_once {
+            return false;
+        }
+        child = ancestor;
+    }
+    false
+}
+
+/// Returns the usages of the parameter bound by `pat` in the body, if all the paths named like the
+/// parameter are its usages. The tokens of macro calls aren't paths, and their usages can't be
+/// found if they fail to expand.
+fn usage_paths(
+    ctx: &AssistContext,
+    body: &ast::BlockExpr,
+    pat: &ast::IdentPat,
+) -> Option<Vec<ast::PathExpr>> {
+    let name = pat.name()?;
+    let local = ctx.sema.to_def(pat)?;
+    let usages = Definition::Local(local).usages(&ctx.sema).all();
+    let references = usages.references.values().flatten().collect::<Vec<_>>();
+    if references.is_empty()
+        || references.iter().any(|it| it.kind == ReferenceKind::FieldShorthandForLocal)
+    {
+        return None;
+    }
+    let paths = paths_named(body.syntax(), &name.text());
+    if paths.len() != references.len() || mentioned_in_macro_call(body.syntax(), &name.text()) {
+        return None;
+    }
+    Some(paths)
+}
+
+/// Returns the closure passed for the parameter bound by `pat`, if the body only calls the
+/// parameter once, so that the call can take the body of the closure instead, see
+/// [`apply_closure_arguments`].
+fn substitutable_closure(
+    ctx: &AssistContext,
+    body: &ast::BlockExpr,
+    pat: &ast::IdentPat,
+    closure: &ast::ClosureExpr,
+) -> Option<ast::Expr> {
+    let paths = usage_paths(ctx, body, pat)?;
+    let call = match paths.as_slice() {
+        [path] => path.syntax().parent().and_then(ast::CallExpr::cast)?,
+        _ => {
+            // Every call would get a copy of the closure body.
+            mark::hit!(inline_function_closure_argument_called_more_than_once);
+            return None;
+        }
+    };
+    if call.expr()?.syntax() != paths[0].syntax() {
+        return None;
+    }
+    let params = closure.param_list()?.params().map(|it| it.pat()).collect::<Option<Vec<_>>>()?;
+    if call.arg_list()?.args().count() != params.len() || closure.body().is_none() {
+        return None;
+    }
+    if captures_mutably(ctx, closure) {
+        // The body of the closure would mutate the captured locals while the function body
+        // may still use them.
+        mark::hit!(inline_function_closure_argument_captures_mutably);
+        return None;
+    }
+    Some(closure.clone().into())
+}
+
+/// Checks whether the body of the closure assigns to, mutably borrows, or calls a `&mut self`
+/// method on a local declared outside the closure, or on a field of one.
+fn captures_mutably(ctx: &AssistContext, closure: &ast::ClosureExpr) -> bool {
+    let db = ctx.db();
+    let body = match closure.body() {
+        Some(it) => it,
+        None => return true,
+    };
+    body.syntax().descendants().filter_map(ast::PathExpr::cast).any(|path| {
+        let local = match path.path().and_then(|it| ctx.sema.resolve_path(&it)) {
+            Some(PathResolution::Local(it)) => it,
+            _ => return false,
+        };
+        let declaration = local
+            .source(db)
+            .value
+            .either(|it| it.syntax().text_range(), |it| it.syntax().text_range());
+        if closure.syntax().text_range().contains_range(declaration) {
+            return false;
+        }
+        let mut place = path.syntax().clone();
+        while let Some(parent) = place.parent() {
+            let is_base = match_ast! {
+                match parent {
+                    ast::FieldExpr(it) => it.expr().map_or(false, |it| it.syntax() == &place),
+                    ast::IndexExpr(it) => it.base().map_or(false, |it| it.syntax() == &place),
+                    _ => false,
+                }
+            };
+            if !is_base {
+                break;
+            }
+            place = parent;
+        }
+        let parent = match place.parent() {
+            Some(it) => it,
+            None => return false,
+        };
+        match_ast! {
+            match parent {
+                ast::BinExpr(it) => {
+                    it.op_kind().map_or(false, |op| op.is_assignment())
+                        && it.lhs().map_or(false, |it| it.syntax() == &place)
+                },
+                ast::RefExpr(it) => it.mut_token().is_some(),
+                ast::MethodCallExpr(it) => {
+                    it.receiver().map_or(false, |it| it.syntax() == &place)
+                        && ctx
+                            .sema
+                            .resolve_method_call(&it)
+                            .and_then(|it| it.self_param(db))
+                            .map_or(false, |it| matches!(it.access(db), hir::Access::Exclusive))
+                },
+                _ => false,
+            }
+        }
+    })
+}
+
+/// Replaces the calls of the parameters taking a closure with the body of the closure, see
+/// [`substitutable_closure`]. The closure parameters are bound to the arguments of the call,
+/// or take their place if they are literals or locals used at most once.
+fn apply_closure_arguments(
+    parameters: &[ast::Pat],
+    substitutions: &[Option<ast::Expr>],
+    body: ast::BlockExpr,
+) -> ast::BlockExpr {
+    let mut rewriter = SyntaxRewriter::default();
+    for (pattern, substitution) in parameters.iter().zip(substitutions) {
+        let (name, closure) = match (pattern, substitution) {
+            (ast::Pat::IdentPat(pat), Some(ast::Expr::ClosureExpr(closure))) => match pat.name() {
+                Some(name) => (name, closure),
+                None => continue,
+            },
+            _ => continue,
+        };
+        for path in paths_named(body.syntax(), &name.text()) {
+            let call = match path.syntax().parent().and_then(ast::CallExpr::cast) {
+                Some(it) => it,
+                None => continue,
+            };
+            if let Some(applied) = applied_closure(closure, &call) {
+                rewriter.replace(call.syntax(), applied.syntax());
+            }
+        }
+    }
+    rewriter.rewrite_ast(&body)
+}
+
+fn applied_closure(closure: &ast::ClosureExpr, call: &ast::CallExpr) -> Option<ast::Expr> {
+    let closure_body = closure.body()?;
+    let params = closure.param_list()?.params().collect::<Vec<_>>();
+    let closure_bindings = closure_body
+        .syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter_map(|it| it.name())
+        .map(|it| it.text().to_string())
+        .collect::<FxHashSet<_>>();
+    let mut rewriter = SyntaxRewriter::default();
+    let mut bindings = Vec::new();
+    for (param, argument) in params.into_iter().zip(call.arg_list()?.args()) {
+        let pat = param.pat()?;
+        let paths = match &pat {
+            ast::Pat::IdentPat(it)
+                if it.ref_token().is_none() && it.mut_token().is_none() && it.pat().is_none() =>
+            {
+                let name = it.name()?.text().to_string();
+                match closure_bindings.contains(&name) {
+                    true => Vec::new(),
+                    false => paths_named(closure_body.syntax(), &name),
+                }
+            }
+            _ => Vec::new(),
+        };
+        let substitutable = param.ty().is_none()
+            && !paths.is_empty()
+            && match &argument {
+                ast::Expr::Literal(_) => true,
+                ast::Expr::PathExpr(it) => {
+                    paths.len() == 1 && !closure_bindings.contains(&it.syntax().text().to_string())
+                }
+                _ => false,
+            };
+        if substitutable {
+            for path in paths {
+                rewriter.replace(path.syntax(), argument.syntax());
+            }
+        } else {
+            bindings.push(ast::Stmt::from(make::let_stmt(pat, param.ty(), Some(argument))));
+        }
+    }
+    let closure_body = rewriter.rewrite_ast(&closure_body);
+    let applied = match (bindings.is_empty(), closure_body) {
+        (true, ast::Expr::BlockExpr(block)) => match extract_trivial_expression(&block) {
+            Some(expr) => expr,
+            None => block.into(),
+        },
+        (true, expr) => expr,
+        (false, ast::Expr::BlockExpr(block)) => {
+            bindings.extend(body_statements(&block));
+            make::block_expr(bindings, block.tail_expr()).into()
+        }
+        (false, expr) => make::block_expr(bindings, Some(expr)).into(),
+    };
+    let applied = applied.reset_indent().indent(IndentLevel::from_node(call.syntax()));
+    match needs_parens_in_place_of(call.syntax(), &applied) {
+        true => Some(make::expr_paren(applied)),
+        false => Some(applied),
+    }
+}
+
+/// The path expressions in `node` naming `name`, without a qualifier.
+fn paths_named(node: &SyntaxNode, name: &str) -> Vec<ast::PathExpr> {
+    node.descendants()
+        .filter_map(ast::PathExpr::cast)
+        .filter(|it| {
+            it.path().map_or(false, |path| {
+                path.qualifier().is_none()
+                    && path
+                        .segment()
+                        .and_then(|it| it.name_ref())
+                        .map_or(false, |it| it.text() == name)
+            })
+        })
+        .collect()
+}
+
+/// Whether the token tree of a macro call in `node` has an identifier `name`.
+fn mentioned_in_macro_call(node: &SyntaxNode, name: &str) -> bool {
+    node.descendants()
+        .filter_map(ast::MacroCall::cast)
+        .filter_map(|it| it.token_tree())
+        .flat_map(|it| it.syntax().descendants_with_tokens())
+        .filter_map(|it| it.into_token())
+        .any(|token| token.kind() == IDENT && token.text() == name)
+}
+
+/// Renames the parameters bound with a `let` that are named like locals in scope at the call
+/// site. Their bindings would shadow the locals for the arguments of the later parameters, and
+/// for the rest of the caller once they are hoisted before the call. A parameter bound to the
+/// local of the same name keeps its name, it only shadows the local with itself.
+fn rename_shadowing_parameters(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+    parameters: Vec<ast::Pat>,
+    arguments: &[ast::Expr],
+    substitutions: &[Option<ast::Expr>],
+    mut body: ast::BlockExpr,
+) -> (Vec<ast::Pat>, ast::BlockExpr) {
+    let mut locals_in_scope = FxHashSet::default();
+    ctx.sema.scope(call.syntax()).process_all_names(&mut |name, def| {
+        if let ScopeDef::Local(_) = def {
+            locals_in_scope.insert(name.to_string());
+        }
+    });
+    let analyzed_function = match analyzed_function(ctx, function_source) {
+        Some(it) => it,
+        None => return (parameters, body),
+    };
+    let analyzed_params = analyzed_function.param_list().into_iter().flat_map(|it| it.params());
+
+    let mut renamed_parameters = Vec::new();
+    for (((pattern, analyzed_param), argument), substitution) in
+        parameters.into_iter().zip(analyzed_params).zip(arguments).zip(substitutions)
+    {
+        let name = match &pattern {
+            ast::Pat::IdentPat(it) if it.pat().is_none() => it.name(),
+            _ => None,
+        };
+        let is_same_local = |name: &ast::Name| match argument {
+            ast::Expr::PathExpr(it) => it.syntax().text() == name.text().as_str(),
+            _ => false,
+        };
+        let renamed = match name {
+            Some(name)
+                if substitution.is_none()
+                    && locals_in_scope.contains(name.text().as_str())
+                    && !is_same_local(&name) =>
+            {
+                rename_parameter(ctx, call, &analyzed_function, &analyzed_param, &name, &body)
+            }
+            _ => None,
+        };
+        match renamed {
+            Some((renamed_pattern, renamed_body)) => {
+                renamed_parameters.push(renamed_pattern);
+                body = renamed_body;
+            }
+            None => renamed_parameters.push(pattern),
+        }
+    }
+    (renamed_parameters, body)
+}
+
+/// Renames the parameter `name` to a fresh name, returning its new pattern and the body using it.
+///
+/// The usages of the parameter are found in the analyzed function, and matched with the paths of
+/// the same name in the body, which may have been rewritten already, by their order.
+fn rename_parameter(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    analyzed_function: &ast::Fn,
+    analyzed_param: &ast::Param,
+    name: &ast::Name,
+    body: &ast::BlockExpr,
+) -> Option<(ast::Pat, ast::BlockExpr)> {
+    let local = match analyzed_param.pat()? {
+        ast::Pat::IdentPat(it) => ctx.sema.to_def(&it)?,
+        _ => return None,
+    };
+    let is_usage = paths_named(analyzed_function.body()?.syntax(), &name.text())
+        .into_iter()
+        .map(|path| {
+            let resolution = path.path().and_then(|it| ctx.sema.resolve_path(&it));
+            matches!(resolution, Some(PathResolution::Local(it)) if it == local)
+        })
+        .collect::<Vec<_>>();
+    let paths = paths_named(body.syntax(), &name.text());
+    if paths.len() != is_usage.len() || mentioned_in_macro_call(body.syntax(), &name.text()) {
+        return None;
+    }
+
+    let new_name = ctx.suggest_name_avoiding(&name.text(), call.syntax(), &identifiers(body));
+    let new_path = make::expr_path(make::path_unqualified(make::path_segment(make::name_ref(
+        &new_name.text(),
+    ))));
+    let mut rewriter = SyntaxRewriter::default();
+    for (path, _) in paths.into_iter().zip(is_usage).filter(|(_, is_usage)| *is_usage) {
+        // A field shorthand has to name the field explicitly now.
+        let shorthand_field = path
+            .syntax()
+            .parent()
+            .and_then(ast::RecordExprField::cast)
+            .filter(|it| it.colon_token().is_none());
+        match shorthand_field {
+            Some(field) => {
+                let field_name = make::name_ref(&name.text());
+                let new_field = make::record_expr_field(field_name, Some(new_path.clone()));
+                rewriter.replace(field.syntax(), new_field.syntax());
+            }
+            None => rewriter.replace(path.syntax(), new_path.syntax()),
+        }
+    }
+    let body = rewriter.rewrite_ast(body);
+
+    let pattern = analyzed_param.pat()?;
+    let mut rewriter = SyntaxRewriter::default();
+    let old_name = pattern.syntax().descendants().find_map(ast::Name::cast)?;
+    rewriter.replace(old_name.syntax(), new_name.syntax());
+    Some((rewriter.rewrite_ast(&pattern), body))
+}
+
+fn substitute_arguments(
+    parameters: &[ast::Pat],
+    substitutions: &[Option<ast::Expr>],
+    body: ast::BlockExpr,
+) -> ast::BlockExpr {
+    let mut rewriter = SyntaxRewriter::default();
+    for (pattern, substitution) in parameters.iter().zip(substitutions) {
+        let (name, argument) = match (pattern, substitution) {
+            (ast::Pat::IdentPat(pat), Some(argument)) => match pat.name() {
+                Some(name) => (name, argument),
+                None => continue,
+            },
+            _ => continue,
+        };
+        for path in paths_named(body.syntax(), &name.text()) {
+            match needs_parens_in_place_of(path.syntax(), argument) {
+                true => {
+                    rewriter.replace(path.syntax(), make::expr_paren(argument.clone()).syntax())
+                }
+                false => rewriter.replace(path.syntax(), argument.syntax()),
+            }
+        }
+    }
+    rewriter.rewrite_ast(&body)
+}
+
+/// Replaces the `if`s conditioned on a parameter taking a `bool` literal with the branch that
+/// is taken, dropping the dead one. Conditions only become literals by the substitution, so the
+/// `if`s the function is written with are left alone.
+fn fold_substituted_conditions(
+    parameters: &[ast::Pat],
+    substitutions: &[Option<ast::Expr>],
+    mut body: ast::BlockExpr,
+) -> ast::BlockExpr {
+    let literal_parameters = parameters
+        .iter()
+        .zip(substitutions)
+        .filter_map(|(pattern, substitution)| {
+            let value = match substitution {
+                Some(ast::Expr::Literal(literal)) => match literal.kind() {
+                    ast::LiteralKind::Bool(value) => value,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            match pattern {
+                ast::Pat::IdentPat(pat) => Some((pat.name()?.text().to_string(), value)),
+                _ => None,
+            }
+        })
+        .collect::<FxHashMap<_, _>>();
+    if literal_parameters.is_empty() {
+        return body;
+    }
+    let condition_value = |if_expr: &ast::IfExpr| {
+        let condition = if_expr.condition()?;
+        if condition.pat().is_some() {
+            return None;
+        }
+        let path = match condition.expr()? {
+            ast::Expr::PathExpr(it) => it.path()?,
+            _ => return None,
+        };
+        if path.qualifier().is_some() {
+            return None;
+        }
+        literal_parameters.get(path.segment()?.name_ref()?.text().as_str()).copied()
+    };
+
+    // The branches can hold conditions of their own, so this folds one `if` at a time.
+    while let Some((if_expr, value)) = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::IfExpr::cast)
+        .find_map(|it| Some((it.clone(), condition_value(&it)?)))
+    {
+        let taken = match (value, if_expr.then_branch(), if_expr.else_branch()) {
+            (true, Some(block), _) | (false, _, Some(ast::ElseBranch::Block(block))) => {
+                Some(ast::Expr::from(block))
+            }
+            (false, _, Some(ast::ElseBranch::IfExpr(it))) => Some(ast::Expr::from(it)),
+            (false, _, None) => None,
+            (true, None, _) => return body,
+        };
+        let is_whole_body = body.statements().next().is_none()
+            && body.tail_expr().map_or(false, |it| it.syntax() == if_expr.syntax());
+        body = match taken {
+            Some(ast::Expr::BlockExpr(block)) if is_whole_body => block.dedent(IndentLevel(1)),
+            Some(taken) => {
+                let taken = match taken {
+                    ast::Expr::BlockExpr(block) => unwrap_trivial_block(block),
+                    taken => taken,
+                };
+                let taken = match needs_parens_in_place_of(if_expr.syntax(), &taken) {
+                    true => make::expr_paren(taken),
+                    false => taken,
+                };
+                let mut rewriter = SyntaxRewriter::default();
+                rewriter.replace(if_expr.syntax(), taken.syntax());
+                rewriter.rewrite_ast(&body)
+            }
+            // A dead `if` without an `else` evaluates to `()`, it goes away entirely as a
+            // statement or as the tail of a block.
+            None => {
+                let removed = match if_expr.syntax().parent() {
+                    Some(parent) if ast::ExprStmt::can_cast(parent.kind()) => Some(parent),
+                    Some(parent) if ast::BlockExpr::can_cast(parent.kind()) => {
+                        Some(if_expr.syntax().clone())
+                    }
+                    _ => None,
+                };
+                let mut rewriter = SyntaxRewriter::default();
+                match removed {
+                    Some(removed) => {
+                        if let Some(whitespace) =
+                            removed.prev_sibling_or_token().filter(|it| it.kind() == WHITESPACE)
+                        {
+                            rewriter.delete(&whitespace);
+                        }
+                        rewriter.delete(&removed);
+                    }
+                    None => rewriter.replace(if_expr.syntax(), make::expr_unit().syntax()),
+                }
+                rewriter.rewrite_ast(&body)
+            }
+        };
+    }
+    body
+}
+
+/// Checks whether dropping `expr` without evaluating it can't change the program's behavior.
+fn is_side_effect_free(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Literal(_) | ast::Expr::PathExpr(_) => true,
+        ast::Expr::RefExpr(it) => it.expr().map_or(false, |it| is_side_effect_free(&it)),
+        ast::Expr::FieldExpr(it) => it.expr().map_or(false, |it| is_side_effect_free(&it)),
+        ast::Expr::ParenExpr(it) => it.expr().map_or(false, |it| is_side_effect_free(&it)),
+        _ => false,
+    }
+}
+
+/// Returns the paths of the traits that have to be imported at the call site for the trait
+/// method calls of the body to keep resolving, or `None` if some of them can't be imported.
+///
+/// Operators don't need this, as they are resolved through their lang item traits regardless
+/// of what is in scope.
+fn required_trait_imports(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+) -> Option<Vec<ast::Path>> {
+    let db = ctx.db();
+    let function = match analyzed_function(ctx, function_source) {
+        Some(it) => it,
+        None => return Some(Vec::new()),
+    };
+    let body = function.body()?;
+
+    let call_site = ctx.sema.scope(call.syntax());
+    let module = call_site.module()?;
+    let traits_in_scope = call_site.traits_in_scope();
+
+    let mut paths = Vec::new();
+    let mut seen = FxHashSet::default();
+    for method_call in body.syntax().descendants().filter_map(ast::MethodCallExpr::cast) {
+        let method = match ctx.sema.resolve_method_call(&method_call) {
+            Some(it) => it,
+            None => continue,
+        };
+        let trait_ = match method.as_assoc_item(db) {
+            Some(assoc) => match assoc.container(db) {
+                AssocItemContainer::Trait(it) => it,
+                AssocItemContainer::Impl(_) => continue,
+            },
+            None => continue,
+        };
+        if traits_in_scope.contains(&trait_.into()) || !seen.insert(trait_) {
+            continue;
+        }
+        let path = module.find_use_path_prefixed(
+            db,
+            hir::ModuleDef::Trait(trait_),
+            ctx.config.insert_use.prefix_kind,
+        );
+        match path {
+            Some(path) => paths.push(mod_path_to_ast(&path)),
+            None => {
+                mark::hit!(inline_function_trait_not_importable);
+                return None;
+            }
+        }
+    }
+    Some(paths)
+}
+
+/// Returns the paths of the macros that have to be imported at the call site for the macro calls
+/// of the body to keep invoking the same macros, or `None` if some of them can't be named there.
+fn required_macro_imports(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+) -> Option<Vec<ast::Path>> {
+    let db = ctx.db();
+    let function = match analyzed_function(ctx, function_source) {
+        Some(it) => it,
+        None => return Some(Vec::new()),
+    };
+    let body = function.body()?;
+
+    let call_site = ctx.sema.scope(call.syntax());
+    let module = call_site.module()?;
+
+    let mut paths = Vec::new();
+    let mut seen = FxHashSet::default();
+    for macro_call in body.syntax().descendants().filter_map(ast::MacroCall::cast) {
+        let macro_def = match ctx.sema.resolve_macro_call(&macro_call) {
+            Some(it) => it,
+            None => continue,
+        };
+        let path = macro_call.path()?;
+        if call_site.speculative_resolve(&path) == Some(PathResolution::Macro(macro_def))
+            || !seen.insert(macro_def)
+        {
+            continue;
+        }
+        // `macro_rules!` macros are only visible after their definition, unless exported.
+        let path = module.find_use_path_prefixed(db, macro_def, ctx.config.insert_use.prefix_kind);
+        match path {
+            Some(path) => paths.push(mod_path_to_ast(&path)),
+            None => {
+                mark::hit!(inline_function_macro_not_nameable);
+                return None;
+            }
+        }
+    }
+    Some(paths)
+}
+
+/// Returns the paths of the items that have to be imported at the call site for the paths of the
+/// body to keep resolving, like `HashMap` imported by the module of the function, or `None` if
+/// some of them can't be imported or are named like something else at the call site.
+///
+/// Only the first segments of the paths are imported, `collections::HashMap` needs `collections`.
+fn required_item_imports(
+    ctx: &AssistContext,
+    call: &ast::Expr,
+    function_source: &InFile<ast::Fn>,
+) -> Option<Vec<ast::Path>> {
+    let db = ctx.db();
+    let function = match analyzed_function(ctx, function_source) {
+        Some(it) => it,
+        None => return Some(Vec::new()),
+    };
+    let body = function.body()?;
+
+    let call_site = ctx.sema.scope(call.syntax());
+    let module = call_site.module()?;
+    // The items and imports of the body are inlined along with it.
+    let body_names = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Item::cast)
+        .flat_map(|item| {
+            item.syntax()
+                .children()
+                .filter_map(ast::Name::cast)
+                .map(|it| it.text().to_string())
+                .chain(item.syntax().descendants().filter_map(ast::UseTree::cast).filter_map(
+                    |tree| match tree.rename() {
+                        Some(rename) => Some(rename.name()?.text().to_string()),
+                        None => Some(tree.path()?.segment()?.name_ref()?.text().to_string()),
+                    },
+                ))
+                .collect::<Vec<_>>()
+        })
+        .collect::<FxHashSet<_>>();
+
+    let mut paths = Vec::new();
+    let mut seen = FxHashSet::default();
+    for path in body.syntax().descendants().filter_map(ast::Path::cast) {
+        let parent_kind = path.syntax().parent().map(|it| it.kind());
+        let in_item = path
+            .syntax()
+            .ancestors()
+            .take_while(|it| it != body.syntax())
+            .any(|it| ast::Item::can_cast(it.kind()));
+        if path.qualifier().is_some()
+            || matches!(parent_kind, Some(USE_TREE) | Some(ATTR) | Some(MACRO_CALL))
+            || in_item
+        {
+            continue;
+        }
+        let name_ref = match path.segment().and_then(|it| it.kind()) {
+            Some(ast::PathSegmentKind::Name(it)) => it,
+            _ => continue,
+        };
+        if body_names.contains(&name_ref.text().to_string()) {
+            continue;
+        }
+        let def = match ctx.sema.resolve_path(&path) {
+            Some(PathResolution::Def(def)) => def,
+            _ => continue,
+        };
+        let at_call_site = call_site.speculative_resolve(&path);
+        if at_call_site == Some(PathResolution::Def(def)) || !seen.insert(def) {
+            continue;
+        }
+        if at_call_site.is_some() {
+            // The import would clash with what the name stands for at the call site.
+            mark::hit!(inline_function_item_name_taken_at_call_site);
+            return None;
+        }
+        match module.find_use_path_prefixed(db, def, ctx.config.insert_use.prefix_kind) {
+            Some(path) => paths.push(mod_path_to_ast(&path)),
+            None => {
+                mark::hit!(inline_function_item_not_importable);
+                return None;
+            }
+        }
+    }
+    Some(paths)
+}
+
+fn insert_imports(
+    builder: &mut AssistBuilder,
+    ctx: &AssistContext,
+    scope: ImportScope,
+    paths: &[ast::Path],
+) {
+    let call_site = ctx.sema.scope(scope.as_syntax_node());
+    let mut new_scope = scope.clone();
+    for path in paths.iter().cloned() {
+        let already_imported = path.segment().map_or(false, |segment| {
+            let name_only = make::path_unqualified(segment);
+            let resolved = call_site.speculative_resolve(&name_only);
+            resolved.is_some() && resolved == call_site.speculative_resolve(&path)
+        });
+        if already_imported {
+            continue;
+        }
+        let rewriter = insert_use(&new_scope, path, ctx.config.insert_use.merge);
+        match ImportScope::from(rewriter.rewrite(new_scope.as_syntax_node())) {
+            Some(it) => new_scope = it,
+            None => return,
+        }
+    }
+
+    match (scope, new_scope) {
+        (ImportScope::File(old), ImportScope::File(new)) => builder.replace_ast(old, new),
+        (ImportScope::Module(old), ImportScope::Module(new)) => builder.replace_ast(old, new),
+        _ => (),
+    }
+}
+
+/// Checks whether the call being inlined is the only usage of `function`.
+fn is_only_usage(ctx: &AssistContext, function: hir::Function) -> bool {
+    let usages = Definition::ModuleDef(function.into()).usages(&ctx.sema).all();
+    usages.file_ranges().count() == 1
+}
+
+/// Checks whether the code blocks of doc comments in the crate of `function` mention it.
+fn is_referenced_from_doc_tests(ctx: &AssistContext, function: hir::Function) -> bool {
+    let db = ctx.db();
+    let name = function.name(db).to_string();
+    let krate = function.module(db).krate();
+
+    let mut files = FxHashSet::default();
+    let mut modules = vec![krate.root_module(db)];
+    while let Some(module) = modules.pop() {
+        files.insert(module.definition_source(db).file_id.original_file(db));
+        modules.extend(module.children(db));
+    }
+
+    files.into_iter().any(|file_id| doc_tests_mention(ctx, file_id, &name))
+}
+
+fn doc_tests_mention(ctx: &AssistContext, file_id: FileId, name: &str) -> bool {
+    let source_file = ctx.sema.parse(file_id);
+    let mut in_code_block = false;
+    for comment in source_file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter_map(ast::Comment::cast)
+    {
+        let line = match comment.doc_comment() {
+            Some(it) => it.trim(),
+            None => continue,
+        };
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block && mentions_identifier(line, name) {
+            return true;
+        }
+    }
+    false
+}
+
+fn mentions_identifier(text: &str, ident: &str) -> bool {
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(ident).any(|(idx, _)| {
+        let before = text[..idx].chars().next_back();
+        let after = text[idx + ident.len()..].chars().next();
+        !before.map_or(false, is_ident_char) && !after.map_or(false, is_ident_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{
+            check_assist, check_assist_not_applicable, check_assist_not_applicable_with_config,
+            check_assist_with_config, TEST_CONFIG,
+        },
+        AssistConfig,
+    };
+    use hir::Semantics;
+    use ide_db::{
+        base_db::{fixture::WithFixture, FileRange},
+        RootDatabase,
+    };
+    use syntax::TextRange;
+    use test_utils::extract_offset;
+
+    use super::*;
+
+    fn check_inline_error(before: &str, expected: InlineError) {
+        check_inline_error_with_config(TEST_CONFIG, before, expected)
+    }
+
+    fn check_inline_error_with_config(config: AssistConfig, before: &str, expected: InlineError) {
+        let (offset, before) = extract_offset(before.trim_start());
+        let (db, file_id) = RootDatabase::with_single_file(&before);
+        let frange = FileRange { file_id, range: TextRange::empty(offset) };
+        let ctx = AssistContext::new(Semantics::new(&db), &config, frange);
+        let call = inlined_call(&ctx).expect("no call at the offset");
+        assert_eq!(can_inline(&ctx, call, false).err(), Some(expected));
+    }
+
+    /// Checks that inlining all the calls, once resolved, does nothing and tells why.
+    fn check_inline_all_reason(before: &str, expected: &str) {
+        let (offset, before) = extract_offset(before.trim_start());
+        let (db, file_id) = RootDatabase::with_single_file(&before);
+        let frange = FileRange { file_id, range: TextRange::empty(offset) };
+        let ctx = AssistContext::new(Semantics::new(&db), &TEST_CONFIG, frange);
+        let mut acc = Assists::new(&ctx, true);
+        inline_function_all(&mut acc, &ctx).expect("assist is not available");
+        let assist = acc.finish().pop().unwrap();
+        assert!(assist.source_change.unwrap().source_file_edits.is_empty());
+        assert_eq!(assist.reason.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn no_args_or_return_value_gets_inlined_without_block() {
+        check_assist(
+            inline_function,
+            r#"
+fn foo() { println!("Hello, World!"); }
+fn main() {
+    fo$0o();
+}
+"#,
+            r#"
+fn foo() { println!("Hello, World!"); }
+fn main() {
+    println!("Hello, World!");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unit_statement_is_replaced_by_the_statements_of_the_body() {
+        check_assist(
+            inline_function,
+            r#"
+fn step() {}
+fn steps() {
+    step();
+    if true {
+        step();
+    }
+}
+fn main() {
+    if true {
+        steps$0();
+    }
+}
+"#,
+            r#"
+fn step() {}
+fn steps() {
+    step();
+    if true {
+        step();
+    }
+}
+fn main() {
+    if true {
+        step();
+        if true {
+            step();
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unit_statement_with_bindings_keeps_its_block() {
+        check_assist(
+            inline_function,
+            r#"
+fn log(msg: &str) { eprintln!("{}", msg); }
+fn main() {
+    log$0("hi");
+}
+"#,
+            r#"
+fn log(msg: &str) { eprintln!("{}", msg); }
+fn main() {
+    {
+        let msg = "hi";
+        eprintln!("{}", msg);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn args_with_side_effects() {
+        check_assist(
+            inline_function,
+            r#"
+fn foo(name: String) { println!("Hello, {}!", name); }
+fn main() {
+    foo$0(String::from("Michael"));
+}
+"#,
+            r#"
+fn foo(name: String) { println!("Hello, {}!", name); }
+fn main() {
+    {
+        let name = String::from("Michael");
+        println!("Hello, {}!", name);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_with_borrowed_place_receiver() {
+        check_assist(
+            inline_function,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn sum(&self) -> u32 { self.x + self.y }
+}
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let s = p.sum$0();
+}
+"#,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn sum(&self) -> u32 { self.x + self.y }
+}
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let s = p.x + p.y;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_receiver_is_bound_if_it_is_not_a_place() {
+        check_assist(
+            inline_function,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+    fn scaled(&self, factor: u32) -> u32 { (self.x + self.y) * factor }
+}
+fn main() {
+    let s = Point::origin().scaled$0(2);
+}
+"#,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+    fn scaled(&self, factor: u32) -> u32 { (self.x + self.y) * factor }
+}
+fn main() {
+    let s = {
+        let this = &Point::origin();
+        (this.x + this.y) * 2
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn receiver_binding_avoids_the_names_in_scope() {
+        check_assist(
+            inline_function,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+    fn scaled(&self, factor: u32) -> u32 { (self.x + self.y) * factor }
+}
+fn main() {
+    let this = 2;
+    let s = Point::origin().scaled$0(this);
+}
+"#,
+            r#"
+struct Point { x: u32, y: u32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+    fn scaled(&self, factor: u32) -> u32 { (self.x + self.y) * factor }
+}
+fn main() {
+    let this = 2;
+    let s = {
+        let this1 = &Point::origin();
+        (this1.x + this1.y) * this
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_receiver_passed_on_is_bound() {
+        check_assist(
+            inline_function,
+            r#"
+struct Counter { count: u32 }
+fn bump(counter: &mut Counter) { counter.count += 1; }
+impl Counter {
+    fn bump_twice(&mut self) {
+        bump(self);
+        bump(self);
+    }
+}
+fn main(counter: &mut Counter) {
+    counter.bump_twice$0();
+}
+"#,
+            r#"
+struct Counter { count: u32 }
+fn bump(counter: &mut Counter) { counter.count += 1; }
+impl Counter {
+    fn bump_twice(&mut self) {
+        bump(self);
+        bump(self);
+    }
+}
+fn main(counter: &mut Counter) {
+    {
+        let this = &mut *counter;
+        bump(this);
+        bump(this);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_provided_trait_methods() {
+        mark::check!(inline_function_generic_self);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+trait Describe {
+    fn name(&self) -> u32;
+    fn describe(&self) -> u32 { self.name() }
+}
+struct Foo;
+impl Describe for Foo {
+    fn name(&self) -> u32 { 0 }
+}
+fn main() { Foo.describe$0(); }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_self_paths_of_generic_impls() {
+        mark::check!(inline_function_self_of_generic_impl);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+struct Wrapper<T>(T);
+impl<T> Wrapper<T> {
+    fn rewrap(self) -> Self { Self(self.0) }
+}
+fn main() { let w = Wrapper(1).rewrap$0(); }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_methods_called_through_autoderef() {
+        mark::check!(inline_function_receiver_needs_autoderef);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+struct Foo;
+impl Foo { fn bar(&self) {} }
+fn main(foo: &&Foo) { foo.bar$0(); }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_on_the_receiver_of_a_method_call() {
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+struct Foo;
+impl Foo { fn bar(&self) {} }
+fn foo() -> Foo { Foo }
+fn main() { foo()$0.bar(); }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_incorrect_number_of_parameters_are_provided() {
+        mark::check!(inline_function_incorrect_number_of_arguments);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add$0(42); }
+"#,
+        );
+    }
+
+    #[test]
+    fn warns_when_last_usage_is_referenced_in_doc_tests() {
+        mark::check!(inline_function_referenced_in_doc_tests);
+        check_assist(
+            inline_function,
+            r#"
+/// ```
+/// assert_eq!(add(1, 2), 3);
+/// ```
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = add$0(1, 2);
+}
+"#,
+            r#"
+/// ```
+/// assert_eq!(add(1, 2), 3);
+/// ```
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = 1 + 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn merges_body_imports_into_call_site_imports() {
+        check_assist(
+            inline_function,
+            r#"
+mod foo {
+    pub fn a() {}
+    pub fn b() {}
+}
+use foo::b;
+
+fn bar() {
+    use foo::a;
+    a();
+}
+fn main() {
+    b();
+    bar$0();
+}
+"#,
+            r#"
+mod foo {
+    pub fn a() {}
+    pub fn b() {}
+}
+use foo::{a, b};
+
+fn bar() {
+    use foo::a;
+    a();
+}
+fn main() {
+    b();
+    a();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn drops_body_imports_already_present_at_call_site() {
+        check_assist(
+            inline_function,
+            r#"
+mod foo {
+    pub fn b() {}
+}
+use foo::b;
+
+fn bar() {
+    use foo::b;
+    b();
+}
+fn main() {
+    bar$0();
+}
+"#,
+            r#"
+mod foo {
+    pub fn b() {}
+}
+use foo::b;
+
+fn bar() {
+    use foo::b;
+    b();
+}
+fn main() {
+    b();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn single_expression_body_is_inlined_without_block() {
+        check_assist(
+            inline_function,
+            r#"
+fn three() -> u32 { 1 + 2 }
+fn main() {
+    let x = three$0();
+}
+"#,
+            r#"
+fn three() -> u32 { 1 + 2 }
+fn main() {
+    let x = 1 + 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn single_expression_body_is_parenthesized_if_needed() {
+        check_assist(
+            inline_function,
+            r#"
+fn three() -> u32 { 1 + 2 }
+fn main() {
+    let x = three$0() * 2;
+}
+"#,
+            r#"
+fn three() -> u32 { 1 + 2 }
+fn main() {
+    let x = (1 + 2) * 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn function_returning_reference_to_parameter() {
+        check_assist(
+            inline_function,
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let name = String::new();
+    let x = as_str$0(&name);
+}
+"#,
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let name = String::new();
+    let x = {
+        let s = &name;
+        &s[..]
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_returning_reference_to_temporary_argument() {
+        mark::check!(inline_function_returns_borrow_of_temporary);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let n = 1 + as_str$0(&String::new()).len();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn promotes_temporary_argument_borrowed_by_the_result() {
+        check_assist(
+            inline_function,
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let n = as_str$0(&String::new()).len();
+}
+"#,
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let s = &String::new();
+    let n = (&s[..]).len();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_moving_unused_argument_into_closure() {
+        check_assist(
+            inline_function,
+            r#"
+struct Token;
+fn consume(_token: Token, n: u32) -> u32 { n + 1 }
+fn main() {
+    let token = Token;
+    let f = || consume$0(token, 1);
+}
+"#,
+            r#"
+struct Token;
+fn consume(_token: Token, n: u32) -> u32 { n + 1 }
+fn main() {
+    let token = Token;
+    let f = || {
+        let _token = token;
+        1 + 1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn drops_unused_argument_declared_in_closure() {
+        check_assist(
+            inline_function,
+            r#"
+struct Token;
+fn consume(_token: Token, n: u32) -> u32 { n + 1 }
+fn main() {
+    let f = || {
+        let token = Token;
+        consume$0(token, 1)
+    };
+}
+"#,
+            r#"
+struct Token;
+fn consume(_token: Token, n: u32) -> u32 { n + 1 }
+fn main() {
+    let f = || {
+        let token = Token;
+        1 + 1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn splices_method_chain_into_the_callers_chain() {
+        check_assist(
+            inline_function,
+            r#"
+struct Iter;
+impl Iter {
+    fn map(self, f: fn(u32) -> u32) -> Iter { self }
+    fn filter(self, f: fn(&u32) -> bool) -> Iter { self }
+    fn collect(self) -> Vec<u32> { Vec::new() }
+}
+fn evens_doubled(iter: Iter) -> Iter {
+    iter.map(|it| it * 2).filter(|it| it % 4 == 0)
+}
+fn main() {
+    let v = evens_doubled$0(Iter).collect();
+}
+"#,
+            r#"
+struct Iter;
+impl Iter {
+    fn map(self, f: fn(u32) -> u32) -> Iter { self }
+    fn filter(self, f: fn(&u32) -> bool) -> Iter { self }
+    fn collect(self) -> Vec<u32> { Vec::new() }
+}
+fn evens_doubled(iter: Iter) -> Iter {
+    iter.map(|it| it * 2).filter(|it| it % 4 == 0)
+}
+fn main() {
+    let v = Iter.map(|it| it * 2).filter(|it| it % 4 == 0).collect();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn does_not_splice_body_that_is_not_a_chain_on_the_param() {
+        check_assist(
+            inline_function,
+            r#"
+struct Iter;
+impl Iter {
+    fn map(self, f: fn(u32) -> u32) -> Iter { self }
+    fn collect(self) -> Vec<u32> { Vec::new() }
+}
+fn doubled(iter: Iter) -> Iter {
+    let iter = iter;
+    iter.map(|it| it * 2)
+}
+fn main() {
+    let v = doubled$0(Iter).collect();
+}
+"#,
+            r#"
+struct Iter;
+impl Iter {
+    fn map(self, f: fn(u32) -> u32) -> Iter { self }
+    fn collect(self) -> Vec<u32> { Vec::new() }
+}
+fn doubled(iter: Iter) -> Iter {
+    let iter = iter;
+    iter.map(|it| it * 2)
+}
+fn main() {
+    let v = {
+        let iter = Iter;
+        let iter = iter;
+        iter.map(|it| it * 2)
+    }.collect();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inlines_wrapper_supplying_default_arguments() {
+        check_assist(
+            inline_function,
+            r#"
+fn connect(host: &str, port: u16, retries: u32) {}
+fn connect_default(host: &str) { connect(host, 8080, 3) }
+fn main() {
+    connect_default$0(&format!("{}.local", "db"));
+}
+"#,
+            r#"
+fn connect(host: &str, port: u16, retries: u32) {}
+fn connect_default(host: &str) { connect(host, 8080, 3) }
+fn main() {
+    connect(&format!("{}.local", "db"), 8080, 3);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn does_not_forward_arguments_that_are_not_passed_on_as_is() {
+        check_assist(
+            inline_function,
+            r#"
+fn connect(host: &str, port: u16) {}
+fn connect_next(host: &str, port: u16) { connect(host, port + 1) }
+fn main() {
+    connect_next$0(&String::new(), 8080);
+}
+"#,
+            r#"
+fn connect(host: &str, port: u16) {}
+fn connect_next(host: &str, port: u16) { connect(host, port + 1) }
+fn main() {
+    connect(&String::new(), 8080 + 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replaces_self_assoc_types_with_concrete_types() {
+        check_assist(
+            inline_function,
+            r#"
+trait Source {
+    type Item;
+    fn first() -> Self::Item;
+}
+struct Counter;
+impl Source for Counter {
+    type Item = u32;
+    fn first() -> Self::Item {
+        let x: Self::Item = 0;
+        x
+    }
+}
+fn main() {
+    let n = Counter::first$0();
+}
+"#,
+            r#"
+trait Source {
+    type Item;
+    fn first() -> Self::Item;
+}
+struct Counter;
+impl Source for Counter {
+    type Item = u32;
+    fn first() -> Self::Item {
+        let x: Self::Item = 0;
+        x
+    }
+}
+fn main() {
+    let n = {
+        let x: u32 = 0;
+        x
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_assoc_type_qualifies_a_path() {
+        mark::check!(inline_function_assoc_type_not_nameable);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+trait Source {
+    type Item;
+    fn first() -> Self::Item;
+}
+struct Counter;
+#[derive(Default)]
+struct Count;
+impl Source for Counter {
+    type Item = Count;
+    fn first() -> Self::Item {
+        Self::Item::default()
+    }
+}
+fn main() {
+    let n = Counter::first$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_deref_coercion_of_the_return_type() {
+        check_assist(
+            inline_function,
+            r#"
+#[lang = "deref"]
+trait Deref {
+    type Target: ?Sized;
+    fn deref(&self) -> &Self::Target;
+}
+struct String;
+impl Deref for String {
+    type Target = str;
+    fn deref(&self) -> &str { loop {} }
+}
+struct Person { name: String }
+fn name(person: &Person) -> &str {
+    &person.name
+}
+fn greet(person: &Person) {
+    let name = name$0(person);
+}
+"#,
+            r#"
+#[lang = "deref"]
+trait Deref {
+    type Target: ?Sized;
+    fn deref(&self) -> &Self::Target;
+}
+struct String;
+impl Deref for String {
+    type Target = str;
+    fn deref(&self) -> &str { loop {} }
+}
+struct Person { name: String }
+fn name(person: &Person) -> &str {
+    &person.name
+}
+fn greet(person: &Person) {
+    let name = {
+        let person = person;
+        &*person.name
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_return_type_coerces_mutability() {
+        mark::check!(inline_function_return_coercion_not_reproducible);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+struct Counter { n: u32 }
+fn count(counter: &mut Counter) -> &u32 {
+    &mut counter.n
+}
+fn main() {
+    let mut counter = Counter { n: 0 };
+    let n = count$0(&mut counter);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inlines_braceless_match_arm_body() {
+        check_assist(
+            inline_function,
+            r#"
+fn one() -> u32 { 1 }
+fn add(a: u32, b: u32) -> u32 { a * a + b }
+fn main() {
+    let x = match 1 {
+        0 => add$0(one(), 2),
+        _ => 0,
+    };
+}
+"#,
+            r#"
+fn one() -> u32 { 1 }
+fn add(a: u32, b: u32) -> u32 { a * a + b }
+fn main() {
+    let x = match 1 {
+        0 => {
+            let a = one();
+            a * a + 2
+        }
+        _ => 0,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn renames_body_labels_used_around_the_call() {
+        check_assist(
+            inline_function,
+            r#"
+fn find(items: &[u32]) -> Option<u32> {
+    let mut found = None;
+    'search: for item in items {
+        if *item > 1 {
+            found = Some(*item);
+            break 'search;
+        }
+    }
+    found
+}
+fn main() {
+    'search: loop {
+        let found = find$0(&[1, 2]);
+        break 'search;
+    }
+}
+"#,
+            r#"
+fn find(items: &[u32]) -> Option<u32> {
+    let mut found = None;
+    'search: for item in items {
+        if *item > 1 {
+            found = Some(*item);
+            break 'search;
+        }
+    }
+    found
+}
+fn main() {
+    'search: loop {
+        let found = {
+            let items = &[1, 2];
+            let mut found = None;
+            'search_1: for item in items {
+                if *item > 1 {
+                    found = Some(*item);
+                    break 'search_1;
+                }
+            }
+            found
+        };
+        break 'search;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_recursive_functions() {
+        mark::check!(inline_function_recursive_function);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod math {
+    pub fn factorial(n: u64) -> u64 {
+        if n == 0 { 1 } else { n * factorial(n - 1) }
+    }
+}
+fn main() {
+    let x = math::factorial$0(5);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_functions_returning_early() {
+        mark::check!(inline_function_early_return);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn f(x: i32) -> i32 {
+    if x < 0 {
+        return 0;
+    }
+    x * 2
+}
+fn main() {
+    let y = f$0(-1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn returns_of_closures_in_the_body_are_kept() {
+        check_assist(
+            inline_function,
+            r#"
+fn f(x: i32) -> i32 {
+    let abs = |x: i32| {
+        if x < 0 {
+            return -x;
+        }
+        x
+    };
+    abs(x) * 2
+}
+fn main() {
+    let y = f$0(-1);
+}
+"#,
+            r#"
+fn f(x: i32) -> i32 {
+    let abs = |x: i32| {
+        if x < 0 {
+            return -x;
+        }
+        x
+    };
+    abs(x) * 2
+}
+fn main() {
+    let y = {
+        let x = -1;
+        let abs = |x: i32| {
+            if x < 0 {
+                return -x;
+            }
+            x
+        };
+        abs(x) * 2
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_recursive_call_in_the_function_itself() {
+        mark::check!(inline_function_recursive_call_in_itself);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn factorial(n: u64) -> u64 {
+    if n == 0 { 1 } else { n * factorial$0(n - 1) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rewrites_super_paths_for_the_call_site() {
+        check_assist(
+            inline_function,
+            r#"
+mod net {
+    pub fn helper() -> u32 { 42 }
+    pub mod tcp {
+        pub fn connect() -> u32 { super::helper() + self::PORT }
+        pub const PORT: u32 = 80;
+    }
+}
+mod app {
+    pub mod client {
+        fn main() {
+            let x = crate::net::tcp::connect$0();
+        }
+    }
+}
+"#,
+            r#"
+mod net {
+    pub fn helper() -> u32 { 42 }
+    pub mod tcp {
+        pub fn connect() -> u32 { super::helper() + self::PORT }
+        pub const PORT: u32 = 80;
+    }
+}
+mod app {
+    pub mod client {
+        fn main() {
+            let x = crate::net::helper() + crate::net::tcp::PORT;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_super_path_is_not_visible_from_call_site() {
+        mark::check!(inline_function_relative_path_not_visible);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod net {
+    fn helper() -> u32 { 42 }
+    pub mod tcp {
+        pub fn connect() -> u32 { super::helper() }
+    }
+}
+fn main() {
+    let x = net::tcp::connect$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_body_accesses_private_fields() {
+        mark::check!(inline_function_body_item_not_visible);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod geometry {
+    pub struct Point { x: u32 }
+    pub fn x_of(point: &Point) -> u32 { point.x }
+}
+fn main(point: geometry::Point) {
+    let x = geometry::x_of$0(&point);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn const_fn_inlined_as_array_length() {
+        check_assist(
+            inline_function,
+            r#"
+const fn compute_size() -> usize { 4 * 8 }
+fn main() {
+    let buf = [0u8; compute_size$0()];
+}
+"#,
+            r#"
+const fn compute_size() -> usize { 4 * 8 }
+fn main() {
+    let buf = [0u8; 4 * 8];
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn const_fn_inlined_in_const_generic_argument() {
+        check_assist(
+            inline_function,
+            r#"
+struct Buf<const N: usize>;
+const fn compute_size() -> usize { 4 * 8 }
+fn main() {
+    let buf: Buf<{ compute_size$0() }> = Buf;
+}
+"#,
+            r#"
+struct Buf<const N: usize>;
+const fn compute_size() -> usize { 4 * 8 }
+fn main() {
+    let buf: Buf<{ 4 * 8 }> = Buf;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_non_const_fn_in_const_context() {
+        mark::check!(inline_function_const_context_requires_const_fn);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn compute_size() -> usize { 4 * 8 }
+const SIZE: usize = compute_size$0();
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_const_fn_needing_bindings_in_const_context() {
+        mark::check!(inline_function_const_context_requires_single_expression);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+const fn double(n: usize) -> usize { n * 2 }
+fn main() {
+    let buf: [u8; double$0(4)];
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn identity_function_is_replaced_by_its_argument() {
+        check_assist(
+            inline_function,
+            r#"
+fn id<T>(x: T) -> T { x }
+fn main() {
+    let a = 1;
+    let b = 2;
+    let x = id$0(a + b);
+}
+"#,
+            r#"
+fn id<T>(x: T) -> T { x }
+fn main() {
+    let a = 1;
+    let b = 2;
+    let x = a + b;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn identity_function_argument_is_parenthesized_if_needed() {
+        check_assist(
+            inline_function,
+            r#"
+fn id<T>(x: T) -> T { x }
+fn main() {
+    let a = 1;
+    let b = 2;
+    let x = id$0(a + b) * 2;
+}
+"#,
+            r#"
+fn id<T>(x: T) -> T { x }
+fn main() {
+    let a = 1;
+    let b = 2;
+    let x = (a + b) * 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_traits_of_method_calls_in_body() {
+        check_assist(
+            inline_function,
+            r#"
+mod ops {
+    pub trait Add {
+        fn add(self, other: Self) -> Self;
+    }
+}
+mod math {
+    use crate::ops::Add;
+    pub struct V(pub u32);
+    impl Add for V {
+        fn add(self, other: V) -> V { V(self.0 + other.0) }
+    }
+    pub fn sum(a: V, b: V) -> V { a.add(b) }
+}
+fn main() {
+    let x = math::sum$0(math::V(1), math::V(2));
+}
+"#,
+            r#"
+use ops::Add;
+
+mod ops {
+    pub trait Add {
+        fn add(self, other: Self) -> Self;
+    }
+}
+mod math {
+    use crate::ops::Add;
+    pub struct V(pub u32);
+    impl Add for V {
+        fn add(self, other: V) -> V { V(self.0 + other.0) }
+    }
+    pub fn sum(a: V, b: V) -> V { a.add(b) }
+}
+fn main() {
+    let x = {
+        let a = math::V(1);
+        let b = math::V(2);
+        a.add(b)
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_trait_of_method_call_is_private() {
+        mark::check!(inline_function_trait_not_importable);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod math {
+    trait Double {
+        fn double(self) -> Self;
+    }
+    impl Double for u32 {
+        fn double(self) -> u32 { self * 2 }
+    }
+    pub fn quadruple(a: u32) -> u32 { a.double().double() }
+}
+fn main() {
+    let x = math::quadruple$0(1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_items_imported_by_module_of_function() {
+        check_assist(
+            inline_function,
+            r#"
+mod collections {
+    pub struct HashMap;
+    impl HashMap {
+        pub fn new() -> HashMap { HashMap }
+    }
+}
+mod cache {
+    use crate::collections::HashMap;
+    pub fn empty() -> HashMap { HashMap::new() }
+}
+fn main() {
+    let map = cache::empty$0();
+}
+"#,
+            r#"
+use collections::HashMap;
+
+mod collections {
+    pub struct HashMap;
+    impl HashMap {
+        pub fn new() -> HashMap { HashMap }
+    }
+}
+mod cache {
+    use crate::collections::HashMap;
+    pub fn empty() -> HashMap { HashMap::new() }
+}
+fn main() {
+    let map = HashMap::new();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_item_of_body_is_not_importable() {
+        mark::check!(inline_function_item_not_importable);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod cache {
+    use self::entries::Entry;
+    mod entries {
+        pub struct Entry;
+    }
+    pub fn entry() { let _ = Entry; }
+}
+fn main() {
+    cache::entry$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_name_of_body_item_is_taken_at_call_site() {
+        mark::check!(inline_function_item_name_taken_at_call_site);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod collections {
+    pub struct HashMap;
+}
+mod cache {
+    use crate::collections::HashMap;
+    pub fn empty() { let _ = HashMap; }
+}
+struct HashMap;
+fn main() {
+    cache::empty$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_exported_macros_called_in_body() {
+        check_assist(
+            inline_function,
+            r#"
+mod math {
+    #[macro_export]
+    macro_rules! double {
+        ($e:expr) => { $e * 2 };
+    }
+    pub fn quadruple(a: u32) -> u32 { double!(double!(a)) }
+}
+mod other {
+    fn f() {
+        let x = crate::math::quadruple$0(1);
+    }
+}
+"#,
+            r#"
+mod math {
+    #[macro_export]
+    macro_rules! double {
+        ($e:expr) => { $e * 2 };
+    }
+    pub fn quadruple(a: u32) -> u32 { double!(double!(a)) }
+}
+mod other {
+    use crate::double;
+
+    fn f() {
+        let x = {
+            let a = 1;
+            double!(double!(a))
+        };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn macros_visible_at_call_site_need_no_import() {
+        check_assist(
+            inline_function,
+            r#"
+macro_rules! double {
+    ($e:expr) => { $e * 2 };
+}
+mod math {
+    pub fn twice(a: u32) -> u32 { double!(a) }
+}
+fn main() {
+    let x = math::twice$0(1);
+}
+"#,
+            r#"
+macro_rules! double {
+    ($e:expr) => { $e * 2 };
+}
+mod math {
+    pub fn twice(a: u32) -> u32 { double!(a) }
+}
+fn main() {
+    let x = {
+        let a = 1;
+        double!(a)
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_body_calls_module_local_macro() {
+        mark::check!(inline_function_macro_not_nameable);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod math {
+    macro_rules! double {
+        ($e:expr) => { $e * 2 };
+    }
+    pub fn twice(a: u32) -> u32 { double!(a) }
+}
+fn main() {
+    let x = math::twice$0(1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn operators_overloaded_in_body_need_no_import() {
+        check_assist(
+            inline_function,
+            r#"
+mod ops {
+    #[lang = "add"]
+    pub trait Add<Rhs = Self> {
+        type Output;
+        fn add(self, rhs: Rhs) -> Self::Output;
+    }
+}
+mod math {
+    use crate::ops::Add;
+    pub struct V(pub u32);
+    impl Add for V {
+        type Output = V;
+        fn add(self, other: V) -> V { V(self.0 + other.0) }
+    }
+    pub fn sum(a: V, b: V) -> V { a + b }
+}
+fn main() {
+    let x = math::sum$0(math::V(1), math::V(2));
+}
+"#,
+            r#"
+mod ops {
+    #[lang = "add"]
+    pub trait Add<Rhs = Self> {
+        type Output;
+        fn add(self, rhs: Rhs) -> Self::Output;
+    }
+}
+mod math {
+    use crate::ops::Add;
+    pub struct V(pub u32);
+    impl Add for V {
+        type Output = V;
+        fn add(self, other: V) -> V { V(self.0 + other.0) }
+    }
+    pub fn sum(a: V, b: V) -> V { a + b }
+}
+fn main() {
+    let x = {
+        let a = math::V(1);
+        let b = math::V(2);
+        a + b
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unused_parameters_are_not_bound() {
+        check_assist(
+            inline_function,
+            r#"
+fn first(a: u32, _b: u32, _: u32) -> u32 {
+    let c = a * 2;
+    c
+}
+fn main() {
+    let x = first$0(1, 2, 3);
+}
+"#,
+            r#"
+fn first(a: u32, _b: u32, _: u32) -> u32 {
+    let c = a * 2;
+    c
+}
+fn main() {
+    let x = {
+        let c = 1 * 2;
+        c
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unused_parameters_with_side_effecting_arguments_are_evaluated() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn last(a: u32, b: u32) -> u32 { b }
+fn main() {
+    let x = last$0(compute(), 1);
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn last(a: u32, b: u32) -> u32 { b }
+fn main() {
+    let x = {
+        let _ = compute();
+        1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameters_used_once_take_their_argument() {
+        check_assist(
+            inline_function,
+            r#"
+struct Bytes;
+fn make_bytes() -> Bytes { Bytes }
+struct Foo(Bytes);
+impl Foo { fn new(v: Bytes) -> Foo { Foo(v) } }
+fn wrap(v: Bytes) -> Foo {
+    let foo = Foo::new(v);
+    foo
+}
+fn main() {
+    let foo = wrap$0(make_bytes());
+}
+"#,
+            r#"
+struct Bytes;
+fn make_bytes() -> Bytes { Bytes }
+struct Foo(Bytes);
+impl Foo { fn new(v: Bytes) -> Foo { Foo(v) } }
+fn wrap(v: Bytes) -> Foo {
+    let foo = Foo::new(v);
+    foo
+}
+fn main() {
+    let foo = {
+        let foo = Foo::new(make_bytes());
+        foo
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameters_used_twice_are_bound() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn double(x: u32) -> u32 { x + x }
+fn main() {
+    let y = double$0(compute());
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn double(x: u32) -> u32 { x + x }
+fn main() {
+    let y = {
+        let x = compute();
+        x + x
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameters_used_once_after_side_effects_are_bound() {
+        check_assist(
+            inline_function,
+            r#"
+fn log() {}
+fn compute() -> u32 { 2 }
+fn logged(x: u32) -> u32 {
+    log();
+    x
+}
+fn main() {
+    let y = logged$0(compute());
+}
+"#,
+            r#"
+fn log() {}
+fn compute() -> u32 { 2 }
+fn logged(x: u32) -> u32 {
+    log();
+    x
+}
+fn main() {
+    let y = {
+        let x = compute();
+        log();
+        x
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn side_effecting_arguments_keep_their_order() {
+        check_assist(
+            inline_function,
+            r#"
+fn one() -> u32 { 1 }
+fn two() -> u32 { 2 }
+fn sub(a: u32, b: u32) -> u32 { b - a }
+fn main() {
+    let y = sub$0(one(), two());
+}
+"#,
+            r#"
+fn one() -> u32 { 1 }
+fn two() -> u32 { 2 }
+fn sub(a: u32, b: u32) -> u32 { b - a }
+fn main() {
+    let y = {
+        let a = one();
+        let b = two();
+        b - a
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_arguments_call_the_function() {
+        mark::check!(inline_function_argument_calls_function);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn foo(x: u32) -> u32 { x + 1 }
+fn main() {
+    let x = foo$0(foo(1));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn arguments_before_side_effecting_arguments_are_bound_in_order() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn sum(a: u32, b: u32, c: u32) -> u32 { a + b * b + c }
+fn main() {
+    let x = sum$0(1, compute(), 3);
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn sum(a: u32, b: u32, c: u32) -> u32 { a + b * b + c }
+fn main() {
+    let x = {
+        let a = 1;
+        let b = compute();
+        a + b * b + 3
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wildcard_parameters_with_side_effecting_arguments_are_evaluated() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn second(_: u32, y: u32) -> u32 { y }
+fn main() {
+    let x = second$0(compute(), 1);
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn second(_: u32, y: u32) -> u32 { y }
+fn main() {
+    let x = {
+        let _ = compute();
+        1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameters_binding_nothing_drop_their_arguments() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> (u32, u32) { (1, 2) }
+fn third(_: u32, (_, _): (u32, u32), z: u32) -> u32 { z }
+fn main() {
+    let x = third$0(1, compute(), 3);
+}
+"#,
+            r#"
+fn compute() -> (u32, u32) { (1, 2) }
+fn third(_: u32, (_, _): (u32, u32), z: u32) -> u32 { z }
+fn main() {
+    let x = {
+        let _ = compute();
+        3
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn destructuring_parameters_are_bound_with_their_pattern() {
+        check_assist(
+            inline_function,
+            r#"
+struct Point { x: u32, y: u32 }
+fn compute() -> (u32, u32) { (1, 2) }
+fn sum((a, b): (u32, u32), Point { x, y }: Point) -> u32 { a + b + x + y }
+fn main() {
+    let s = sum$0(compute(), Point { x: 3, y: 4 });
+}
+"#,
+            r#"
+struct Point { x: u32, y: u32 }
+fn compute() -> (u32, u32) { (1, 2) }
+fn sum((a, b): (u32, u32), Point { x, y }: Point) -> u32 { a + b + x + y }
+fn main() {
+    let s = {
+        let (a, b) = compute();
+        let Point { x, y } = Point { x: 3, y: 4 };
+        a + b + x + y
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn binding_modes_of_parameters_are_kept() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> (u32, u32) { (1, 2) }
+fn sum((mut a, ref b): (u32, u32), ref c: u32) -> u32 {
+    a += *b;
+    a + *c
+}
+fn main() {
+    let s = sum$0(compute(), 3);
+}
+"#,
+            r#"
+fn compute() -> (u32, u32) { (1, 2) }
+fn sum((mut a, ref b): (u32, u32), ref c: u32) -> u32 {
+    a += *b;
+    a + *c
+}
+fn main() {
+    let s = {
+        let (mut a, ref b) = compute();
+        let ref c = 3;
+        a += *b;
+        a + *c
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn blocks_holding_only_the_call_take_the_body() {
+        check_assist(
+            inline_function,
+            r#"
+fn log() {}
+fn compute() -> u32 {
+    {
+        log();
+        1
+    }
+}
+fn main() {
+    let x = unsafe { compute$0() };
+}
+"#,
+            r#"
+fn log() {}
+fn compute() -> u32 {
+    {
+        log();
+        1
+    }
+}
+fn main() {
+    let x = unsafe {
+        log();
+        1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn side_effecting_arguments_of_empty_bodies_are_evaluated() {
+        check_assist(
+            inline_function,
+            r#"
+fn log(message: &str) -> usize { message.len() }
+fn ignore(_len: usize) {}
+fn main() {
+    ignore$0(log("inlined"));
+}
+"#,
+            r#"
+fn log(message: &str) -> usize { message.len() }
+fn ignore(_len: usize) {}
+fn main() {
+    {
+        let _ = log("inlined");
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn arguments_are_wrapped_in_dbg() {
+        check_assist(
+            inline_function_with_dbg,
+            r#"
+fn id(x: u32) -> u32 { x }
+fn first(a: u32, _b: u32) -> u32 { a }
+fn main() {
+    let x = first$0(id(1), 2);
+}
+"#,
+            r#"
+fn id(x: u32) -> u32 { x }
+fn first(a: u32, _b: u32) -> u32 { a }
+fn main() {
+    let x = {
+        let a = dbg!(id(1));
+        let _ = dbg!(2);
+        a
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_function_with_satisfied_bounds() {
+        check_assist(
+            inline_function,
+            r#"
+trait Display {}
+struct S;
+impl Display for S {}
+fn show<T>(value: T) -> T where T: Display { value }
+fn main() {
+    let x = show$0(S);
+}
+"#,
+            r#"
+trait Display {}
+struct S;
+impl Display for S {}
+fn show<T>(value: T) -> T where T: Display { value }
+fn main() {
+    let x = S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_argument_types_dont_satisfy_bounds() {
+        mark::check!(inline_function_unsatisfied_bounds);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+trait Display {}
+struct S;
+fn show<T>(value: T) -> T where T: Display { value }
+fn main() {
+    let x = show$0(S);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn redundant_nested_block_is_flattened() {
+        check_assist(
+            inline_function,
+            r#"
+fn foo(a: u32) -> u32 {
+    {
+        let b = a * 2;
+        b + 1
+    }
+}
+fn main() {
+    let x = foo$0(1);
+}
+"#,
+            r#"
+fn foo(a: u32) -> u32 {
+    {
+        let b = a * 2;
+        b + 1
+    }
+}
+fn main() {
+    let x = {
+        let b = 1 * 2;
+        b + 1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn single_expression_inlined_into_match_scrutinee() {
+        check_assist(
+            inline_function,
+            r#"
+fn eight() -> u32 { 4 * 2 }
+fn main() {
+    match eight$0() {
+        8 => (),
+        _ => (),
+    }
+}
+"#,
+            r#"
+fn eight() -> u32 { 4 * 2 }
+fn main() {
+    match 4 * 2 {
+        8 => (),
+        _ => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bindings_are_hoisted_before_match_on_inlined_call() {
+        check_assist(
+            inline_function,
+            r#"
+fn add(a: u32, b: u32) -> u32 { a * a + b }
+fn main() {
+    let x = 1;
+    let y = match add$0(x, 2) {
+        3 => true,
+        _ => false,
+    };
+}
+"#,
+            r#"
+fn add(a: u32, b: u32) -> u32 { a * a + b }
+fn main() {
+    let x = 1;
+    let a = x;
+    let y = match a * a + 2 {
+        3 => true,
+        _ => false,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn self_paths_of_associated_function_are_replaced() {
+        check_assist(
+            inline_function,
+            r#"
+mod shapes {
+    pub struct Square(pub u32);
+    impl Square {
+        fn side() -> u32 { 2 }
+        pub fn unit() -> Self {
+            let side = Self::side();
+            Self(side)
+        }
+    }
+    pub fn f() {
+        let s = Square::unit$0();
+    }
+}
+"#,
+            r#"
+mod shapes {
+    pub struct Square(pub u32);
+    impl Square {
+        fn side() -> u32 { 2 }
+        pub fn unit() -> Self {
+            let side = Self::side();
+            Self(side)
+        }
+    }
+    pub fn f() {
+        let s = {
+            let side = Square::side();
+            Square(side)
+        };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_items_accessed_through_self_are_private() {
+        mark::check!(inline_function_self_item_not_visible);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+mod shapes {
+    pub struct Square(pub u32);
+    impl Square {
+        fn side() -> u32 { 2 }
+        pub fn unit() -> Self {
+            let side = Self::side();
+            Self(side)
+        }
+    }
+}
+fn main() {
+    let s = shapes::Square::unit$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn copy_args_with_no_side_effects_get_inlined() {
+        check_assist(
+            inline_function,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn compute() -> u32 { 2 }
+fn scaled(factor: u32, value: u32) -> u32 { value * factor + factor }
+fn main() {
+    let x = 1;
+    let y = scaled$0(compute(), x);
+}
+"#,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn compute() -> u32 { 2 }
+fn scaled(factor: u32, value: u32) -> u32 { value * factor + factor }
+fn main() {
+    let x = 1;
+    let y = {
+        let factor = compute();
+        x * factor + factor
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn tail_return_becomes_the_tail_expression() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn double(x: u32) -> u32 {
+    let doubled = x * 2;
+    return doubled;
+}
+fn main() {
+    let y = double$0(compute());
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn double(x: u32) -> u32 {
+    let doubled = x * 2;
+    return doubled;
+}
+fn main() {
+    let y = {
+        let doubled = compute() * 2;
+        doubled
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameter_shadowing_local_is_renamed() {
+        check_assist(
+            inline_function,
+            r#"
+fn compute() -> u32 { 2 }
+fn offset(start: u32, len: u32) -> u32 { start + len }
+fn main() {
+    let start = compute();
+    let end = offset$0(compute(), start * 2);
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn offset(start: u32, len: u32) -> u32 { start + len }
+fn main() {
+    let start = compute();
+    let end = {
+        let start1 = compute();
+        let len = start * 2;
+        start1 + len
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parameter_shadowing_argument_of_the_caller_is_renamed() {
+        check_assist(
+            inline_function,
+            r#"
+struct Range { start: u32, end: u32 }
+fn compute() -> u32 { 2 }
+fn range(start: u32, end: u32) -> Range { Range { start, end } }
+fn shifted(start: u32) -> Range {
+    range$0(compute(), start + 1)
+}
+"#,
+            r#"
+struct Range { start: u32, end: u32 }
+fn compute() -> u32 { 2 }
+fn range(start: u32, end: u32) -> Range { Range { start, end } }
+fn shifted(start: u32) -> Range {
+    let start1 = compute();
+    let end = start + 1;
+    Range { start: start1, end }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_bindings_of_context_dependent_arguments_are_annotated() {
+        check_assist(
+            inline_function,
+            r#"
+trait Default { fn default() -> Self; }
+impl Default for u32 { fn default() -> Self { 0 } }
+fn compute() -> u32 { 2 }
+fn sum(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = sum$0(Default::default(), compute());
+}
+"#,
+            r#"
+trait Default { fn default() -> Self; }
+impl Default for u32 { fn default() -> Self { 0 } }
+fn compute() -> u32 { 2 }
+fn sum(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = {
+        let a: u32 = Default::default();
+        let b = compute();
+        a + b
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bindings_of_conversions_are_annotated_with_the_parameter_type() {
+        check_assist(
+            inline_function,
+            r#"
+trait Into<T> { fn into(self) -> T; }
+struct Meters(u32);
+type Distance = Meters;
+impl Into<Meters> for u32 { fn into(self) -> Meters { Meters(self) } }
+fn walk(distance: Distance) -> u32 {
+    let steps = distance.0 * 2;
+    steps
+}
+fn main() {
+    let x = 5u32;
+    let steps = walk$0(x.into());
+}
+"#,
+            r#"
+trait Into<T> { fn into(self) -> T; }
+struct Meters(u32);
+type Distance = Meters;
+impl Into<Meters> for u32 { fn into(self) -> Meters { Meters(self) } }
+fn walk(distance: Distance) -> u32 {
+    let steps = distance.0 * 2;
+    steps
+}
+fn main() {
+    let x = 5u32;
+    let steps = {
+        let distance: Distance = x.into();
+        let steps = distance.0 * 2;
+        steps
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn calls_of_closure_arguments_take_the_closure_body() {
+        check_assist(
+            inline_function,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn apply(f: impl Fn(u32) -> u32, x: u32) -> u32 { f(x) }
+fn main() {
+    let z = 1u32;
+    let r = apply$0(|y| y + 1, z);
+}
+"#,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn apply(f: impl Fn(u32) -> u32, x: u32) -> u32 { f(x) }
+fn main() {
+    let z = 1u32;
+    let r = z + 1;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_parameters_are_bound_to_complex_arguments() {
+        check_assist(
+            inline_function,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn apply(f: impl Fn(u32) -> u32, x: u32) -> u32 {
+    let doubled = f(x * 2);
+    doubled + 1
+}
+fn main() {
+    let z = 1u32;
+    let r = apply$0(|y| y * y, z);
+}
+"#,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn apply(f: impl Fn(u32) -> u32, x: u32) -> u32 {
+    let doubled = f(x * 2);
+    doubled + 1
+}
+fn main() {
+    let z = 1u32;
+    let r = {
+        let doubled = {
+            let y = z * 2;
+            y * y
+        };
+        doubled + 1
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_called_more_than_once_is_bound() {
+        mark::check!(inline_function_closure_argument_called_more_than_once);
+        check_assist(
+            inline_function,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn twice(f: impl Fn(u32) -> u32, x: u32) -> u32 { f(f(x)) }
+fn main() {
+    let z = 1u32;
+    let r = twice$0(|y| y + 1, z);
+}
+"#,
+            r#"
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for u32 {}
+fn twice(f: impl Fn(u32) -> u32, x: u32) -> u32 { f(f(x)) }
+fn main() {
+    let z = 1u32;
+    let r = {
+        let f = |y| y + 1;
+        f(f(z))
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_capturing_mutably_is_bound() {
+        mark::check!(inline_function_closure_argument_captures_mutably);
+        check_assist(
+            inline_function,
+            r#"
+fn apply(f: impl FnMut(u32) -> u32, x: u32) -> u32 { f(x) }
+fn main() {
+    let mut total = 0;
+    let r = apply$0(|y| { total += y; y }, 1);
+}
+"#,
+            r#"
+fn apply(f: impl FnMut(u32) -> u32, x: u32) -> u32 { f(x) }
+fn main() {
+    let mut total = 0;
+    let r = {
+        let f = |y| { total += y; y };
+        f(1)
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inlines_all_calls_and_removes_the_function() {
+        check_assist(
+            inline_function_all,
+            r#"
+fn compute() -> u32 { 2 }
+fn double$0(x: u32) -> u32 {
+    let doubled = x * 2;
+    doubled
+}
+fn main() {
+    let a = double(compute());
+    let b = double(a);
+}
+"#,
+            r#"
+fn compute() -> u32 { 2 }
+fn main() {
+    let a = {
+        let doubled = compute() * 2;
+        doubled
+    };
+    let b = {
+        let doubled = a * 2;
+        doubled
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_all_does_nothing_with_wrong_number_of_arguments() {
+        check_inline_all_reason(
+            r#"
+fn add$0(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = add(1, 2);
+    let y = add(3);
+}
+"#,
+            "Not all the uses of `add` can be inlined",
+        );
+    }
+
+    #[test]
+    fn inline_all_does_nothing_when_doc_tests_call_the_function() {
+        check_inline_all_reason(
+            r#"
+/// ```
+/// assert_eq!(add(1, 2), 3);
+/// ```
+fn add$0(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = add(1, 2);
+}
+"#,
+            "`add` is still called by doc tests",
+        );
+    }
 
-            statements.extend(body.statements());
+    #[test]
+    fn inline_all_does_nothing_to_calls_where_the_function_is_not_visible() {
+        mark::check!(inline_function_all_not_visible);
+        check_inline_all_reason(
+            r#"
+mod math {
+    fn square$0(x: u32) -> u32 { x * x }
+    pub fn cube(x: u32) -> u32 { square(x) * x }
+}
+fn main() {
+    let x = math::square(2);
+}
+"#,
+            "`square` is called where it isn't visible",
+        );
+    }
 
-            let original_indentation = call.indent_level();
-            let replacement = make::block_expr(statements, body.tail_expr())
-                .reset_indent()
-                .indent(original_indentation);
+    #[test]
+    fn generic_params_are_replaced_by_the_turbofish() {
+        check_assist(
+            inline_function,
+            r#"
+trait Zero { fn zero() -> Self; }
+impl Zero for u32 { fn zero() -> Self { 0 } }
+fn zero<T: Zero>() -> T {
+    let zero: T = T::zero();
+    zero
+}
+fn main() {
+    let x = zero$0::<u32>();
+}
+"#,
+            r#"
+trait Zero { fn zero() -> Self; }
+impl Zero for u32 { fn zero() -> Self { 0 } }
+fn zero<T: Zero>() -> T {
+    let zero: T = T::zero();
+    zero
+}
+fn main() {
+    let x = {
+        let zero: u32 = u32::zero();
+        zero
+    };
+}
+"#,
+        );
+    }
 
-            builder.replace_ast(ast::Expr::CallExpr(call), ast::Expr::BlockExpr(replacement));
-        },
-    )
+    #[test]
+    fn const_params_are_replaced_by_the_turbofish() {
+        check_assist(
+            inline_function,
+            r#"
+fn twice<const N: usize>() -> usize { N * 2 }
+fn main() {
+    let x = twice$0::<4>();
+}
+"#,
+            r#"
+fn twice<const N: usize>() -> usize { N * 2 }
+fn main() {
+    let x = 4 * 2;
 }
+"#,
+        );
+    }
 
-fn function_parameter_patterns(value: &ast::Fn) -> Option<Vec<ast::Pat>> {
-    let mut patterns = Vec::new();
+    #[test]
+    fn not_applicable_when_body_mentions_generic_params_not_in_turbofish() {
+        mark::check!(inline_function_generic_args_not_pinned);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+trait Zero { fn zero() -> Self; }
+impl Zero for u32 { fn zero() -> Self { 0 } }
+fn zero<T: Zero>() -> T { T::zero() }
+fn main() {
+    let x: u32 = zero$0();
+}
+"#,
+        );
+    }
 
-    for param in value.param_list()?.params() {
-        let pattern = param.pat()?;
-        patterns.push(pattern);
+    #[test]
+    fn generic_params_are_replaced_by_the_type_params_of_the_caller() {
+        check_assist(
+            inline_function,
+            r#"
+fn copy<T: Clone>(value: &T) -> T { let copied: T = value.clone(); copied }
+fn caller<U: Clone>(u: &U) -> U {
+    copy$0(u)
+}
+"#,
+            r#"
+fn copy<T: Clone>(value: &T) -> T { let copied: T = value.clone(); copied }
+fn caller<U: Clone>(u: &U) -> U {
+    let value = u;
+    let copied: U = value.clone();
+    copied
+}
+"#,
+        );
     }
 
-    Some(patterns)
+    #[test]
+    fn not_applicable_when_caller_type_params_are_ambiguous() {
+        mark::check!(inline_function_caller_type_params_ambiguous);
+        check_assist_not_applicable(
+            inline_function,
+            r#"
+fn pair<T>(a: T, b: T) -> (T, T) { let p: (T, T) = (a, b); p }
+fn caller<U, V>(u: U, v: V) {
+    pair$0(u, v);
 }
+"#,
+        );
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable};
+    #[test]
+    fn comments_of_the_body_are_kept() {
+        check_assist(
+            inline_function,
+            r#"
+fn foo(a: u32) -> u32 {
+    // Doubles the value.
+    let b = a * 2; // Can't overflow.
+    /* Then adds one. */
+    b + 1 // The tail.
+    // The end.
+}
+fn main() {
+    let x = foo$0(1);
+}
+"#,
+            r#"
+fn foo(a: u32) -> u32 {
+    // Doubles the value.
+    let b = a * 2; // Can't overflow.
+    /* Then adds one. */
+    b + 1 // The tail.
+    // The end.
+}
+fn main() {
+    let x = {
+        // Doubles the value.
+        let b = 1 * 2; // Can't overflow.
+        /* Then adds one. */
+        b + 1 // The tail.
+        // The end.
+    };
+}
+"#,
+        );
+    }
 
-    use super::*;
+    #[test]
+    fn comments_of_unit_statements_are_kept() {
+        check_assist(
+            inline_function,
+            r#"
+fn log(x: u32) {}
+fn foo() {
+    // Logs twice.
+    log(1);
+    return log(2); // Done.
+}
+fn main() {
+    foo$0();
+}
+"#,
+            r#"
+fn log(x: u32) {}
+fn foo() {
+    // Logs twice.
+    log(1);
+    return log(2); // Done.
+}
+fn main() {
+    // Logs twice.
+    log(1);
+    log(2); // Done.
+}
+"#,
+        );
+    }
 
     #[test]
-    fn no_args_or_return_value_gets_inlined_without_block() {
+    fn unsafe_operations_are_wrapped_in_an_unsafe_block() {
         check_assist(
             inline_function,
             r#"
-fn foo() { println!("Hello, World!"); }
+unsafe fn read(p: *const u32) -> u32 {
+    *p
+}
 fn main() {
-    fo$0o();
+    let value = 1;
+    let p = &value as *const u32;
+    let x = read$0(p);
 }
 "#,
             r#"
-fn foo() { println!("Hello, World!"); }
+unsafe fn read(p: *const u32) -> u32 {
+    *p
+}
 fn main() {
-    {
-        println!("Hello, World!");
+    let value = 1;
+    let p = &value as *const u32;
+    let x = unsafe {
+        *p
     };
 }
 "#,
@@ -119,48 +6257,104 @@ fn main() {
     }
 
     #[test]
-    fn args_with_side_effects() {
+    fn no_unsafe_block_in_unsafe_context() {
         check_assist(
             inline_function,
             r#"
-fn foo(name: String) { println!("Hello, {}!", name); }
+static mut HITS: u32 = 0;
+unsafe fn hits() -> u32 {
+    HITS
+}
 fn main() {
-    foo$0(String::from("Michael"));
+    let x = unsafe { hits$0() + 1 };
 }
 "#,
             r#"
-fn foo(name: String) { println!("Hello, {}!", name); }
+static mut HITS: u32 = 0;
+unsafe fn hits() -> u32 {
+    HITS
+}
 fn main() {
-    {
-        let name = String::from("Michael");
-        println!("Hello, {}!", name);
-    };
+    let x = unsafe { HITS + 1 };
 }
 "#,
         );
     }
 
     #[test]
-    fn method_inlining_isnt_supported() {
+    fn not_applicable_when_unsafe_code_is_forbidden() {
+        mark::check!(inline_function_unsafe_code_forbidden);
         check_assist_not_applicable(
             inline_function,
-            r"
-struct Foo;
-impl Foo { fn bar(&self) {} }
+            r#"
+mod raw {
+    pub unsafe fn read(p: *const u32) -> u32 {
+        *p
+    }
+}
+#[forbid(unsafe_code)]
+fn main() {
+    let value = 1;
+    let p = &value as *const u32;
+    let x = raw::read$0(p);
+}
+"#,
+        );
+    }
+
+    const VERIFYING_CONFIG: AssistConfig = AssistConfig { verify_inlining: true, ..TEST_CONFIG };
 
-fn main() { Foo.bar$0(); }
-",
+    #[test]
+    fn verified_inlining_declines_paths_not_resolving_at_call_site() {
+        mark::check!(inline_function_unresolved_path_at_call_site);
+        check_assist_not_applicable_with_config(
+            inline_function,
+            VERIFYING_CONFIG,
+            r#"
+mod shapes {
+    pub fn unit() { missing() }
+}
+fn main() {
+    let u = shapes::unit$0();
+}
+"#,
         );
     }
 
     #[test]
-    fn not_applicable_when_incorrect_number_of_parameters_are_provided() {
-        mark::check!(inline_function_incorrect_number_of_arguments);
-        check_assist_not_applicable(
+    fn verified_inlining_accepts_bindings_and_items_of_the_body() {
+        check_assist_with_config(
             inline_function,
+            VERIFYING_CONFIG,
             r#"
-fn add(a: u32, b: u32) -> u32 { a + b }
-fn main() { let x = add$0(42); }
+mod shapes {
+    pub fn compute() -> u32 { 2 }
+    pub fn area(side: u32) -> u32 {
+        fn square(x: u32) -> u32 { x * x }
+        let area = square(side);
+        area + crate::shapes::compute()
+    }
+}
+fn main() {
+    let a = shapes::area$0(shapes::compute());
+}
+"#,
+            r#"
+mod shapes {
+    pub fn compute() -> u32 { 2 }
+    pub fn area(side: u32) -> u32 {
+        fn square(x: u32) -> u32 { x * x }
+        let area = square(side);
+        area + crate::shapes::compute()
+    }
+}
+fn main() {
+    let a = {
+        fn square(x: u32) -> u32 { x * x }
+        let area = square(shapes::compute());
+        area + crate::shapes::compute()
+    };
+}
 "#,
         );
     }
@@ -189,14 +6383,214 @@ fn foo(a: u32, b: u32) -> u32 {
 
 fn main() {
     let x = {
-        let a = 1;
-        let b = 2;
-        let x = a + b;
-        let y = x - b;
+        let x = 1 + 2;
+        let y = x - 2;
         x * y
     };
 }
 "#,
         );
     }
+
+    #[test]
+    fn folds_conditions_on_bool_literal_arguments() {
+        check_assist_with_config(
+            inline_function,
+            AssistConfig { fold_inlined_conditions: true, ..TEST_CONFIG },
+            r#"
+fn scale(value: u32, fast: bool) -> u32 {
+    if fast {
+        value * 2
+    } else {
+        let slow = value + 1;
+        slow * 3
+    }
+}
+fn main() {
+    let x = scale$0(4, false);
+}
+"#,
+            r#"
+fn scale(value: u32, fast: bool) -> u32 {
+    if fast {
+        value * 2
+    } else {
+        let slow = value + 1;
+        slow * 3
+    }
+}
+fn main() {
+    let x = {
+        let slow = 4 + 1;
+        slow * 3
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn can_inline_reports_why_a_call_is_not_inlinable() {
+        check_inline_error(
+            r#"
+fn foo(a: u32) -> u32 { if a > 1 { return 0; } a }
+fn main() { foo$0(1); }
+"#,
+            InlineError::EarlyReturn,
+        );
+        check_inline_error(
+            r#"
+fn foo(a: u32) -> u32 { a }
+fn main() { foo$0(1, 2); }
+"#,
+            InlineError::IncorrectNumberOfArguments,
+        );
+        check_inline_error(
+            r#"
+fn foo(a: u32) -> u32 { foo(a) }
+fn main() { foo$0(1); }
+"#,
+            InlineError::RecursiveFunction,
+        );
+        check_inline_error(
+            r#"
+fn foo(a: u32) -> u32 { a }
+fn main() { foo$0(foo(1)); }
+"#,
+            InlineError::ArgumentCallsFunction,
+        );
+        check_inline_error(
+            r#"
+fn foo(a: u32) -> u32;
+fn main() { foo$0(1); }
+"#,
+            InlineError::IncompleteCode,
+        );
+        check_inline_error(
+            r#"
+fn factorial(n: u64) -> u64 {
+    if n == 0 { 1 } else { n * factorial$0(n - 1) }
+}
+"#,
+            InlineError::RecursiveCallInItself,
+        );
+        check_inline_error(
+            r#"
+struct Foo;
+impl Foo { fn bar(&self) {} }
+fn main(foo: &&Foo) { foo.bar$0(); }
+"#,
+            InlineError::UnsupportedReceiver,
+        );
+        check_inline_error(
+            r#"
+fn as_str(s: &String) -> &str { &s[..] }
+fn main() {
+    let n = 1 + as_str$0(&String::new()).len();
+}
+"#,
+            InlineError::ReturnsBorrowOfTemporary,
+        );
+        check_inline_error(
+            r#"
+trait Display {}
+struct S;
+fn show<T>(value: T) -> T where T: Display { value }
+fn main() { show$0(S); }
+"#,
+            InlineError::UnsatisfiedBounds,
+        );
+        check_inline_error(
+            r#"
+mod net {
+    fn helper() -> u32 { 42 }
+    pub mod tcp {
+        pub fn connect() -> u32 { super::helper() }
+    }
+}
+fn main() { net::tcp::connect$0(); }
+"#,
+            InlineError::PathNotNameable,
+        );
+        check_inline_error(
+            r#"
+trait Zero { fn zero() -> Self; }
+impl Zero for u32 { fn zero() -> Self { 0 } }
+fn zero<T: Zero>() -> T { T::zero() }
+fn main() { let x: u32 = zero$0(); }
+"#,
+            InlineError::GenericArgsNotNameable,
+        );
+        check_inline_error(
+            r#"
+struct Counter { n: u32 }
+fn count(counter: &mut Counter) -> &u32 { &mut counter.n }
+fn main(counter: &mut Counter) { count$0(counter); }
+"#,
+            InlineError::ReturnCoercionNotReproducible,
+        );
+    }
+
+    #[test]
+    fn can_inline_reports_why_the_body_is_not_valid_at_the_call_site() {
+        check_inline_error(
+            r#"
+fn compute_size() -> usize { 4 * 8 }
+const SIZE: usize = compute_size$0();
+"#,
+            InlineError::ConstContext,
+        );
+        check_inline_error(
+            r#"
+const fn double(n: usize) -> usize { n * 2 }
+fn main() { let buf: [u8; double$0(4)]; }
+"#,
+            InlineError::ConstContext,
+        );
+        check_inline_error(
+            r#"
+mod math {
+    trait Double {
+        fn double(self) -> Self;
+    }
+    impl Double for u32 {
+        fn double(self) -> u32 { self * 2 }
+    }
+    pub fn quadruple(a: u32) -> u32 { a.double().double() }
+}
+fn main() { math::quadruple$0(1); }
+"#,
+            InlineError::ImportNotPossible,
+        );
+        check_inline_error(
+            r#"
+mod geometry {
+    pub struct Point { x: u32 }
+    pub fn x_of(point: &Point) -> u32 { point.x }
+}
+fn main(point: geometry::Point) { geometry::x_of$0(&point); }
+"#,
+            InlineError::BodyItemNotVisible,
+        );
+        check_inline_error(
+            r#"
+mod raw {
+    pub unsafe fn read(p: *const u32) -> u32 { *p }
+}
+#[forbid(unsafe_code)]
+fn main(p: *const u32) { raw::read$0(p); }
+"#,
+            InlineError::UnsafeCodeForbidden,
+        );
+        check_inline_error_with_config(
+            VERIFYING_CONFIG,
+            r#"
+mod shapes {
+    pub fn unit() { missing() }
+}
+fn main() { shapes::unit$0(); }
+"#,
+            InlineError::UnresolvedPathAtCallSite,
+        );
+    }
 }
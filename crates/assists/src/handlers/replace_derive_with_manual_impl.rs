@@ -66,6 +66,7 @@ pub(crate) fn replace_derive_with_manual_impl(
         &ctx.sema,
         current_crate,
         trait_token.text().to_string(),
+        None,
     )
     .filter_map(|candidate: either::Either<hir::ModuleDef, hir::MacroDef>| match candidate {
         either::Either::Left(hir::ModuleDef::Trait(trait_)) => Some(trait_),
@@ -82,6 +82,13 @@ pub struct Assist {
     /// cumbersome, especially if you want to embed an assist into another data
     /// structure, such as a diagnostic.
     pub source_change: Option<SourceChange>,
+    /// Whether this is the assist the user most likely wants here, which editors offer first.
+    /// Working this out can be as costly as the source change, so it is only set along with it.
+    pub is_preferred: bool,
+    /// Why the change may need more work from the user, like code it can't update, which is
+    /// shown along with the label. Like `is_preferred`, it is only set along with the source
+    /// change.
+    pub reason: Option<String>,
 }
 
 impl Assist {
@@ -187,6 +194,8 @@ mod handlers {
             generate_new::generate_new,
             infer_function_return_type::infer_function_return_type,
             inline_function::inline_function,
+            inline_function::inline_function_with_dbg,
+            inline_function::inline_function_all,
             inline_local_variable::inline_local_variable,
             introduce_named_lifetime::introduce_named_lifetime,
             invert_if::invert_if,
@@ -7,7 +7,7 @@ use ide_db::{
     source_change::FileSystemEdit,
     RootDatabase,
 };
-use syntax::TextRange;
+use syntax::{ast, AstNode, TextRange};
 use test_utils::{assert_eq_text, extract_offset, extract_range};
 
 use crate::{
@@ -22,6 +22,8 @@ pub(crate) const TEST_CONFIG: AssistConfig = AssistConfig {
         merge: Some(MergeBehavior::Full),
         prefix_kind: hir::PrefixKind::Plain,
     },
+    verify_inlining: false,
+    fold_inlined_conditions: false,
 };
 
 pub(crate) fn with_single_file(text: &str) -> (RootDatabase, FileId) {
@@ -30,7 +32,17 @@ pub(crate) fn with_single_file(text: &str) -> (RootDatabase, FileId) {
 
 pub(crate) fn check_assist(assist: Handler, ra_fixture_before: &str, ra_fixture_after: &str) {
     let ra_fixture_after = trim_indent(ra_fixture_after);
-    check(assist, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), None);
+    check(assist, TEST_CONFIG, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), None);
+}
+
+pub(crate) fn check_assist_with_config(
+    assist: Handler,
+    config: AssistConfig,
+    ra_fixture_before: &str,
+    ra_fixture_after: &str,
+) {
+    let ra_fixture_after = trim_indent(ra_fixture_after);
+    check(assist, config, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), None);
 }
 
 // There is no way to choose what assist within a group you want to test against,
@@ -42,18 +54,32 @@ pub(crate) fn check_assist_by_label(
     label: &str,
 ) {
     let ra_fixture_after = trim_indent(ra_fixture_after);
-    check(assist, ra_fixture_before, ExpectedResult::After(&ra_fixture_after), Some(label));
+    check(
+        assist,
+        TEST_CONFIG,
+        ra_fixture_before,
+        ExpectedResult::After(&ra_fixture_after),
+        Some(label),
+    );
 }
 
 // FIXME: instead of having a separate function here, maybe use
 // `extract_ranges` and mark the target as `<target> </target>` in the
 // fixture?
 pub(crate) fn check_assist_target(assist: Handler, ra_fixture: &str, target: &str) {
-    check(assist, ra_fixture, ExpectedResult::Target(target), None);
+    check(assist, TEST_CONFIG, ra_fixture, ExpectedResult::Target(target), None);
 }
 
 pub(crate) fn check_assist_not_applicable(assist: Handler, ra_fixture: &str) {
-    check(assist, ra_fixture, ExpectedResult::NotApplicable, None);
+    check(assist, TEST_CONFIG, ra_fixture, ExpectedResult::NotApplicable, None);
+}
+
+pub(crate) fn check_assist_not_applicable_with_config(
+    assist: Handler,
+    config: AssistConfig,
+    ra_fixture: &str,
+) {
+    check(assist, config, ra_fixture, ExpectedResult::NotApplicable, None);
 }
 
 fn check_doc_test(assist_id: &str, before: &str, after: &str) {
@@ -94,14 +120,19 @@ enum ExpectedResult<'a> {
     Target(&'a str),
 }
 
-fn check(handler: Handler, before: &str, expected: ExpectedResult, assist_label: Option<&str>) {
+fn check(
+    handler: Handler,
+    config: AssistConfig,
+    before: &str,
+    expected: ExpectedResult,
+    assist_label: Option<&str>,
+) {
     let (db, file_with_caret_id, range_or_offset) = RootDatabase::with_range_or_offset(before);
     let text_without_caret = db.file_text(file_with_caret_id).to_string();
 
     let frange = FileRange { file_id: file_with_caret_id, range: range_or_offset.into() };
 
     let sema = Semantics::new(&db);
-    let config = TEST_CONFIG;
     let ctx = AssistContext::new(sema, &config, frange);
     let mut acc = Assists::new(&ctx, true);
     handler(&mut acc, &ctx);
@@ -233,3 +264,69 @@ fn assist_filter_works() {
         assert!(assists.is_empty(), "All asserts but quickfixes should be filtered out");
     }
 }
+
+#[test]
+fn suggested_names_avoid_names_in_scope() {
+    let before = r#"
+fn f() {
+    let x = 1;
+    let x1 = 2;
+    $0x + x1;
+}
+"#;
+    let (offset, before) = extract_offset(before);
+    let (db, file_id) = with_single_file(&before);
+    let frange = FileRange { file_id, range: TextRange::empty(offset) };
+    let sema = Semantics::new(&db);
+    let ctx = AssistContext::new(sema, &TEST_CONFIG, frange);
+    let at = ctx.find_node_at_offset::<ast::Expr>().unwrap();
+
+    assert_eq!(ctx.suggest_name("x", at.syntax()).to_string(), "x2");
+    assert_eq!(ctx.suggest_name("x1", at.syntax()).to_string(), "x11");
+    assert_eq!(ctx.suggest_name("y", at.syntax()).to_string(), "y");
+}
+
+#[test]
+fn inlining_the_only_call_is_preferred() {
+    let is_preferred = |before: &str, resolve: bool| {
+        let (offset, before) = extract_offset(before);
+        let (db, file_id) = with_single_file(&before);
+        let frange = FileRange { file_id, range: TextRange::empty(offset) };
+        let assists = Assist::get(&db, &TEST_CONFIG, resolve, frange);
+        assists.into_iter().find(|it| it.id.0 == "inline_function").unwrap().is_preferred
+    };
+    let single_call = r#"fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add$0(1, 2); }
+"#;
+    assert!(is_preferred(single_call, true));
+    assert!(!is_preferred(single_call, false), "the calls are only searched when resolving");
+
+    let two_calls = r#"fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add$0(1, 2); let y = add(3, 4); }
+"#;
+    assert!(!is_preferred(two_calls, true));
+}
+
+#[test]
+fn inlining_the_last_call_left_to_doc_tests_gives_a_reason() {
+    let reason = |before: &str, resolve: bool| {
+        let (offset, before) = extract_offset(before);
+        let (db, file_id) = with_single_file(&before);
+        let frange = FileRange { file_id, range: TextRange::empty(offset) };
+        let assists = Assist::get(&db, &TEST_CONFIG, resolve, frange);
+        assists.into_iter().find(|it| it.id.0 == "inline_function").unwrap().reason
+    };
+    let doc_tested = r#"/// ```
+/// assert_eq!(add(1, 2), 3);
+/// ```
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add$0(1, 2); }
+"#;
+    assert_eq!(reason(doc_tested, true).as_deref(), Some("`add` is still called by doc tests"));
+    assert_eq!(reason(doc_tested, false), None, "the doc tests are only searched when resolving");
+
+    let untested = r#"fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add$0(1, 2); }
+"#;
+    assert_eq!(reason(untested, true), None);
+}
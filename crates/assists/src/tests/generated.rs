@@ -527,10 +527,49 @@ fn main() {
 "#####,
         r#####"
 fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = 1 + 2;
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_inline_function_all() {
+    check_doc_test(
+        "inline_function_all",
+        r#####"
+fn add$0(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = add(1, 2);
+    let y = add(3, 4);
+}
+"#####,
+        r#####"
+fn main() {
+    let x = 1 + 2;
+    let y = 3 + 4;
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_inline_function_with_dbg() {
+    check_doc_test(
+        "inline_function_with_dbg",
+        r#####"
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() {
+    let x = add$0(1, 2);
+}
+"#####,
+        r#####"
+fn add(a: u32, b: u32) -> u32 { a + b }
 fn main() {
     let x = {
-        let a = 1;
-        let b = 2;
+        let a = dbg!(1);
+        let b = dbg!(2);
         a + b
     };
 }
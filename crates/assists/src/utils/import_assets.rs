@@ -118,7 +118,7 @@ impl ImportAssets {
         let mut trait_candidates = FxHashSet::default();
         let current_crate = self.module_with_name_to_import.krate();
 
-        let filter = |candidate: Either<hir::ModuleDef, hir::MacroDef>| {
+        let mut filter = |candidate: Either<hir::ModuleDef, hir::MacroDef>| {
             trait_candidates.clear();
             match &self.import_candidate {
                 ImportCandidate::TraitAssocItem(trait_candidate) => {
@@ -179,14 +179,21 @@ impl ImportAssets {
             }
         };
 
-        let mut res = imports_locator::find_exact_imports(
+        let mut res = imports_locator::find_exact_imports_with_paths(
             sema,
-            current_crate,
+            self.module_with_name_to_import,
             self.get_search_query().to_string(),
+            None,
+            prefixed,
         )
-        .filter_map(filter)
-        .filter_map(|candidate| {
+        .into_iter()
+        .filter_map(|located| {
+            let candidate = filter(located.item)?;
             let item: hir::ItemInNs = candidate.either(Into::into, Into::into);
+            // The traits of the located associated items need paths of their own.
+            if candidate == located.item {
+                return Some((located.import_path, item));
+            }
             if let Some(prefix_kind) = prefixed {
                 self.module_with_name_to_import.find_use_path_prefixed(db, item, prefix_kind)
             } else {
@@ -5,7 +5,7 @@ use std::iter;
 use either::Either;
 use hir::{Adt, ModPath, ModuleDef, ScopeDef, Type};
 use ide_db::helpers::insert_use::ImportScope;
-use ide_db::imports_locator;
+use ide_db::imports_locator::{self, ImportNamespace, ImportSearchOptions};
 use syntax::AstNode;
 use test_utils::mark;
 
@@ -136,13 +136,18 @@ fn fuzzy_completion(acc: &mut Completions, ctx: &CompletionContext) -> Option<()
     let import_scope = ImportScope::find_insert_use_container(anchor.syntax(), &ctx.sema)?;
 
     let user_input_lowercased = potential_import_name.to_lowercase();
+    let namespace = if ctx.is_path_type { ImportNamespace::Types } else { ImportNamespace::Both };
     let mut all_mod_paths = imports_locator::find_similar_imports(
         &ctx.sema,
         ctx.krate?,
-        Some(40),
         potential_import_name,
-        true,
-        true,
+        &ImportSearchOptions {
+            limit: Some(40),
+            ignore_assoc_items: true,
+            namespace,
+            max_per_crate: Some(imports_locator::DEFAULT_MAX_CANDIDATES_PER_CRATE),
+            ..ImportSearchOptions::default()
+        },
     )
     .filter_map(|import_candidate| {
         Some(match import_candidate {
@@ -940,6 +945,31 @@ fn main() {
         );
     }
 
+    #[test]
+    fn fuzzy_completions_in_type_position_are_types() {
+        check_with_config(
+            TEST_CONFIG,
+            r#"
+//- /lib.rs crate:dep
+pub mod formats {
+    pub struct Format;
+    pub fn format_all() {}
+}
+
+//- /main.rs crate:main deps:dep
+fn main() {
+    let f: form$0
+}
+"#,
+            expect![[r#"
+                fn main()           fn main()
+                md dep
+                md dep::formats
+                st dep::formats::Format
+            "#]],
+        );
+    }
+
     #[test]
     fn fuzzy_completions_come_in_specific_order() {
         mark::check!(certain_fuzzy_order_test);
@@ -146,12 +146,13 @@ pub fn resolve_completion_edits(
     let current_module = ctx.sema.scope(anchor.syntax()).module()?;
     let current_crate = current_module.krate();
 
-    let import_path = imports_locator::find_exact_imports(&ctx.sema, current_crate, imported_name)
-        .filter_map(|candidate| {
-            let item: hir::ItemInNs = candidate.either(Into::into, Into::into);
-            current_module.find_use_path(db, item)
-        })
-        .find(|mod_path| mod_path.to_string() == full_import_path)?;
+    let import_path =
+        imports_locator::find_exact_imports(&ctx.sema, current_crate, imported_name, None)
+            .filter_map(|candidate| {
+                let item: hir::ItemInNs = candidate.either(Into::into, Into::into);
+                current_module.find_use_path(db, item)
+            })
+            .find(|mod_path| mod_path.to_string() == full_import_path)?;
 
     ImportEdit { import_path, import_scope }.to_text_edit(config.merge).map(|edit| vec![edit])
 }
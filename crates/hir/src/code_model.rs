@@ -8,6 +8,7 @@ use hir_def::{
     adt::{ReprKind, StructKind, VariantData},
     builtin_type::BuiltinType,
     expr::{BindingAnnotation, LabelId, Pat, PatId},
+    generics::TypeParamProvenance,
     import_map,
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
@@ -106,6 +107,22 @@ impl Crate {
         db.crate_graph()[self.id].display_name.clone()
     }
 
+    /// Returns the items of the crate left out by a `#[cfg]` that enabling one of its features would
+    /// bring in, with the module they would be declared in and that feature.
+    pub fn feature_gated_items(
+        self,
+        db: &dyn HirDatabase,
+    ) -> Vec<(Module, InFile<ast::Item>, SmolStr)> {
+        let def_map = db.crate_def_map(self.id);
+        def_map
+            .feature_gated_items()
+            .map(|(module_id, ast_id, feature)| {
+                let item = InFile::new(ast_id.file_id, ast_id.to_node(db.upcast()));
+                (Module::new(self, module_id), item, feature)
+            })
+            .collect()
+    }
+
     pub fn query_external_importables(
         self,
         db: &dyn DefDatabase,
@@ -117,6 +134,57 @@ impl Crate {
         })
     }
 
+    /// Searches the dependencies for the items matching any of the `queries`, see
+    /// `import_map::search_dependencies_matching_any`.
+    pub fn query_external_importables_matching_any(
+        self,
+        db: &dyn DefDatabase,
+        queries: &[import_map::Query],
+    ) -> impl Iterator<Item = (usize, Either<ModuleDef, MacroDef>)> {
+        import_map::search_dependencies_matching_any(db, self.into(), queries).into_iter().map(
+            |(query_index, item)| match item {
+                ItemInNs::Types(mod_id) | ItemInNs::Values(mod_id) => {
+                    (query_index, Either::Left(mod_id.into()))
+                }
+                ItemInNs::Macros(mac_id) => (query_index, Either::Right(mac_id.into())),
+            },
+        )
+    }
+
+    /// Searches the items of the crate that are reachable from its root through public paths.
+    pub fn query_public_items(
+        self,
+        db: &dyn DefDatabase,
+        query: import_map::Query,
+    ) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+        import_map::search_crate(db, self.into(), query).into_iter().map(|item| match item {
+            ItemInNs::Types(mod_id) | ItemInNs::Values(mod_id) => Either::Left(mod_id.into()),
+            ItemInNs::Macros(mac_id) => Either::Right(mac_id.into()),
+        })
+    }
+
+    /// Returns the module the shortest public path from the crate's root to `item` goes through,
+    /// like the crate's root for an item it re-exports from a private module.
+    pub fn import_container(
+        self,
+        db: &dyn DefDatabase,
+        item: impl Into<ItemInNs>,
+    ) -> Option<Module> {
+        db.import_map(self.id).import_info_for(item.into()).map(|it| it.container.into())
+    }
+
+    /// Searches the `#[doc(alias = "...")]` values of the crate's own public items.
+    pub fn query_doc_aliases(
+        self,
+        db: &dyn DefDatabase,
+        query: import_map::Query,
+    ) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+        import_map::search_doc_aliases(db, self.into(), query).into_iter().map(|item| match item {
+            ItemInNs::Types(mod_id) | ItemInNs::Values(mod_id) => Either::Left(mod_id.into()),
+            ItemInNs::Macros(mac_id) => Either::Right(mac_id.into()),
+        })
+    }
+
     pub fn all(db: &dyn HirDatabase) -> Vec<Crate> {
         db.crate_graph().iter().map(|id| Crate { id }).collect()
     }
@@ -794,6 +862,12 @@ impl Function {
         db.function_data(self.id).is_unsafe
     }
 
+    /// Whether the body has unsafe operations outside of `unsafe` blocks, which only the bodies
+    /// of `unsafe fn`s are allowed to have.
+    pub fn has_unsafe_operations(self, db: &dyn HirDatabase) -> bool {
+        hir_ty::diagnostics::has_unsafe_operations(db, self.id.into())
+    }
+
     pub fn diagnostics(self, db: &dyn HirDatabase, sink: &mut DiagnosticSink) {
         let krate = self.module(db).id.krate;
         hir_def::diagnostics::validate_body(db.upcast(), self.id.into(), sink);
@@ -1296,6 +1370,13 @@ impl TypeParam {
         self.id.parent.module(db.upcast()).into()
     }
 
+    /// Whether the parameter is not written in a generic parameter list, being either the `Self`
+    /// of a trait or introduced by an `impl Trait` argument.
+    pub fn is_implicit(self, db: &dyn HirDatabase) -> bool {
+        let params = db.generic_params(self.id.parent);
+        params.types[self.id.local_id].provenance != TypeParamProvenance::TypeParamList
+    }
+
     pub fn ty(self, db: &dyn HirDatabase) -> Type {
         let resolver = self.id.parent.resolver(db.upcast());
         let environment = TraitEnvironment::lower(db, &resolver);
@@ -1537,6 +1618,40 @@ impl Type {
         matches!(self.ty.value, Ty::Unknown)
     }
 
+    /// The generic type parameter this type is, like the `T` of a `fn foo<T>(x: T)` inside the
+    /// body of `foo`.
+    pub fn as_type_param(&self) -> Option<TypeParam> {
+        match self.ty.value {
+            Ty::Placeholder(id) => Some(TypeParam { id }),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a value of this type can be used where a `target` is expected, either as
+    /// is or through deref coercions, like `&String` for `&str`. Other coercions, like unsizing
+    /// ones, aren't considered.
+    pub fn could_coerce_to(&self, db: &dyn HirDatabase, target: &Type) -> bool {
+        if self.contains_unknown() || target.contains_unknown() {
+            return false;
+        }
+        if self.ty.value == target.ty.value {
+            return true;
+        }
+        let (inner, target_inner) = match (&self.ty.value, &target.ty.value) {
+            (
+                Ty::Apply(ApplicationTy { ctor: TypeCtor::Ref(mutability), parameters }),
+                Ty::Apply(ApplicationTy {
+                    ctor: TypeCtor::Ref(target_mutability),
+                    parameters: target_parameters,
+                }),
+            ) if *mutability == Mutability::Mut || *target_mutability == Mutability::Shared => {
+                (parameters.as_single(), target_parameters.as_single())
+            }
+            _ => return false,
+        };
+        self.derived(inner.clone()).autoderef(db).any(|it| it.ty.value == *target_inner)
+    }
+
     /// Checks that particular type `ty` implements `std::future::Future`.
     /// This function is used in `.await` syntax completion.
     pub fn impls_future(&self, db: &dyn HirDatabase) -> bool {
@@ -1848,6 +1963,11 @@ impl Type {
         self.ty.value.associated_type_parent_trait(db).map(Into::into)
     }
 
+    /// Checks whether the types are the same, regardless of the bounds in scope where each is used.
+    pub fn is_same_ty(&self, other: &Type) -> bool {
+        self.ty.value == other.ty.value
+    }
+
     // FIXME: provide required accessors such that it becomes implementable from outside.
     pub fn is_equal_for_find_impls(&self, other: &Type) -> bool {
         match (&self.ty.value, &other.ty.value) {
@@ -2069,6 +2189,17 @@ impl ScopeDef {
     }
 }
 
+impl HasVisibility for Module {
+    fn visibility(&self, db: &dyn HirDatabase) -> Visibility {
+        match self.parent(db) {
+            Some(parent) => {
+                parent.visibility_of(db, &ModuleDef::Module(*self)).unwrap_or(Visibility::Public)
+            }
+            None => Visibility::Public,
+        }
+    }
+}
+
 pub trait HasVisibility {
     fn visibility(&self, db: &dyn HirDatabase) -> Visibility;
     fn is_visible_from(&self, db: &dyn HirDatabase, module: Module) -> bool {
@@ -51,7 +51,7 @@ pub use hir_def::{
     find_path::PrefixKind,
     import_map,
     item_scope::ItemInNs,
-    nameres::ModuleSource,
+    nameres::{CrateDefMap, ModuleSource},
     path::{ModPath, PathKind},
     type_ref::{Mutability, TypeRef},
     visibility::Visibility,
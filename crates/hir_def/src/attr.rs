@@ -14,7 +14,7 @@ use syntax::{
     match_ast, AstToken, SmolStr, SyntaxNode,
 };
 use test_utils::mark;
-use tt::Subtree;
+use tt::{Leaf, Subtree, TokenTree};
 
 use crate::{
     db::DefDatabase,
@@ -330,6 +330,108 @@ impl Attrs {
             Some(Documentation(docs.into()))
         }
     }
+
+    /// Returns the alternative names given to the item with `#[doc(alias = "...")]` or
+    /// `#[doc(alias("...", "..."))]`.
+    pub fn doc_aliases(&self) -> impl Iterator<Item = SmolStr> + '_ {
+        self.by_key("doc").tt_values().flat_map(|tt| {
+            let mut aliases = Vec::new();
+            let mut trees = tt.token_trees.iter();
+            while let Some(tree) = trees.next() {
+                match tree {
+                    TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "alias" => {}
+                    _ => continue,
+                }
+                match trees.next() {
+                    Some(TokenTree::Leaf(Leaf::Punct(punct))) if punct.char == '=' => {
+                        if let Some(TokenTree::Leaf(Leaf::Literal(lit))) = trees.next() {
+                            aliases.push(SmolStr::new(lit.text.trim_matches('"')));
+                        }
+                    }
+                    Some(TokenTree::Subtree(subtree)) => {
+                        aliases.extend(subtree.token_trees.iter().filter_map(|it| match it {
+                            TokenTree::Leaf(Leaf::Literal(lit)) => {
+                                Some(SmolStr::new(lit.text.trim_matches('"')))
+                            }
+                            _ => None,
+                        }));
+                    }
+                    _ => {}
+                }
+            }
+            aliases
+        })
+    }
+
+    /// Checks whether the item is marked `#[doc(hidden)]`, as an implementation detail of its crate.
+    pub fn is_doc_hidden(&self) -> bool {
+        self.by_key("doc").tt_values().any(|tt| {
+            tt.token_trees.iter().any(|tree| {
+                matches!(tree, TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "hidden")
+            })
+        })
+    }
+
+    /// Whether the `unsafe_code` lint is set to `forbid` or `deny` by these attributes.
+    pub fn forbids_unsafe_code(&self) -> bool {
+        let lint_levels = self.by_key("forbid").tt_values().chain(self.by_key("deny").tt_values());
+        lint_levels.into_iter().any(|tt| {
+            tt.token_trees.iter().any(|tree| {
+                matches!(tree, TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "unsafe_code")
+            })
+        })
+    }
+
+    /// Returns the Rust version the item was stabilized in, given by
+    /// `#[stable(feature = "...", since = "...")]`.
+    pub fn stable_since(&self) -> Option<SmolStr> {
+        self.by_key("stable").tt_values().find_map(|tt| {
+            let mut trees = tt.token_trees.iter();
+            while let Some(tree) = trees.next() {
+                match tree {
+                    TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "since" => {}
+                    _ => continue,
+                }
+                match (trees.next(), trees.next()) {
+                    (
+                        Some(TokenTree::Leaf(Leaf::Punct(punct))),
+                        Some(TokenTree::Leaf(Leaf::Literal(lit))),
+                    ) if punct.char == '=' => {
+                        return Some(SmolStr::new(lit.text.trim_matches('"')));
+                    }
+                    _ => return None,
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns the note of a deprecated item, given by `#[deprecated(note = "...")]` or by
+    /// `#[deprecated = "..."]`.
+    pub fn deprecation_note(&self) -> Option<SmolStr> {
+        if let Some(note) = self.by_key("deprecated").string_value() {
+            return Some(SmolStr::new(note.trim_matches('"')));
+        }
+        self.by_key("deprecated").tt_values().find_map(|tt| {
+            let mut trees = tt.token_trees.iter();
+            while let Some(tree) = trees.next() {
+                match tree {
+                    TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "note" => {}
+                    _ => continue,
+                }
+                match (trees.next(), trees.next()) {
+                    (
+                        Some(TokenTree::Leaf(Leaf::Punct(punct))),
+                        Some(TokenTree::Leaf(Leaf::Literal(lit))),
+                    ) if punct.char == '=' => {
+                        return Some(SmolStr::new(lit.text.trim_matches('"')));
+                    }
+                    _ => return None,
+                }
+            }
+            None
+        })
+    }
 }
 
 fn inner_attributes(
@@ -8,11 +8,12 @@ use hir_expand::name::Name;
 use indexmap::{map::Entry, IndexMap};
 use itertools::Itertools;
 use rustc_hash::{FxHashSet, FxHasher};
+use syntax::SmolStr;
 use test_utils::mark;
 
 use crate::{
-    db::DefDatabase, item_scope::ItemInNs, visibility::Visibility, AssocItemId, ModuleDefId,
-    ModuleId, TraitId,
+    db::DefDatabase, item_scope::ItemInNs, visibility::Visibility, AssocItemId, AttrDefId,
+    ModuleDefId, ModuleId, TraitId,
 };
 
 type FxIndexMap<K, V> = IndexMap<K, V, BuildHasherDefault<FxHasher>>;
@@ -65,6 +66,11 @@ pub struct ImportMap {
     /// the index of the first one.
     importables: Vec<ItemInNs>,
     fst: fst::Map<Vec<u8>>,
+
+    /// Lowercased `#[doc(alias = "...")]` values of the items stored in `map`.
+    ///
+    /// Aliases are rare, so they are searched linearly instead of being put into the `fst`.
+    doc_aliases: Vec<(SmolStr, ItemInNs)>,
 }
 
 impl ImportMap {
@@ -167,6 +173,18 @@ impl ImportMap {
         import_map.fst = fst::Map::new(builder.into_inner().unwrap()).unwrap();
         import_map.importables = importables.iter().map(|(item, _)| **item).collect();
 
+        import_map.doc_aliases = import_map
+            .map
+            .keys()
+            .filter_map(|&item| Some((item_attr_owner(item)?, item)))
+            .flat_map(|(owner, item)| {
+                db.attrs(owner)
+                    .doc_aliases()
+                    .map(|alias| (SmolStr::new(alias.to_lowercase()), item))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
         Arc::new(import_map)
     }
 
@@ -213,8 +231,9 @@ impl ImportMap {
 
 impl PartialEq for ImportMap {
     fn eq(&self, other: &Self) -> bool {
-        // `fst` and `importables` are built from `map`, so we don't need to compare them.
-        self.map == other.map
+        // `fst` and `importables` are built from `map`, so we don't need to compare them. The
+        // aliases come from the attributes of the items instead.
+        self.map == other.map && self.doc_aliases == other.doc_aliases
     }
 }
 
@@ -252,7 +271,7 @@ fn cmp((_, lhs): &(&ItemInNs, &ImportInfo), (_, rhs): &(&ItemInNs, &ImportInfo))
     lhs_str.cmp(&rhs_str)
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum ImportKind {
     Module,
     Function,
@@ -266,7 +285,7 @@ pub enum ImportKind {
 }
 
 /// A way to match import map contents against the search query.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SearchMode {
     /// Import map entry should strictly match the query string.
     Equals,
@@ -275,9 +294,12 @@ pub enum SearchMode {
     /// Import map entry should contain all letters from the query string,
     /// in the same order, but not necessary adjacent.
     Fuzzy,
+    /// Import map entry should be at most this many single letter insertions, deletions or
+    /// substitutions away from the query string.
+    EditDistance(usize),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Query {
     query: String,
     lowercased: String,
@@ -286,6 +308,7 @@ pub struct Query {
     case_sensitive: bool,
     limit: usize,
     exclude_import_kinds: FxHashSet<ImportKind>,
+    include_doc_aliases: bool,
 }
 
 impl Query {
@@ -299,9 +322,15 @@ impl Query {
             case_sensitive: false,
             limit: usize::max_value(),
             exclude_import_kinds: FxHashSet::default(),
+            include_doc_aliases: false,
         }
     }
 
+    /// The string searched for, as it was given.
+    pub fn text(&self) -> &str {
+        &self.query
+    }
+
     /// Matches entries' names only, ignoring the rest of
     /// the qualifier.
     /// Example: for `std::marker::PhantomData`, the name is `PhantomData`.
@@ -330,12 +359,22 @@ impl Query {
         self
     }
 
+    /// Also match the query against the `#[doc(alias = "...")]` values of the items,
+    /// ignoring the case.
+    pub fn include_doc_aliases(self) -> Self {
+        Self { include_doc_aliases: true, ..self }
+    }
+
     fn import_matches(&self, import: &ImportInfo, enforce_lowercase: bool) -> bool {
-        let mut input = if import.is_trait_assoc_item || self.name_only {
+        let input = if import.is_trait_assoc_item || self.name_only {
             import.path.segments.last().unwrap().to_string()
         } else {
             import.path.to_string()
         };
+        self.text_matches(input, enforce_lowercase)
+    }
+
+    fn text_matches(&self, mut input: String, enforce_lowercase: bool) -> bool {
         if enforce_lowercase || !self.case_sensitive {
             input.make_ascii_lowercase();
         }
@@ -361,6 +400,14 @@ impl Query {
                 }
                 mismatching_query_char.is_none()
             }
+            SearchMode::EditDistance(max_distance) => {
+                // Each edit changes the length by one at most, so skip computing the distance for
+                // the entries whose length is out of reach.
+                let (input_len, query_len) = (input.chars().count(), query_string.chars().count());
+                let length_difference = input_len.max(query_len) - input_len.min(query_len);
+                length_difference <= max_distance
+                    && stdx::edit_distance(&input, query_string) <= max_distance
+            }
         }
     }
 }
@@ -378,56 +425,198 @@ pub fn search_dependencies<'a>(
     let graph = db.crate_graph();
     let import_maps: Vec<_> =
         graph[krate].dependencies.iter().map(|dep| db.import_map(dep.crate_id)).collect();
+    search_import_maps(db, &import_maps, query)
+}
+
+/// Searches the items of `krate` that are reachable from its root through public paths, that is,
+/// the items that the crate's dependents can import.
+pub fn search_crate(db: &dyn DefDatabase, krate: CrateId, query: Query) -> Vec<ItemInNs> {
+    let _p = profile::span("search_crate").detail(|| format!("{:?}", query));
+
+    search_import_maps(db, &[db.import_map(krate)], query)
+}
 
+fn search_import_maps(
+    db: &dyn DefDatabase,
+    import_maps: &[Arc<ImportMap>],
+    query: Query,
+) -> Vec<ItemInNs> {
     let automaton = fst::automaton::Subsequence::new(&query.lowercased);
 
     let mut op = fst::map::OpBuilder::new();
-    for map in &import_maps {
-        op = op.add(map.fst.search(&automaton));
+    for map in import_maps {
+        op = match query.search_mode {
+            // The edit distance is checked on the entries themselves.
+            SearchMode::EditDistance(_) => op.add(map.fst.stream()),
+            _ => op.add(map.fst.search(&automaton)),
+        };
     }
 
     let mut stream = op.union();
     let mut res = Vec::new();
     while let Some((_, indexed_values)) = stream.next() {
+        db.check_canceled();
         for indexed_value in indexed_values {
             let import_map = &import_maps[indexed_value.index];
-            let importables = &import_map.importables[indexed_value.value as usize..];
+            res.extend(matching_importables(import_map, indexed_value.value as usize, &query));
 
-            let common_importable_data = &import_map.map[&importables[0]];
-            if !query.import_matches(common_importable_data, true) {
-                continue;
+            if res.len() >= query.limit {
+                res.truncate(query.limit);
+                return res;
             }
+        }
+    }
+
+    if query.include_doc_aliases {
+        for import_map in import_maps {
+            extend_with_doc_aliases(&mut res, import_map, &query);
+        }
+        res.truncate(query.limit);
+    }
+
+    res
+}
 
-            // Path shared by the importable items in this group.
-            let common_importables_path_fst = fst_path(&common_importable_data.path);
-            // Add the items from this `ModPath` group. Those are all subsequent items in
-            // `importables` whose paths match `path`.
-            let iter = importables
-                .iter()
-                .copied()
-                .take_while(|item| {
-                    common_importables_path_fst == fst_path(&import_map.map[item].path)
-                })
-                .filter(|&item| match item_import_kind(item) {
-                    Some(import_kind) => !query.exclude_import_kinds.contains(&import_kind),
-                    None => true,
-                })
-                .filter(|item| {
-                    !query.case_sensitive // we've already checked the common importables path case-insensitively
-                        || query.import_matches(&import_map.map[item], false)
-                });
-            res.extend(iter);
+/// Searches the dependencies of `krate` for the items matching any of the `queries`, walking the
+/// import maps once for all of them.
+///
+/// Each item is paired with the index of the query it matched, an item matching several queries
+/// is returned once for each of them. The limit of a query only caps its own matches.
+pub fn search_dependencies_matching_any(
+    db: &dyn DefDatabase,
+    krate: CrateId,
+    queries: &[Query],
+) -> Vec<(usize, ItemInNs)> {
+    let _p = profile::span("search_dependencies_matching_any").detail(|| format!("{:?}", queries));
+
+    let graph = db.crate_graph();
+    let import_maps: Vec<_> =
+        graph[krate].dependencies.iter().map(|dep| db.import_map(dep.crate_id)).collect();
+    let automata: Vec<_> =
+        queries.iter().map(|query| fst::automaton::Subsequence::new(&query.lowercased)).collect();
+
+    // The stream of the query `i` over the import map `j` is at index `i * import_maps.len() + j`.
+    let mut op = fst::map::OpBuilder::new();
+    for (query, automaton) in queries.iter().zip(&automata) {
+        for map in &import_maps {
+            op = match query.search_mode {
+                SearchMode::EditDistance(_) => op.add(map.fst.stream()),
+                _ => op.add(map.fst.search(automaton)),
+            };
+        }
+    }
 
+    let mut stream = op.union();
+    let mut matches = vec![Vec::new(); queries.len()];
+    while let Some((_, indexed_values)) = stream.next() {
+        db.check_canceled();
+        for indexed_value in indexed_values {
+            let query_index = indexed_value.index / import_maps.len();
+            let query = &queries[query_index];
+            let res = &mut matches[query_index];
             if res.len() >= query.limit {
-                res.truncate(query.limit);
-                return res;
+                continue;
+            }
+            let import_map = &import_maps[indexed_value.index % import_maps.len()];
+            res.extend(matching_importables(import_map, indexed_value.value as usize, query));
+            res.truncate(query.limit);
+        }
+    }
+
+    for (query, res) in queries.iter().zip(&mut matches) {
+        if query.include_doc_aliases {
+            for import_map in &import_maps {
+                extend_with_doc_aliases(res, import_map, query);
             }
+            res.truncate(query.limit);
         }
     }
 
+    matches
+        .into_iter()
+        .enumerate()
+        .flat_map(|(query_index, res)| res.into_iter().map(move |item| (query_index, item)))
+        .collect()
+}
+
+/// The items of the group of `import_map` starting at `start` that `query` matches. The items of
+/// a group share their import path.
+fn matching_importables<'a>(
+    import_map: &'a ImportMap,
+    start: usize,
+    query: &'a Query,
+) -> impl Iterator<Item = ItemInNs> + 'a {
+    let importables = &import_map.importables[start..];
+
+    let common_importable_data = &import_map.map[&importables[0]];
+    let group_matches = query.import_matches(common_importable_data, true);
+
+    // Path shared by the importable items in this group.
+    let common_importables_path_fst = fst_path(&common_importable_data.path);
+    // Add the items from this `ModPath` group. Those are all subsequent items in
+    // `importables` whose paths match `path`.
+    importables
+        .iter()
+        .copied()
+        .take_while(move |_| group_matches)
+        .take_while(move |item| common_importables_path_fst == fst_path(&import_map.map[item].path))
+        .filter(move |&item| match item_import_kind(item) {
+            Some(import_kind) => !query.exclude_import_kinds.contains(&import_kind),
+            None => true,
+        })
+        .filter(move |item| {
+            !query.case_sensitive // we've already checked the common importables path case-insensitively
+                || query.import_matches(&import_map.map[item], false)
+        })
+}
+
+/// Searches the `#[doc(alias = "...")]` values of the items `krate` exports for a match with
+/// `query`.
+///
+/// Returns nothing unless the query was created with `Query::include_doc_aliases`. Unlike
+/// `search_dependencies`, this looks at the public items of `krate` itself.
+pub fn search_doc_aliases(db: &dyn DefDatabase, krate: CrateId, query: Query) -> Vec<ItemInNs> {
+    let _p = profile::span("search_doc_aliases").detail(|| format!("{:?}", query));
+
+    let mut res = Vec::new();
+    if query.include_doc_aliases {
+        extend_with_doc_aliases(&mut res, &db.import_map(krate), &query);
+        res.truncate(query.limit);
+    }
     res
 }
 
+fn extend_with_doc_aliases(res: &mut Vec<ItemInNs>, import_map: &ImportMap, query: &Query) {
+    for (alias, item) in &import_map.doc_aliases {
+        if res.contains(item) || !query.text_matches(alias.to_string(), true) {
+            continue;
+        }
+        if let Some(import_kind) = item_import_kind(*item) {
+            if query.exclude_import_kinds.contains(&import_kind) {
+                continue;
+            }
+        }
+        res.push(*item);
+    }
+}
+
+fn item_attr_owner(item: ItemInNs) -> Option<AttrDefId> {
+    Some(match item {
+        ItemInNs::Types(id) | ItemInNs::Values(id) => match id {
+            ModuleDefId::ModuleId(it) => it.into(),
+            ModuleDefId::FunctionId(it) => it.into(),
+            ModuleDefId::AdtId(it) => it.into(),
+            ModuleDefId::EnumVariantId(it) => it.into(),
+            ModuleDefId::ConstId(it) => it.into(),
+            ModuleDefId::StaticId(it) => it.into(),
+            ModuleDefId::TraitId(it) => it.into(),
+            ModuleDefId::TypeAliasId(it) => it.into(),
+            ModuleDefId::BuiltinType(_) => return None,
+        },
+        ItemInNs::Macros(it) => it.into(),
+    })
+}
+
 fn item_import_kind(item: ItemInNs) -> Option<ImportKind> {
     Some(match item.as_module_def_id()? {
         ModuleDefId::ModuleId(_) => ImportKind::Module,
@@ -969,6 +1158,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_edit_distance() {
+        check_search(
+            r#"
+        //- /main.rs crate:main deps:dep
+        //- /dep.rs crate:dep
+        pub mod collections {
+            pub struct HashMap;
+            pub struct HashSet;
+            pub struct BTreeMap;
+        }
+        pub struct Hash;
+        pub fn hash_map() {}
+    "#,
+            "main",
+            Query::new("HasMap".to_string()).name_only().search_mode(SearchMode::EditDistance(1)),
+            expect![[r#"
+                dep::collections::HashMap (t)
+                dep::collections::HashMap (v)
+            "#]],
+        );
+    }
+
     #[test]
     fn search_limit() {
         check_search(
@@ -61,7 +61,7 @@ use hir_expand::{diagnostics::DiagnosticSink, name::Name, InFile};
 use la_arena::Arena;
 use rustc_hash::FxHashMap;
 use stdx::format_to;
-use syntax::ast;
+use syntax::{ast, SmolStr};
 
 use crate::{
     db::DefDatabase,
@@ -201,6 +201,14 @@ impl CrateDefMap {
         self.diagnostics.iter().for_each(|it| it.add_to(db, module, sink))
     }
 
+    /// Returns the items left out by a `#[cfg]` that enabling a single feature of the crate would
+    /// bring in, with the module they would be declared in and that feature.
+    pub fn feature_gated_items(
+        &self,
+    ) -> impl Iterator<Item = (LocalModuleId, AstId<ast::Item>, SmolStr)> + '_ {
+        self.diagnostics.iter().filter_map(|it| it.enabling_feature())
+    }
+
     pub fn modules_for_file(&self, file_id: FileId) -> impl Iterator<Item = LocalModuleId> + '_ {
         self.modules
             .iter()
@@ -283,12 +291,12 @@ pub enum ModuleSource {
 }
 
 mod diagnostics {
-    use cfg::{CfgExpr, CfgOptions};
+    use cfg::{CfgAtom, CfgExpr, CfgOptions};
     use hir_expand::diagnostics::DiagnosticSink;
     use hir_expand::hygiene::Hygiene;
     use hir_expand::{InFile, MacroCallKind};
     use syntax::ast::AttrsOwner;
-    use syntax::{ast, AstNode, AstPtr, SyntaxKind, SyntaxNodePtr};
+    use syntax::{ast, AstNode, AstPtr, SmolStr, SyntaxKind, SyntaxNodePtr};
 
     use crate::path::ModPath;
     use crate::{db::DefDatabase, diagnostics::*, nameres::LocalModuleId, AstId};
@@ -365,6 +373,25 @@ mod diagnostics {
             Self { in_module: container, kind: DiagnosticKind::MacroError { ast, message } }
         }
 
+        /// For unconfigured code that enabling a single feature would turn on, returns that
+        /// feature.
+        pub(super) fn enabling_feature(
+            &self,
+        ) -> Option<(LocalModuleId, AstId<ast::Item>, SmolStr)> {
+            let (ast, cfg, opts) = match &self.kind {
+                DiagnosticKind::UnconfiguredCode { ast, cfg, opts } => (ast, cfg, opts),
+                _ => return None,
+            };
+            let mut features = Vec::new();
+            collect_features(cfg, &mut features);
+            let feature = features.into_iter().find(|feature| {
+                let mut opts = opts.clone();
+                opts.insert_key_value("feature".into(), feature.clone());
+                opts.check(cfg) == Some(true)
+            })?;
+            Some((self.in_module, *ast, feature))
+        }
+
         pub(super) fn add_to(
             &self,
             db: &dyn DefDatabase,
@@ -493,4 +520,19 @@ mod diagnostics {
             }
         }
     }
+
+    fn collect_features(cfg: &CfgExpr, features: &mut Vec<SmolStr>) {
+        match cfg {
+            CfgExpr::Atom(CfgAtom::KeyValue { key, value }) if key == "feature" => {
+                if !features.contains(value) {
+                    features.push(value.clone());
+                }
+            }
+            CfgExpr::All(exprs) | CfgExpr::Any(exprs) => {
+                exprs.iter().for_each(|it| collect_features(it, features))
+            }
+            CfgExpr::Not(expr) => collect_features(expr, features),
+            CfgExpr::Invalid | CfgExpr::Atom(_) => {}
+        }
+    }
 }
@@ -38,6 +38,13 @@ pub fn validate_body(db: &dyn HirDatabase, owner: DefWithBodyId, sink: &mut Diag
     validator.validate_body(db);
 }
 
+/// Whether the body has operations that are only allowed in an unsafe context, outside of its
+/// `unsafe` blocks. Only the bodies of `unsafe fn`s can have them without an error.
+pub fn has_unsafe_operations(db: &dyn HirDatabase, owner: DefWithBodyId) -> bool {
+    let infer = db.infer(owner);
+    unsafe_check::unsafe_expressions(db, &infer, owner).iter().any(|it| !it.inside_unsafe_block)
+}
+
 // Diagnostic: no-such-field
 //
 // This diagnostic is triggered if created structure does not have field provided in record.
@@ -65,7 +65,9 @@ impl ImportScope {
         }
     }
 
-    fn insert_pos_after_last_inner_element(&self) -> (InsertPosition<SyntaxElement>, AddBlankLine) {
+    pub(crate) fn insert_pos_after_last_inner_element(
+        &self,
+    ) -> (InsertPosition<SyntaxElement>, AddBlankLine) {
         self.as_syntax_node()
             .children_with_tokens()
             .filter(|child| match child {
@@ -477,8 +479,9 @@ impl MergeBehavior {
     }
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Ord)]
-enum ImportGroup {
+/// The kind of crate or module a `use` imports from, `use`s of the same kind are kept together.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub enum ImportGroup {
     // the order here defines the order of new group inserts
     Std,
     ExternCrate,
@@ -488,7 +491,7 @@ enum ImportGroup {
 }
 
 impl ImportGroup {
-    fn new(path: &ast::Path) -> ImportGroup {
+    pub fn new(path: &ast::Path) -> ImportGroup {
         let default = ImportGroup::ExternCrate;
 
         let first_segment = match first_segment(path) {
@@ -512,7 +515,7 @@ impl ImportGroup {
 }
 
 #[derive(PartialEq, Eq)]
-enum AddBlankLine {
+pub(crate) enum AddBlankLine {
     Before,
     BeforeTwice,
     Around,
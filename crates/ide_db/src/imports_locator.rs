@@ -1,110 +1,2112 @@
 //! This module contains an import search functionality that is provided to the assists module.
 //! Later, this should be moved away to a separate crate that is accessible from the assists module.
 
-use hir::{import_map, AsAssocItem, Crate, MacroDef, ModuleDef, Semantics};
-use syntax::{ast, AstNode, SyntaxKind::NAME};
+use base_db::{CheckCanceled, CrateId, FileId};
+use hir::{
+    db::DefDatabase,
+    import_map::{self, ImportMap},
+    Access, Adt, AsAssocItem, AsName, AssocItem, AssocItemContainer, Attrs, Crate, CrateDefMap,
+    Function, GenericDef, GenericParam, HasAttrs, HasSource, HasVisibility, HirDisplay, ItemInNs,
+    MacroDef, ModPath, Module, ModuleDef, Name, PathKind, PrefixKind, ScopeDef, Semantics,
+    StructKind, Trait, Type, Visibility,
+};
+use syntax::{
+    ast, AstNode, InsertPosition, NodeOrToken, SmolStr,
+    SyntaxKind::{NAME, WHITESPACE},
+    TextRange, TextSize,
+};
 
 use crate::{
     defs::{Definition, NameClass},
-    symbol_index::{self, FileSymbol},
+    helpers::insert_use::{ImportGroup, ImportScope},
+    search::SearchScope,
+    symbol_index::{self, FileSymbol, SymbolIndex, SymbolsDatabase},
     RootDatabase,
 };
 use either::Either;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    cmp::Reverse,
+    iter,
+    sync::{Arc, Mutex, Weak},
+    time::Instant,
+};
+use stdx::format_to;
 
+/// Searches for the items named exactly `name_to_import` that can be imported into `krate`.
+///
+/// The `exclude` definition is never returned, to avoid suggesting to import the item that is
+/// being defined.
 pub fn find_exact_imports<'a>(
     sema: &Semantics<'a, RootDatabase>,
     krate: Crate,
     name_to_import: String,
+    exclude: Option<Definition>,
 ) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
     let _p = profile::span("find_exact_imports");
+    find_exact_imports_with_case(sema, krate, name_to_import, exclude, true)
+}
+
+/// Searches for the items named `name_to_import` like `find_exact_imports`, ignoring case, so
+/// that `hashmap` finds `HashMap`.
+pub fn find_exact_imports_case_insensitive<'a>(
+    sema: &Semantics<'a, RootDatabase>,
+    krate: Crate,
+    name_to_import: String,
+    exclude: Option<Definition>,
+) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_exact_imports_case_insensitive");
+    find_exact_imports_with_case(sema, krate, name_to_import, exclude, false)
+}
+
+fn find_exact_imports_with_case<'a>(
+    sema: &Semantics<'a, RootDatabase>,
+    krate: Crate,
+    name_to_import: String,
+    exclude: Option<Definition>,
+    case_sensitive: bool,
+) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+    let mut external_query = import_map::Query::new(name_to_import.clone())
+        .limit(40)
+        .name_only()
+        .search_mode(import_map::SearchMode::Equals);
+    if case_sensitive {
+        external_query = external_query.case_sensitive();
+    }
     find_imports(
         sema,
         krate,
         {
-            let mut local_query = symbol_index::Query::new(name_to_import.clone());
+            let mut local_query = symbol_index::Query::new(name_to_import);
             local_query.exact();
+            if !case_sensitive {
+                local_query.case_insensitive();
+            }
             local_query.limit(40);
             local_query
         },
-        import_map::Query::new(name_to_import)
-            .limit(40)
-            .name_only()
-            .search_mode(import_map::SearchMode::Equals)
-            .case_sensitive(),
+        external_query,
+        exclude,
+        None,
+        &ImportSearchOptions::default(),
+    )
+}
+
+/// Searches for the macros named `name` that can be imported into `krate`, matching the name
+/// exactly, or fuzzily if `fuzzy` is set. The dependencies are searched for macros only, so the
+/// other items named alike don't take up the limit of the search.
+pub fn find_macro_imports<'a>(
+    sema: &Semantics<'a, RootDatabase>,
+    krate: Crate,
+    name: String,
+    fuzzy: bool,
+) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_macro_imports");
+    let mut local_query = symbol_index::Query::new(name.clone());
+    local_query.limit(40);
+    let mut external_query = [
+        import_map::ImportKind::Module,
+        import_map::ImportKind::Function,
+        import_map::ImportKind::Adt,
+        import_map::ImportKind::EnumVariant,
+        import_map::ImportKind::Const,
+        import_map::ImportKind::Static,
+        import_map::ImportKind::Trait,
+        import_map::ImportKind::TypeAlias,
+        import_map::ImportKind::BuiltinType,
+    ]
+    .iter()
+    .fold(import_map::Query::new(name), |query, kind| query.exclude_import_kind(kind.clone()))
+    .name_only()
+    .limit(40);
+    if fuzzy {
+        external_query = external_query.search_mode(import_map::SearchMode::Fuzzy);
+    } else {
+        local_query.exact();
+        external_query =
+            external_query.search_mode(import_map::SearchMode::Equals).case_sensitive();
+    }
+
+    find_imports(
+        sema,
+        krate,
+        local_query,
+        external_query,
+        None,
+        None,
+        &ImportSearchOptions::default(),
+    )
+    .filter(|candidate| matches!(candidate, Either::Right(_)))
+}
+
+/// An import candidate along with the path to import it with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocatedImport {
+    pub item: Either<ModuleDef, MacroDef>,
+    pub import_path: ModPath,
+}
+
+/// Searches for the items named exactly `name_to_import` that can be imported into `from`, like
+/// `find_exact_imports`, along with the paths to import them with.
+///
+/// The paths are the ones `find_use_path` picks from `from`, using `prefix_kind` if given. The
+/// candidates are sorted by path.
+pub fn find_exact_imports_with_paths(
+    sema: &Semantics<RootDatabase>,
+    from: Module,
+    name_to_import: String,
+    exclude: Option<Definition>,
+    prefix_kind: Option<PrefixKind>,
+) -> Vec<LocatedImport> {
+    let _p = profile::span("find_exact_imports_with_paths");
+    let db = sema.db;
+    let krate = from.krate();
+
+    let external_query = import_map::Query::new(name_to_import.clone())
+        .limit(40)
+        .name_only()
+        .search_mode(import_map::SearchMode::Equals)
+        .case_sensitive();
+    let mut items = krate.query_external_importables(db, external_query).collect::<Vec<_>>();
+
+    let mut local_query = symbol_index::Query::new(name_to_import);
+    local_query.exact();
+    local_query.limit(40);
+    items.extend(
+        symbol_index::crate_symbols(db, krate.into(), local_query).into_iter().filter_map(
+            |import_candidate| match get_name_definition(sema, &import_candidate)? {
+                Definition::ModuleDef(module_def) => Some(Either::Left(module_def)),
+                Definition::Macro(macro_def) => Some(Either::Right(macro_def)),
+                _ => None,
+            },
+        ),
+    );
+
+    let use_path = |item: ItemInNs| match prefix_kind {
+        Some(prefix_kind) => from.find_use_path_prefixed(db, item, prefix_kind),
+        None => from.find_use_path(db, item),
+    };
+    let mut seen = FxHashSet::default();
+    let mut res = Vec::new();
+    for item in items {
+        if !seen.insert(item) || is_doc_hidden(db, &item) {
+            continue;
+        }
+        if let Some(exclude) = exclude {
+            if item.either(Definition::ModuleDef, Definition::Macro) == exclude {
+                continue;
+            }
+        }
+        // Like in the import maps, the associated items of traits are found through their
+        // traits.
+        let path = match item {
+            Either::Left(module_def) => {
+                let assoc_container = match module_def {
+                    ModuleDef::Function(it) => it.as_assoc_item(db),
+                    ModuleDef::Const(it) => it.as_assoc_item(db),
+                    ModuleDef::TypeAlias(it) => it.as_assoc_item(db),
+                    _ => None,
+                }
+                .map(|assoc| assoc.container(db));
+                match assoc_container {
+                    Some(AssocItemContainer::Trait(trait_)) => {
+                        use_path(ModuleDef::Trait(trait_).into()).and_then(|mut path| {
+                            path.segments.push(module_def.name(db)?);
+                            Some(path)
+                        })
+                    }
+                    Some(AssocItemContainer::Impl(_)) => None,
+                    None => use_path(module_def.into()),
+                }
+            }
+            Either::Right(macro_def) => use_path(macro_def.into()),
+        };
+        res.extend(path.map(|import_path| LocatedImport { item, import_path }));
+    }
+    res.sort_by_cached_key(|it| it.import_path.to_string());
+    res
+}
+
+/// Fuzzy searches for the items that can be imported into `from`, pairing each with a label to
+/// show it with: its name, or when other candidates have the same name, the shortest end of its
+/// path telling it apart from them, like `collections::HashMap` and `text::HashMap`.
+///
+/// The paths are searched for with [`PrefixKind::ByCrate`], so a label spanning a whole path of
+/// the local crate starts with `crate`. The candidates that can't be imported are left out.
+pub fn find_similar_imports_disambiguated(
+    sema: &Semantics<RootDatabase>,
+    from: Module,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<(Either<ModuleDef, MacroDef>, ModPath)> {
+    let _p = profile::span("find_similar_imports_disambiguated");
+    let db = sema.db;
+
+    let candidates = find_similar_imports(sema, from.krate(), fuzzy_search_string, options)
+        .filter_map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            Some((candidate, from.find_use_path_prefixed(db, item, PrefixKind::ByCrate)?))
+        })
+        .collect::<Vec<_>>();
+
+    let mut paths_by_name: FxHashMap<&Name, Vec<&ModPath>> = FxHashMap::default();
+    for (_, path) in &candidates {
+        if let Some(name) = path.segments.last() {
+            paths_by_name.entry(name).or_default().push(path);
+        }
+    }
+    let labels = candidates
+        .iter()
+        .map(|(_, path)| match path.segments.last().map(|name| &paths_by_name[name]) {
+            Some(same_named) => disambiguating_suffix(path, same_named),
+            None => path.clone(),
+        })
+        .collect::<Vec<_>>();
+    candidates.into_iter().map(|(candidate, _)| candidate).zip(labels).collect()
+}
+
+/// The fewest last segments of `path` not ending any of the `other` paths. Without a plain
+/// suffix telling them apart, `path` is returned as is, its kind makes the difference then.
+fn disambiguating_suffix(path: &ModPath, others: &[&ModPath]) -> ModPath {
+    let suffix = |path: &ModPath, len: usize| -> Vec<Name> {
+        path.segments[path.segments.len().saturating_sub(len)..].to_vec()
+    };
+    for len in 1..=path.segments.len() {
+        let own_suffix = suffix(path, len);
+        let is_ambiguous =
+            others.iter().any(|other| *other != path && suffix(other, len) == own_suffix);
+        if !is_ambiguous {
+            return ModPath::from_segments(PathKind::Plain, own_suffix);
+        }
+    }
+    path.clone()
+}
+
+/// Fuzzy searches for the items defined in `file_id` that can be imported into `krate`.
+///
+/// Only the symbol index of `krate` is queried, so dependencies are never searched.
+pub fn find_similar_imports_in_file<'a>(
+    sema: &Semantics<'a, RootDatabase>,
+    krate: Crate,
+    file_id: FileId,
+    fuzzy_search_string: String,
+) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_similar_imports_in_file");
+    find_imports(
+        sema,
+        krate,
+        symbol_index::Query::new(fuzzy_search_string.clone()),
+        import_map::Query::new(fuzzy_search_string).search_mode(import_map::SearchMode::Fuzzy),
+        None,
+        Some(file_id),
+        &ImportSearchOptions::default(),
     )
 }
 
+/// The kind of an item that can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum ItemCategory {
+    Module,
+    Function,
+    Adt,
+    Variant,
+    Const,
+    Static,
+    Trait,
+    TypeAlias,
+    BuiltinType,
+    Macro,
+}
+
+impl ItemCategory {
+    pub fn of(item: &Either<ModuleDef, MacroDef>) -> ItemCategory {
+        match item {
+            Either::Left(ModuleDef::Module(_)) => ItemCategory::Module,
+            Either::Left(ModuleDef::Function(_)) => ItemCategory::Function,
+            Either::Left(ModuleDef::Adt(_)) => ItemCategory::Adt,
+            Either::Left(ModuleDef::Variant(_)) => ItemCategory::Variant,
+            Either::Left(ModuleDef::Const(_)) => ItemCategory::Const,
+            Either::Left(ModuleDef::Static(_)) => ItemCategory::Static,
+            Either::Left(ModuleDef::Trait(_)) => ItemCategory::Trait,
+            Either::Left(ModuleDef::TypeAlias(_)) => ItemCategory::TypeAlias,
+            Either::Left(ModuleDef::BuiltinType(_)) => ItemCategory::BuiltinType,
+            Either::Right(_) => ItemCategory::Macro,
+        }
+    }
+}
+
+/// The namespace the imported items have to be usable in, like the type namespace for the type of
+/// a `let` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportNamespace {
+    Types,
+    Values,
+    Both,
+}
+
+impl ImportNamespace {
+    /// Checks whether `item` can be used in the namespace. Modules can be used in both, as the
+    /// qualifiers of the paths to the items in them.
+    pub fn contains(self, db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+        let module_def = match (self, item) {
+            (ImportNamespace::Both, _) => return true,
+            (_, Either::Left(it)) => it,
+            (_, Either::Right(_)) => return false,
+        };
+        match (self, module_def) {
+            (_, ModuleDef::Module(_)) => true,
+            (ImportNamespace::Types, it) => !matches!(
+                it,
+                ModuleDef::Function(_)
+                    | ModuleDef::Const(_)
+                    | ModuleDef::Static(_)
+                    | ModuleDef::Variant(_)
+            ),
+            // Unit and tuple structs are values as well, their constructors.
+            (_, ModuleDef::Adt(Adt::Struct(it))) => it.kind(db) != StructKind::Record,
+            (_, it) => matches!(
+                it,
+                ModuleDef::Function(_)
+                    | ModuleDef::Const(_)
+                    | ModuleDef::Static(_)
+                    | ModuleDef::Variant(_)
+            ),
+        }
+    }
+}
+
+/// Returns the items named exactly `name` that are of the given `category`.
+pub fn find_exact_typed<'a>(
+    sema: &Semantics<'a, RootDatabase>,
+    krate: Crate,
+    name: String,
+    category: ItemCategory,
+) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
+    find_exact_imports(sema, krate, name, None)
+        .filter(move |candidate| ItemCategory::of(candidate) == category)
+}
+
+/// Checks whether any importable item named exactly `name` exists, without collecting all of them.
+pub fn is_importable(sema: &Semantics<RootDatabase>, krate: Crate, name: &str) -> bool {
+    let _p = profile::span("is_importable");
+    let db = sema.db;
+
+    let external_query = import_map::Query::new(name.to_string())
+        .limit(1)
+        .name_only()
+        .search_mode(import_map::SearchMode::Equals)
+        .case_sensitive();
+    if krate.query_external_importables(db, external_query).next().is_some() {
+        return true;
+    }
+
+    let mut local_query = symbol_index::Query::new(name.to_string());
+    local_query.exact();
+    local_query.limit(1);
+    symbol_index::crate_symbols(db, krate.into(), local_query).iter().any(|import_candidate| {
+        matches!(
+            get_name_definition(sema, import_candidate),
+            Some(Definition::ModuleDef(_)) | Some(Definition::Macro(_))
+        )
+    })
+}
+
+/// How [`find_similar_imports`] and the searches built on it search, besides the query itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportSearchOptions {
+    /// The number of candidates to look for, in the local crate and in the dependencies each.
+    pub limit: Option<usize>,
+    /// Whether to leave out the associated items, like methods.
+    pub ignore_assoc_items: bool,
+    /// Whether to match the query against the modules of the item paths as well, so that `sync`
+    /// finds `std::sync::Arc`. Such candidates come after the ones whose names match.
+    pub match_full_path: bool,
+    /// When to stop searching, returning the candidates found so far.
+    pub deadline: Option<Instant>,
+    /// The namespace the candidates have to be usable in.
+    pub namespace: ImportNamespace,
+    /// Whether to keep the items marked `#[doc(hidden)]`, see [`is_doc_hidden`].
+    pub include_hidden: bool,
+    /// The number of candidates to keep from each dependency, for the lists to pick an import
+    /// from, see [`DEFAULT_MAX_CANDIDATES_PER_CRATE`]. All of them are kept by default.
+    pub max_per_crate: Option<usize>,
+    /// The crates whose items are left out, which can include the crate searched from.
+    pub excluded_crates: Vec<Crate>,
+}
+
+impl Default for ImportSearchOptions {
+    fn default() -> ImportSearchOptions {
+        ImportSearchOptions {
+            limit: None,
+            ignore_assoc_items: false,
+            match_full_path: false,
+            deadline: None,
+            namespace: ImportNamespace::Both,
+            include_hidden: false,
+            max_per_crate: None,
+            excluded_crates: Vec::new(),
+        }
+    }
+}
+
+/// Fuzzy searches for the items that can be imported into `krate`, as the `options` say.
+///
+/// The search checks for cancellation while going through the candidates, so a change to the
+/// database pending elsewhere unwinds it with [`Canceled`](base_db::Canceled) instead of letting
+/// it finish with stale results.
+///
+/// The candidates left out, like the hidden ones or the ones of the excluded crates, are dropped
+/// before `max_per_crate` is applied, so they don't take the places of others. Deprecated items
+/// come last.
 pub fn find_similar_imports<'a>(
     sema: &Semantics<'a, RootDatabase>,
     krate: Crate,
-    limit: Option<usize>,
     fuzzy_search_string: String,
-    ignore_assoc_items: bool,
-    name_only: bool,
+    options: &ImportSearchOptions,
 ) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> + 'a {
     let _p = profile::span("find_similar_imports");
+    let db = sema.db;
+    let ImportSearchOptions { limit, ignore_assoc_items, match_full_path, deadline, .. } = *options;
+
+    // Nothing is searched past the deadline, not even the cache.
+    if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+        return Vec::new().into_iter();
+    }
+    let key = SearchKey::new(&fuzzy_search_string, options);
+    let inputs = SearchInputs::new(db, krate);
+    if let Some(candidates) = db.import_search_cache.get(krate, &inputs, &key) {
+        return candidates.into_iter();
+    }
+    #[cfg(test)]
+    db.import_search_cache.scans.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
     let mut external_query = import_map::Query::new(fuzzy_search_string.clone())
-        .search_mode(import_map::SearchMode::Fuzzy);
-    if name_only {
+        .search_mode(import_map::SearchMode::Fuzzy)
+        .include_doc_aliases();
+    if !match_full_path {
         external_query = external_query.name_only();
     }
 
-    let mut local_query = symbol_index::Query::new(fuzzy_search_string);
+    let mut local_query = symbol_index::Query::new(fuzzy_search_string.clone());
 
     if let Some(limit) = limit {
         local_query.limit(limit);
         external_query = external_query.limit(limit);
     }
 
+    let mut candidates =
+        find_imports(sema, krate, local_query, external_query, None, None, options)
+            .filter(|import_candidate| !ignore_assoc_items || !is_assoc_item(db, import_candidate))
+            .collect::<Vec<_>>();
+
+    if match_full_path {
+        // The symbol index only knows the names of the local items, so add the items of the
+        // local modules that match.
+        let local_module_items = candidates
+            .iter()
+            .filter_map(|candidate| match candidate {
+                Either::Left(ModuleDef::Module(module)) if module.krate() == krate => Some(*module),
+                _ => None,
+            })
+            .flat_map(|module| module.declarations(db))
+            .map(Either::Left)
+            .collect::<Vec<_>>();
+        for item in local_module_items {
+            if !candidates.contains(&item) {
+                candidates.push(item);
+            }
+        }
+
+        let query = fuzzy_search_string.to_lowercase();
+        let name_matches = |candidate: &Either<ModuleDef, MacroDef>| {
+            let name = match candidate {
+                Either::Left(module_def) => module_def.name(db),
+                Either::Right(macro_def) => macro_def.name(db),
+            };
+            name.map_or(false, |name| is_subsequence(&query, &name.to_string().to_lowercase()))
+        };
+        candidates.sort_by_key(|candidate| !name_matches(candidate));
+    }
+    candidates.sort_by_key(|candidate| is_deprecated(db, candidate));
+
+    // The results of a search cut short are incomplete.
+    if deadline.map_or(true, |deadline| Instant::now() < deadline) {
+        db.import_search_cache.insert(krate, inputs, key, candidates.clone());
+    }
+    candidates.into_iter()
+}
+
+/// The results of the recent `find_similar_imports` searches, which are repeated with the same
+/// strings while typing.
+///
+/// The results for a crate are dropped once one of the salsa values the search reads changes, see
+/// `SearchInputs`.
+#[derive(Debug, Default)]
+pub(crate) struct ImportSearchCache {
+    crates: Mutex<FxHashMap<Crate, CachedSearches>>,
+    /// The number of searches that were not answered from the cache.
+    #[cfg(test)]
+    pub(crate) scans: std::sync::atomic::AtomicUsize,
+}
+
+#[derive(Debug)]
+struct CachedSearches {
+    inputs: SearchInputs,
+    results: FxHashMap<SearchKey, Vec<Either<ModuleDef, MacroDef>>>,
+}
+
+/// The salsa values a search of the items importable into a crate is computed from: the def map
+/// and the symbols of the files of the crate, for its local items, and the import maps of the
+/// crate and its dependencies.
+///
+/// Salsa hands out the same value for as long as it is not recomputed, so a search is still valid
+/// while these point to the current values. They are weak as not to keep the values of the past
+/// revisions alive, and hold on to the allocations so that a new value can't take their address.
+#[derive(Debug)]
+struct SearchInputs {
+    def_map: Weak<CrateDefMap>,
+    file_symbols: Vec<Weak<SymbolIndex>>,
+    import_maps: Vec<Weak<ImportMap>>,
+}
+
+impl SearchInputs {
+    fn new(db: &RootDatabase, krate: Crate) -> SearchInputs {
+        let krate_id = CrateId::from(krate);
+        let file_symbols = symbol_index::crate_files(db, krate_id)
+            .into_iter()
+            .map(|file_id| Arc::downgrade(&db.file_symbols(file_id)))
+            .collect();
+        let import_maps = iter::once(krate)
+            .chain(krate.dependencies(db).into_iter().map(|dep| dep.krate))
+            .map(|krate| Arc::downgrade(&db.import_map(krate.into())))
+            .collect();
+        SearchInputs {
+            def_map: Arc::downgrade(&db.crate_def_map(krate_id)),
+            file_symbols,
+            import_maps,
+        }
+    }
+
+    fn is_same(&self, other: &SearchInputs) -> bool {
+        fn same_values<T>(old: &[Weak<T>], new: &[Weak<T>]) -> bool {
+            old.len() == new.len() && old.iter().zip(new).all(|(old, new)| old.ptr_eq(new))
+        }
+
+        self.def_map.ptr_eq(&other.def_map)
+            && same_values(&self.file_symbols, &other.file_symbols)
+            && same_values(&self.import_maps, &other.import_maps)
+    }
+}
+
+/// The query string and the options of a `find_similar_imports` search. The searches are case
+/// insensitive, so the query string is lowercased.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct SearchKey {
+    query: String,
+    options: ImportSearchOptions,
+}
+
+impl SearchKey {
+    fn new(query: &str, options: &ImportSearchOptions) -> SearchKey {
+        let mut options = options.clone();
+        // The results of a search with a deadline are only cached if it didn't pass.
+        options.deadline = None;
+        options.excluded_crates.sort_by_key(|&it| CrateId::from(it));
+        options.excluded_crates.dedup();
+        SearchKey { query: query.to_lowercase(), options }
+    }
+}
+
+impl ImportSearchCache {
+    /// The number of searches kept for a crate, the cache of the crate starts over past it.
+    const CAPACITY: usize = 64;
+
+    fn get(
+        &self,
+        krate: Crate,
+        inputs: &SearchInputs,
+        key: &SearchKey,
+    ) -> Option<Vec<Either<ModuleDef, MacroDef>>> {
+        let mut crates = self.crates.lock().unwrap();
+        let searches = crates.get(&krate)?;
+        if !searches.inputs.is_same(inputs) {
+            crates.remove(&krate);
+            return None;
+        }
+        searches.results.get(key).cloned()
+    }
+
+    fn insert(
+        &self,
+        krate: Crate,
+        inputs: SearchInputs,
+        key: SearchKey,
+        candidates: Vec<Either<ModuleDef, MacroDef>>,
+    ) {
+        let mut crates = self.crates.lock().unwrap();
+        if crates.get(&krate).map_or(false, |searches| !searches.inputs.is_same(&inputs)) {
+            crates.remove(&krate);
+        }
+        let searches = crates
+            .entry(krate)
+            .or_insert_with(|| CachedSearches { inputs, results: FxHashMap::default() });
+        if searches.results.len() >= Self::CAPACITY {
+            searches.results.clear();
+        }
+        searches.results.insert(key, candidates);
+    }
+}
+
+/// Fuzzy searches for the items that can be imported into `krate` whose names match any of the
+/// `fuzzy_search_strings`, for the flows that offer importing one of several related names.
+///
+/// Each candidate is paired with the index of the search string it matched, a candidate matching
+/// several of them comes once for each. The dependencies are searched in a single pass for all
+/// the strings. Only the `limit`, `ignore_assoc_items` and `include_hidden` of the `options` are
+/// used.
+pub fn find_similar_imports_matching_any(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_strings: &[String],
+    options: &ImportSearchOptions,
+) -> Vec<(usize, Either<ModuleDef, MacroDef>)> {
+    let _p = profile::span("find_similar_imports_matching_any");
     let db = sema.db;
-    find_imports(sema, krate, local_query, external_query).filter(move |import_candidate| {
-        if ignore_assoc_items {
-            match import_candidate {
-                Either::Left(ModuleDef::Function(function)) => function.as_assoc_item(db).is_none(),
-                Either::Left(ModuleDef::Const(const_)) => const_.as_assoc_item(db).is_none(),
-                Either::Left(ModuleDef::TypeAlias(type_alias)) => {
-                    type_alias.as_assoc_item(db).is_none()
-                }
-                _ => true,
+    let ImportSearchOptions { limit, ignore_assoc_items, include_hidden, .. } = *options;
+
+    let external_queries = fuzzy_search_strings
+        .iter()
+        .map(|it| {
+            let query = import_map::Query::new(it.clone())
+                .search_mode(import_map::SearchMode::Fuzzy)
+                .name_only()
+                .include_doc_aliases();
+            match limit {
+                Some(limit) => query.limit(limit),
+                None => query,
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut candidates =
+        krate.query_external_importables_matching_any(db, &external_queries).collect::<Vec<_>>();
+
+    // The symbol index has no way to search for several names at once.
+    for (query_index, fuzzy_search_string) in fuzzy_search_strings.iter().enumerate() {
+        let mut local_query = symbol_index::Query::new(fuzzy_search_string.clone());
+        if let Some(limit) = limit {
+            local_query.limit(limit);
+        }
+        candidates.extend(
+            symbol_index::crate_symbols(db, krate.into(), local_query)
+                .into_iter()
+                .filter_map(|import_candidate| get_name_definition(sema, &import_candidate))
+                .filter_map(|definition| match definition {
+                    Definition::ModuleDef(module_def) => Some(Either::Left(module_def)),
+                    Definition::Macro(macro_def) => Some(Either::Right(macro_def)),
+                    _ => None,
+                })
+                .map(|candidate| (query_index, candidate)),
+        );
+    }
+
+    let mut seen = FxHashSet::default();
+    candidates.retain(|(query_index, candidate)| {
+        seen.insert((*query_index, *candidate))
+            && (include_hidden || !is_doc_hidden(db, candidate))
+            && (!ignore_assoc_items || !is_assoc_item(db, candidate))
+    });
+    candidates.sort_by_key(|(query_index, _)| *query_index);
+    candidates
+}
+
+/// Fuzzy searches for the items that can be imported into `krate`, like `find_similar_imports`,
+/// but puts the candidates that are already used in the file first.
+///
+/// `present_paths` are the names and paths the caller found in the file. A path matches the
+/// candidates it ends with, so `std::collections::HashMap` matches `collections::HashMap` and
+/// plain `HashMap` names.
+pub fn find_similar_imports_preferring(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+    present_paths: &FxHashSet<String>,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_similar_imports_preferring");
+    let db = sema.db;
+
+    let is_present = |candidate: &Either<ModuleDef, MacroDef>| {
+        let path = match candidate {
+            Either::Left(module_def) => module_def.canonical_path(db),
+            Either::Right(macro_def) => macro_def.name(db).map(|name| name.to_string()),
+        };
+        let path = match path {
+            Some(it) => it,
+            None => return false,
+        };
+        present_paths.iter().any(|present| {
+            present == &path
+                || present.ends_with(&format!("::{}", path))
+                || path.ends_with(&format!("::{}", present))
+        })
+    };
+
+    let mut res =
+        find_similar_imports(sema, krate, fuzzy_search_string, options).collect::<Vec<_>>();
+    res.sort_by_key(|candidate| !is_present(candidate));
+    res
+}
+
+/// Narrows down the candidates that `find_similar_imports` returned for `previous_query` to the
+/// ones matching `fuzzy_search_string`, as if searching for it with the same `options` from
+/// scratch.
+///
+/// This is only possible if the new query extends the previous one, so that it can't match
+/// anything the previous one didn't, and the previous search can't have been cut short, by a
+/// `limit`, by the cap of a dependency or by the deadline. The limit applies before the hidden
+/// and excluded items are left out, so any search with one may have been. Searching the item
+/// paths matches more than the names the previous candidates are narrowed down by. Otherwise, a
+/// full search is done.
+pub fn refine_similar_imports(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    previous_query: &str,
+    previous_candidates: &[Either<ModuleDef, MacroDef>],
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("refine_similar_imports");
+    let db = sema.db;
+
+    let query = fuzzy_search_string.to_lowercase();
+    let is_extension = query.starts_with(&previous_query.to_lowercase());
+    let was_capped = options.max_per_crate.map_or(false, |max_per_crate| {
+        let mut counts = FxHashMap::default();
+        for candidate in previous_candidates {
+            match candidate_crate(db, candidate) {
+                // The local crate isn't capped.
+                Some(it) if it == krate => (),
+                it => *counts.entry(it).or_insert(0) += 1,
+            }
+        }
+        counts.values().any(|&count| count >= max_per_crate)
+    });
+    if !is_extension
+        || options.limit.is_some()
+        || was_capped
+        || options.deadline.is_some()
+        || options.match_full_path
+    {
+        return find_similar_imports(sema, krate, fuzzy_search_string, options).collect();
+    }
+
+    previous_candidates
+        .iter()
+        .copied()
+        .filter(|candidate| {
+            let name = match candidate {
+                Either::Left(module_def) => module_def.name(db),
+                Either::Right(macro_def) => macro_def.name(db),
+            };
+            let name_matches =
+                name.map_or(false, |name| is_subsequence(&query, &name.to_string().to_lowercase()));
+            name_matches
+                || candidate_attrs(db, candidate).map_or(false, |attrs| {
+                    attrs.doc_aliases().any(|alias| is_subsequence(&query, &alias.to_lowercase()))
+                })
+        })
+        .collect()
+}
+
+/// Fuzzy searches for the items importable into `krate`, like [`find_similar_imports`], adding the
+/// replacements named by the notes of deprecated candidates, see [`deprecation_replacement`].
+/// Each replacement comes right before the deprecated candidate.
+pub fn find_similar_imports_with_replacements(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_similar_imports_with_replacements");
+    let candidates =
+        find_similar_imports(sema, krate, fuzzy_search_string, options).collect::<Vec<_>>();
+
+    let mut res = Vec::new();
+    for candidate in candidates.iter().copied() {
+        let replacement = deprecation_replacement(sema, krate, &candidate)
+            .filter(|it| !res.contains(it) && !candidates.contains(it));
+        res.extend(replacement);
+        if !res.contains(&candidate) {
+            res.push(candidate);
+        }
+    }
+    res
+}
+
+/// Returns the item the deprecation note of `item` suggests to use instead, like `Bar` for
+/// `#[deprecated(note = "use `bar::Bar` instead")]`.
+///
+/// This is a best effort: the replacement is the first path in backticks of the note, or else the
+/// word following `use`, and it has to name exactly one item importable into `krate` through a
+/// path ending with it.
+pub fn deprecation_replacement(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    item: &Either<ModuleDef, MacroDef>,
+) -> Option<Either<ModuleDef, MacroDef>> {
+    let db = sema.db;
+    let note = candidate_attrs(db, item)?.deprecation_note()?;
+    let quoted = note.split('`').nth(1);
+    let after_use = || {
+        let mut words = note.split_whitespace();
+        words.find(|it| it.eq_ignore_ascii_case("use"))?;
+        words.next()
+    };
+    let path = quoted.or_else(after_use)?;
+    let path = path.trim_end_matches(|c: char| !(c.is_alphanumeric() || c == '_'));
+    let segments = path.split("::").collect::<Vec<_>>();
+    let (name, qualifier) = segments.split_last()?;
+    if name.is_empty() {
+        return None;
+    }
+    // The crate of the replacement is given by its name, if at all.
+    let qualifier = qualifier
+        .iter()
+        .filter(|it| !matches!(**it, "crate" | "self" | "super"))
+        .collect::<Vec<_>>();
+
+    let mut replacements = find_exact_imports(sema, krate, name.to_string(), None)
+        .filter(|candidate| candidate != item)
+        .filter(|candidate| {
+            let module = match candidate {
+                Either::Left(module_def) => module_def.module(db),
+                Either::Right(macro_def) => macro_def.module(db),
+            };
+            let module_path = module.map(|it| module_path(db, it)).unwrap_or_default();
+            let module_path = module_path.split("::").collect::<Vec<_>>();
+            qualifier.len() <= module_path.len()
+                && qualifier.iter().rev().zip(module_path.iter().rev()).all(|(a, b)| *a == b)
+        });
+    let replacement = replacements.next()?;
+    // Several items with that path are as good as none.
+    match replacements.next() {
+        Some(_) => None,
+        None => Some(replacement),
+    }
+}
+
+fn is_assoc_item(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    match item {
+        Either::Left(ModuleDef::Function(function)) => function.as_assoc_item(db).is_some(),
+        Either::Left(ModuleDef::Const(const_)) => const_.as_assoc_item(db).is_some(),
+        Either::Left(ModuleDef::TypeAlias(type_alias)) => type_alias.as_assoc_item(db).is_some(),
+        _ => false,
+    }
+}
+
+/// Checks whether `item` is marked `#[doc(hidden)]`, or is only reachable through modules that are.
+/// An item re-exported from a module that isn't hidden can be imported from there, so it isn't
+/// hidden.
+pub fn is_doc_hidden(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    if candidate_attrs(db, item).map_or(false, |it| it.is_doc_hidden()) {
+        return true;
+    }
+    let module = match *item {
+        Either::Left(module_def) => module_def.module(db),
+        Either::Right(macro_def) => macro_def.module(db),
+    };
+    let module = match module {
+        Some(it) => it,
+        None => return false,
+    };
+    let item_in_ns: ItemInNs = item.either(Into::into, Into::into);
+    let container = module.krate().import_container(db, item_in_ns).unwrap_or(module);
+    container.path_to_root(db).into_iter().any(|it| it.attrs(db).is_doc_hidden())
+}
+
+/// Checks whether `item` is marked `#[deprecated]`.
+pub fn is_deprecated(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    candidate_attrs(db, item).map_or(false, |it| it.by_key("deprecated").exists())
+}
+
+fn candidate_attrs(db: &RootDatabase, candidate: &Either<ModuleDef, MacroDef>) -> Option<Attrs> {
+    let attrs = match *candidate {
+        Either::Left(ModuleDef::Module(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Function(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Adt(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Variant(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Const(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Static(it)) => it.attrs(db),
+        Either::Left(ModuleDef::Trait(it)) => it.attrs(db),
+        Either::Left(ModuleDef::TypeAlias(it)) => it.attrs(db),
+        Either::Left(ModuleDef::BuiltinType(_)) => return None,
+        Either::Right(it) => it.attrs(db),
+    };
+    Some(attrs)
+}
+
+/// Searches for the items importable into `krate` whose names are likely typos of `name`, for
+/// "did you mean" suggestions: `HashMap` for `HasMap`.
+///
+/// Unlike the fuzzy search, this only accepts names within one edit of `name`, or two for names
+/// longer than four characters, ignoring the case. The closest names come first.
+pub fn find_typo_corrections(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    name: &str,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_typo_corrections");
+    let db = sema.db;
+    let max_distance = if name.chars().count() > 4 { 2 } else { 1 };
+    let lowercased_name = name.to_lowercase();
+
+    // Both indices only hand out the names within reach, so that only those are resolved.
+    let external_query = import_map::Query::new(name.to_string())
+        .name_only()
+        .search_mode(import_map::SearchMode::EditDistance(max_distance));
+    let mut local_query = symbol_index::Query::new(name.to_string());
+    local_query.max_edit_distance(max_distance);
+
+    let options = ImportSearchOptions::default();
+    let mut res = find_imports(sema, krate, local_query, external_query, None, None, &options)
+        .filter(|candidate| !is_assoc_item(db, candidate))
+        .filter_map(|candidate| {
+            let candidate_name = match &candidate {
+                Either::Left(module_def) => module_def.name(db)?,
+                Either::Right(macro_def) => macro_def.name(db)?,
+            }
+            .to_string();
+            if candidate_name == name {
+                return None;
+            }
+            let distance = stdx::edit_distance(&lowercased_name, &candidate_name.to_lowercase());
+            if distance > max_distance {
+                return None;
+            }
+            Some((distance, candidate_name, candidate))
+        })
+        .collect::<Vec<_>>();
+    res.sort_by(|(distance_a, name_a, _), (distance_b, name_b, _)| {
+        (distance_a, name_a).cmp(&(distance_b, name_b))
+    });
+    res.into_iter().map(|(_, _, candidate)| candidate).collect()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|it| it == c))
+}
+
+/// Fuzzy searches for the items matching `fuzzy_search_string` or one of its `synonyms`, like
+/// `HashMap` for `dict`.
+///
+/// The keys of `synonyms` are lowercase queries. Matches of the query itself come first, the ones
+/// found through its synonyms follow in the order of the synonyms.
+pub fn find_similar_imports_with_synonyms(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+    synonyms: &FxHashMap<String, Vec<String>>,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_similar_imports_with_synonyms");
+    let alternatives = synonyms
+        .get(&fuzzy_search_string.to_lowercase())
+        .map_or(&[][..], |it| it.as_slice())
+        .iter()
+        .cloned();
+
+    let mut res = Vec::new();
+    for query in iter::once(fuzzy_search_string.clone()).chain(alternatives) {
+        for candidate in find_similar_imports(sema, krate, query, options) {
+            if !res.contains(&candidate) {
+                res.push(candidate);
+            }
+        }
+    }
+    if let Some(limit) = options.limit {
+        res.truncate(limit);
+    }
+    res
+}
+
+/// Fuzzy searches for the items that `krate` could re-export, for example from a `prelude`
+/// module: the public items of its dependencies and its own items that are reachable from its
+/// root through public paths. Only the `limit` of the `options` is used.
+pub fn find_reexportable_imports(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_reexportable_imports");
+    let db = sema.db;
+    let limit = options.limit;
+
+    let mut query = import_map::Query::new(fuzzy_search_string)
+        .search_mode(import_map::SearchMode::Fuzzy)
+        .name_only();
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+
+    let mut res = Vec::new();
+    let external_items = krate.query_external_importables(db, query.clone());
+    for item in external_items.chain(krate.query_public_items(db, query)) {
+        // Items living in both namespaces, like unit structs, are found twice.
+        if !res.contains(&item) {
+            res.push(item);
+        }
+    }
+    if let Some(limit) = limit {
+        res.truncate(limit);
+    }
+    res
+}
+
+/// Fuzzy searches for the items importable into `krate` that are part of the public API of their
+/// crate, see [`has_fully_public_path`]. The `limit` applies to the candidates that are kept.
+pub fn find_fully_public_imports(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_fully_public_imports");
+    let db = sema.db;
+    let unlimited = ImportSearchOptions { limit: None, ..options.clone() };
+    let mut res = find_similar_imports(sema, krate, fuzzy_search_string, &unlimited)
+        .filter(|item| has_fully_public_path(db, item))
+        .collect::<Vec<_>>();
+    if let Some(limit) = options.limit {
+        res.truncate(limit);
+    }
+    res
+}
+
+/// Checks whether every segment of the path declaring `item`, from the root of its crate, is
+/// `pub`. An item that is only `pub` itself may still be hidden in a `pub(crate)` module.
+pub fn has_fully_public_path(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    let (module, is_public) = match *item {
+        Either::Left(module_def) => match module_def.module(db) {
+            Some(module) => {
+                let visibility = module.visibility_of(db, &module_def);
+                (module, visibility.map_or(true, |it| it == Visibility::Public))
+            }
+            None => return true,
+        },
+        Either::Right(macro_def) => match macro_def.module(db) {
+            Some(module) => (module, true),
+            None => return true,
+        },
+    };
+    is_public
+        && module.path_to_root(db).into_iter().all(|it| it.visibility(db) == Visibility::Public)
+}
+
+/// The items of a dependency matching a search that only exist with one of its features enabled.
+#[derive(Debug)]
+pub struct FeatureGatedImports {
+    pub krate: Crate,
+    pub feature: SmolStr,
+    /// The paths the items would be imported with, starting with the name of the dependency.
+    pub paths: Vec<ModPath>,
+}
+
+/// Fuzzy searches the dependencies of `krate` for the public items left out by a `#[cfg]` that
+/// enabling a single feature of the dependency would bring in, grouped by the dependency and
+/// that feature, so that the feature can be offered along with the imports.
+pub fn find_feature_gated_imports(
+    db: &RootDatabase,
+    krate: Crate,
+    fuzzy_search_string: &str,
+) -> Vec<FeatureGatedImports> {
+    let _p = profile::span("find_feature_gated_imports");
+    let query = fuzzy_search_string.to_lowercase();
+    let mut groups: Vec<FeatureGatedImports> = Vec::new();
+    for dependency in krate.dependencies(db) {
+        for (module, item, feature) in dependency.krate.feature_gated_items(db) {
+            let item = item.value;
+            let is_public = item
+                .syntax()
+                .children()
+                .find_map(ast::Visibility::cast)
+                .map_or(false, |it| it.syntax().text() == "pub");
+            let name = match item.syntax().children().find_map(ast::Name::cast) {
+                Some(it) if is_public => it.as_name(),
+                _ => continue,
+            };
+            if !is_subsequence(&query, &name.to_string().to_lowercase())
+                || module.path_to_root(db).iter().any(|it| it.visibility(db) != Visibility::Public)
+            {
+                continue;
+            }
+            let mut segments = vec![dependency.name.clone()];
+            segments.extend(module.path_to_root(db).into_iter().rev().filter_map(|it| it.name(db)));
+            segments.push(name);
+            let path = ModPath::from_segments(PathKind::Plain, segments);
+
+            match groups.iter_mut().find(|it| it.krate == dependency.krate && it.feature == feature)
+            {
+                Some(group) => group.paths.push(path),
+                None => groups.push(FeatureGatedImports {
+                    krate: dependency.krate,
+                    feature,
+                    paths: vec![path],
+                }),
+            }
+        }
+    }
+    groups.iter_mut().for_each(|it| it.paths.sort_by_key(|it| it.to_string()));
+    groups
+}
+
+/// Fuzzy searches for the items importable into `krate`, split into the ones available through
+/// the `prelude` module of their crate, see [`is_in_prelude`], and the ones that need an explicit
+/// import.
+pub fn find_similar_imports_by_prelude(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> (Vec<Either<ModuleDef, MacroDef>>, Vec<Either<ModuleDef, MacroDef>>) {
+    let _p = profile::span("find_similar_imports_by_prelude");
+    let db = sema.db;
+    find_similar_imports(sema, krate, fuzzy_search_string, options)
+        .partition(|item| is_in_prelude(db, item))
+}
+
+/// Fuzzy searches for the items importable into `krate`, like [`find_similar_imports`], flagging
+/// the ones that can be passed by name where a `fn` pointer is expected, see
+/// [`is_usable_as_fn_pointer`].
+pub fn find_similar_imports_flagging_fn_pointers(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    fuzzy_search_string: String,
+    options: &ImportSearchOptions,
+) -> Vec<(bool, Either<ModuleDef, MacroDef>)> {
+    let _p = profile::span("find_similar_imports_flagging_fn_pointers");
+    let db = sema.db;
+    find_similar_imports(sema, krate, fuzzy_search_string, options)
+        .map(|candidate| (is_usable_as_fn_pointer(db, &candidate), candidate))
+        .collect()
+}
+
+/// Checks whether `item` is a function without a receiver, a free function or an associated
+/// function like `String::new`, which coerces to a `fn` pointer when used as a value.
+pub fn is_usable_as_fn_pointer(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    match item {
+        Either::Left(ModuleDef::Function(it)) => it.self_param(db).is_none(),
+        _ => false,
+    }
+}
+
+/// Checks whether `item` is re-exported from a public module named `prelude` at the root of its
+/// crate, or from one of the public modules of that prelude, like `std::prelude::v1`.
+pub fn is_in_prelude(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    let module = match *item {
+        Either::Left(module_def) => module_def.module(db),
+        Either::Right(macro_def) => macro_def.module(db),
+    };
+    let crate_root = match module {
+        Some(it) => it.krate().root_module(db),
+        None => return false,
+    };
+    let prelude = crate_root.scope(db, None).into_iter().find_map(|(name, def)| match def {
+        ScopeDef::ModuleDef(ModuleDef::Module(it)) if name.to_string() == "prelude" => Some(it),
+        _ => None,
+    });
+    let prelude = match prelude {
+        Some(it) if is_public_in(db, crate_root, ModuleDef::Module(it)) => it,
+        _ => return false,
+    };
+
+    let exports = |module: Module| {
+        module.scope(db, None).into_iter().any(|(_, def)| match (def, item) {
+            (ScopeDef::ModuleDef(def), Either::Left(item)) => {
+                def == *item && is_public_in(db, module, def)
+            }
+            (ScopeDef::MacroDef(def), Either::Right(item)) => def == *item,
+            _ => false,
+        })
+    };
+    exports(prelude)
+        || prelude.scope(db, None).into_iter().any(|(_, def)| match def {
+            ScopeDef::ModuleDef(ModuleDef::Module(it)) => {
+                is_public_in(db, prelude, ModuleDef::Module(it)) && exports(it)
+            }
+            _ => false,
+        })
+}
+
+fn is_public_in(db: &RootDatabase, module: Module, def: ModuleDef) -> bool {
+    module.visibility_of(db, &def).map_or(false, |it| it == Visibility::Public)
+}
+
+/// How well the name of an import candidate matches the search query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchTier {
+    /// The name is the query itself.
+    Exact,
+    /// The name starts with the query, ignoring the case.
+    Prefix,
+    /// The name contains the characters of the query in order.
+    Fuzzy,
+}
+
+/// Fuzzy searches for the items that can be imported into `krate`, returning them sorted by how
+/// well their names match the `query`: [`MatchTier::Exact`] matches first, then
+/// [`MatchTier::Prefix`], then [`MatchTier::Fuzzy`] ones. Deprecated items come last in their
+/// tier.
+pub fn find_ranked_imports(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    query: &str,
+    options: &ImportSearchOptions,
+) -> Vec<(MatchTier, Either<ModuleDef, MacroDef>)> {
+    let _p = profile::span("find_ranked_imports");
+    let db = sema.db;
+
+    let mut res = find_similar_imports(sema, krate, query.to_string(), options)
+        .filter_map(|candidate| {
+            let name = match &candidate {
+                Either::Left(module_def) => module_def.name(db)?,
+                Either::Right(macro_def) => macro_def.name(db)?,
             }
+            .to_string();
+            let tier = MatchTier::of(&name, query);
+            Some(((tier, is_deprecated(db, &candidate), name), candidate))
+        })
+        .collect::<Vec<_>>();
+    res.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+    res.into_iter().map(|((tier, ..), candidate)| (tier, candidate)).collect()
+}
+
+impl MatchTier {
+    /// How well `name` matches `query`.
+    pub fn of(name: &str, query: &str) -> MatchTier {
+        if name == query {
+            MatchTier::Exact
+        } else if name.to_lowercase().starts_with(&query.to_lowercase()) {
+            MatchTier::Prefix
         } else {
-            true
+            MatchTier::Fuzzy
+        }
+    }
+
+    /// A score for the tier, higher for better matches.
+    pub fn score(self) -> u32 {
+        match self {
+            MatchTier::Exact => 2,
+            MatchTier::Prefix => 1,
+            MatchTier::Fuzzy => 0,
+        }
+    }
+}
+
+/// Fuzzy searches for the items that can be imported into `current_module`, like
+/// [`find_ranked_imports`], but sorts the candidates of each [`MatchTier`] by relevance: those
+/// with shorter paths from `current_module` first, then those available through a prelude, see
+/// [`is_in_prelude`], and the rest by path.
+pub fn find_relevant_imports(
+    sema: &Semantics<RootDatabase>,
+    current_module: Module,
+    query: &str,
+    options: &ImportSearchOptions,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_relevant_imports");
+    let db = sema.db;
+    let mut res = find_ranked_imports(sema, current_module.krate(), query, options)
+        .into_iter()
+        .map(|(tier, candidate)| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            let path = current_module.find_use_path(db, item);
+            let path_len = path.as_ref().map_or(usize::MAX, |it| it.segments.len());
+            let path = path.map(|it| it.to_string()).unwrap_or_default();
+            ((tier, path_len, !is_in_prelude(db, &candidate), path), candidate)
+        })
+        .collect::<Vec<_>>();
+    res.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+    res.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// How much each signal counts when ranking the import candidates, see [`find_weighted_imports`].
+/// The score of a candidate is its [`MatchTier::score`] times `fuzzy_score`, less the penalties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankingWeights {
+    pub fuzzy_score: u32,
+    /// Subtracted for every segment of the path to import the candidate with.
+    pub path_depth_penalty: u32,
+    /// Subtracted for every dependency between the crate searched from and the crate defining
+    /// the candidate, nothing for the local items.
+    pub crate_distance_penalty: u32,
+    /// Subtracted for deprecated candidates, see [`is_deprecated`].
+    pub deprecation_penalty: u32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> RankingWeights {
+        RankingWeights {
+            fuzzy_score: 10,
+            path_depth_penalty: 1,
+            crate_distance_penalty: 2,
+            deprecation_penalty: 20,
+        }
+    }
+}
+
+/// Fuzzy searches for the items that can be imported into `current_module`, like
+/// [`find_ranked_imports`], but ranks the candidates by the score `weights` give them, the
+/// highest first. Candidates with the same score are sorted by path.
+pub fn find_weighted_imports(
+    sema: &Semantics<RootDatabase>,
+    current_module: Module,
+    query: &str,
+    options: &ImportSearchOptions,
+    weights: &RankingWeights,
+) -> Vec<(i64, Either<ModuleDef, MacroDef>)> {
+    let _p = profile::span("find_weighted_imports");
+    let db = sema.db;
+    let krate = current_module.krate();
+
+    // The number of dependencies to go through to reach each crate.
+    let mut crate_distances = FxHashMap::default();
+    crate_distances.insert(krate, 0);
+    let mut queue = std::collections::VecDeque::from(vec![krate]);
+    while let Some(it) = queue.pop_front() {
+        let distance = crate_distances[&it] + 1;
+        for dependency in it.dependencies(db) {
+            if !crate_distances.contains_key(&dependency.krate) {
+                crate_distances.insert(dependency.krate, distance);
+                queue.push_back(dependency.krate);
+            }
+        }
+    }
+
+    let mut res = find_ranked_imports(sema, krate, query, options)
+        .into_iter()
+        .filter_map(|(tier, candidate)| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            let path = current_module.find_use_path(db, item)?;
+            let defining_crate = match candidate {
+                Either::Left(module_def) => module_def.module(db)?.krate(),
+                Either::Right(macro_def) => macro_def.module(db)?.krate(),
+            };
+            let distance: i64 = crate_distances.get(&defining_crate).copied().unwrap_or(0);
+            let mut score = i64::from(tier.score()) * i64::from(weights.fuzzy_score);
+            score -= path.segments.len() as i64 * i64::from(weights.path_depth_penalty);
+            score -= distance * i64::from(weights.crate_distance_penalty);
+            if is_deprecated(db, &candidate) {
+                score -= i64::from(weights.deprecation_penalty);
+            }
+            Some(((Reverse(score), path.to_string()), candidate))
+        })
+        .collect::<Vec<_>>();
+    res.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+    res.into_iter().map(|((Reverse(score), _), candidate)| (score, candidate)).collect()
+}
+
+/// An import candidate in a form that doesn't need the database to be inspected, for tools that
+/// consume the search results outside of rust-analyzer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImportRecord {
+    pub name: String,
+    /// The path to use to import the item into the module the search is done from.
+    pub path: String,
+    pub kind: ItemCategory,
+    /// The display name of the crate defining the item, if it has one.
+    #[cfg_attr(feature = "serde", serde(rename = "crate"))]
+    pub krate: Option<String>,
+    /// See [`MatchTier::score`].
+    pub score: u32,
+    /// Whether the item is a type that can't be used without generic arguments, see
+    /// [`requires_generic_args`].
+    pub requires_generic_args: bool,
+    /// The signature of a function, if requested, see [`function_signature`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub signature: Option<String>,
+}
+
+/// Like [`find_ranked_imports`], but describes the candidates importable into `current_module`
+/// as [`ImportRecord`]s. The signatures of the functions are only rendered `with_signatures`.
+pub fn find_import_records(
+    sema: &Semantics<RootDatabase>,
+    current_module: Module,
+    query: &str,
+    options: &ImportSearchOptions,
+    with_signatures: bool,
+) -> Vec<ImportRecord> {
+    let db = sema.db;
+    find_ranked_imports(sema, current_module.krate(), query, options)
+        .into_iter()
+        .filter_map(|(tier, candidate)| {
+            let (name, module) = match candidate {
+                Either::Left(module_def) => (module_def.name(db)?, module_def.module(db)?),
+                Either::Right(macro_def) => (macro_def.name(db)?, macro_def.module(db)?),
+            };
+            let item_in_ns: ItemInNs = candidate.either(Into::into, Into::into);
+            let path = current_module.find_use_path(db, item_in_ns)?;
+            Some(ImportRecord {
+                name: name.to_string(),
+                path: path.to_string(),
+                kind: ItemCategory::of(&candidate),
+                krate: module.krate().display_name(db).map(|it| it.to_string()),
+                score: tier.score(),
+                requires_generic_args: requires_generic_args(db, &candidate),
+                signature: match candidate {
+                    Either::Left(ModuleDef::Function(it)) if with_signatures => {
+                        Some(function_signature(db, it))
+                    }
+                    _ => None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Like [`find_import_records`], but passes each record through `post_process` after ranking,
+/// which can rewrite it, like mapping `std::` paths to a facade crate, or drop it by returning
+/// `None`. The `limit` applies to the records that are kept.
+pub fn find_import_records_with(
+    sema: &Semantics<RootDatabase>,
+    current_module: Module,
+    query: &str,
+    options: &ImportSearchOptions,
+    with_signatures: bool,
+    post_process: impl FnMut(ImportRecord) -> Option<ImportRecord>,
+) -> Vec<ImportRecord> {
+    let unlimited = ImportSearchOptions { limit: None, ..options.clone() };
+    let records = find_import_records(sema, current_module, query, &unlimited, with_signatures);
+    let records = records.into_iter().filter_map(post_process);
+    match options.limit {
+        Some(limit) => records.take(limit).collect(),
+        None => records.collect(),
+    }
+}
+
+/// Returns a short label combining the kind of the item with its immediate container, like
+/// `fn in std::mem` for a free function or `method in Vec` for a method.
+pub fn kind_and_container(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> Option<String> {
+    let module_def = match *item {
+        Either::Left(it) => it,
+        Either::Right(macro_def) => {
+            return Some(format!("macro in {}", module_path(db, macro_def.module(db)?)));
         }
+    };
+    let kind = match module_def {
+        ModuleDef::Module(_) => "mod",
+        ModuleDef::Function(it) if it.self_param(db).is_some() => "method",
+        ModuleDef::Function(_) => "fn",
+        ModuleDef::Adt(Adt::Struct(_)) => "struct",
+        ModuleDef::Adt(Adt::Enum(_)) => "enum",
+        ModuleDef::Adt(Adt::Union(_)) => "union",
+        ModuleDef::Variant(_) => "variant",
+        ModuleDef::Const(_) => "const",
+        ModuleDef::Static(_) => "static",
+        ModuleDef::Trait(_) => "trait",
+        ModuleDef::TypeAlias(_) => "type",
+        ModuleDef::BuiltinType(_) => return Some("builtin type".to_string()),
+    };
+
+    let assoc_container = match module_def {
+        ModuleDef::Function(it) => it.as_assoc_item(db),
+        ModuleDef::Const(it) => it.as_assoc_item(db),
+        ModuleDef::TypeAlias(it) => it.as_assoc_item(db),
+        _ => None,
+    }
+    .map(|assoc| assoc.container(db));
+    let container = match (module_def, assoc_container) {
+        (_, Some(AssocItemContainer::Trait(trait_))) => trait_.name(db).to_string(),
+        (_, Some(AssocItemContainer::Impl(impl_))) => match impl_.target_ty(db).as_adt() {
+            Some(adt) => adt.name(db).to_string(),
+            None => impl_.target_ty(db).display(db).to_string(),
+        },
+        (ModuleDef::Variant(variant), None) => variant.parent_enum(db).name(db).to_string(),
+        (_, None) => module_path(db, module_def.module(db)?),
+    };
+    Some(format!("{} in {}", kind, container))
+}
+
+/// The path of `module` starting with the display name of its crate, like `std::collections`.
+fn module_path(db: &RootDatabase, module: Module) -> String {
+    let mut segments = module
+        .path_to_root(db)
+        .into_iter()
+        .rev()
+        .filter_map(|it| it.name(db))
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    if let Some(crate_name) = module.krate().display_name(db) {
+        segments.insert(0, crate_name.to_string());
+    }
+    segments.join("::")
+}
+
+/// Renders the parameters and the return type of `function`, like
+/// `fn connect(host: &str, port: u16) -> Connection`.
+pub fn function_signature(db: &RootDatabase, function: Function) -> String {
+    let mut params = Vec::new();
+    let mut param_types = function.assoc_fn_params(db).into_iter();
+    if let Some(self_param) = function.self_param(db) {
+        param_types.next();
+        params.push(
+            match self_param.access(db) {
+                Access::Shared => "&self",
+                Access::Exclusive => "&mut self",
+                Access::Owned => "self",
+            }
+            .to_string(),
+        );
+    }
+    // The names of the parameters are only known from the source, and may be missing there.
+    let param_names = function
+        .source(db)
+        .and_then(|it| it.value.param_list())
+        .map(|it| it.params().map(|param| param.pat()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    for (idx, param) in param_types.enumerate() {
+        let ty = param.ty().display(db);
+        match param_names.get(idx) {
+            Some(Some(pat)) => params.push(format!("{}: {}", pat, ty)),
+            _ => params.push(ty.to_string()),
+        }
+    }
+
+    let mut res = format!("fn {}({})", function.name(db), params.join(", "));
+    let ret_type = function.ret_type(db);
+    if !ret_type.is_unit() {
+        format_to!(res, " -> {}", ret_type.display(db));
+    }
+    res
+}
+
+/// Checks whether the item is a type constructor like `Vec`, which needs generic arguments to be
+/// used as a type, unlike `String`. Type parameters with defaults don't need arguments.
+pub fn requires_generic_args(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> bool {
+    match *item {
+        Either::Left(ModuleDef::Adt(it)) => it.has_non_default_type_params(db),
+        Either::Left(ModuleDef::TypeAlias(it)) => it.has_non_default_type_params(db),
+        _ => false,
+    }
+}
+
+/// Returns the number of generic parameters that can be specified for the item with a turbofish,
+/// that is, its type and const parameters.
+pub fn generic_param_count(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> usize {
+    let def = match *item {
+        Either::Left(ModuleDef::Function(it)) => GenericDef::Function(it),
+        Either::Left(ModuleDef::Adt(it)) => GenericDef::Adt(it),
+        Either::Left(ModuleDef::Trait(it)) => GenericDef::Trait(it),
+        Either::Left(ModuleDef::TypeAlias(it)) => GenericDef::TypeAlias(it),
+        _ => return 0,
+    };
+    def.params(db)
+        .into_iter()
+        .filter(|param| match param {
+            GenericParam::TypeParam(it) => !it.is_implicit(db),
+            GenericParam::ConstParam(_) => true,
+            GenericParam::LifetimeParam(_) => false,
+        })
+        .count()
+}
+
+/// Returns the Rust version the item was stabilized in, as given by its `#[stable]` attribute.
+///
+/// Only the items of the standard library have that attribute.
+pub fn stable_since(db: &RootDatabase, item: &Either<ModuleDef, MacroDef>) -> Option<SmolStr> {
+    candidate_attrs(db, item)?.stable_since()
+}
+
+/// Returns the first module on the path to `item` that is private to `from`, if that's what
+/// prevents importing `item` there.
+///
+/// Making that module public, possibly along with other modules after it, makes the import
+/// possible.
+pub fn first_private_module(
+    db: &RootDatabase,
+    from: Module,
+    item: &Either<ModuleDef, MacroDef>,
+) -> Option<Module> {
+    let item_in_ns: ItemInNs = item.either(Into::into, Into::into);
+    if from.find_use_path(db, item_in_ns).is_some() {
+        return None;
+    }
+    let module = match *item {
+        Either::Left(module_def) => module_def.module(db)?,
+        Either::Right(macro_def) => macro_def.module(db)?,
+    };
+    let mut path = module.path_to_root(db);
+    path.reverse();
+    path.into_iter().find(|module| !module.is_visible_from(db, from))
+}
+
+/// Completes the segment of `partial_path` that doesn't resolve yet, like `collec` in
+/// `std::collec`, with the names of the items visible from `from` in the module that the
+/// segments before it resolve to.
+///
+/// Segments after the first one that isn't a module are ignored. Without any prefix the names of
+/// the dependencies and of the items of the crate root are suggested.
+pub fn complete_path_segment(db: &RootDatabase, from: Module, partial_path: &str) -> Vec<Name> {
+    let _p = profile::span("complete_path_segment");
+    let krate = from.krate();
+    let crate_root = krate.root_module(db);
+    let resolve_segment = |prefix: Option<Module>, segment: &str| match prefix {
+        Some(module) => child_module(db, from, module, segment),
+        None if segment == "crate" => Some(crate_root),
+        None => krate
+            .dependencies(db)
+            .into_iter()
+            .find(|dep| dep.name.to_string() == segment)
+            .map(|dep| dep.krate.root_module(db))
+            .or_else(|| child_module(db, from, crate_root, segment)),
+    };
+
+    let mut segments = partial_path.split("::");
+    let mut prefix = None;
+    let mut partial_segment = segments.next().unwrap_or_default();
+    for segment in segments {
+        match resolve_segment(prefix, partial_segment) {
+            Some(module) => prefix = Some(module),
+            None => break,
+        }
+        partial_segment = segment;
+    }
+
+    let mut names = match prefix {
+        Some(module) => module.scope(db, Some(from)).into_iter().map(|(name, _)| name).collect(),
+        None => {
+            let dependencies = krate.dependencies(db).into_iter().map(|dep| dep.name);
+            let root_items = crate_root.scope(db, Some(from)).into_iter().map(|(name, _)| name);
+            dependencies.chain(root_items).collect::<Vec<_>>()
+        }
+    };
+    let partial_segment = partial_segment.to_lowercase();
+    names.retain(|name| name.to_string().to_lowercase().starts_with(&partial_segment));
+    names.sort_by_key(|name| name.to_string());
+    names.dedup();
+    names
+}
+
+fn child_module(db: &RootDatabase, from: Module, module: Module, name: &str) -> Option<Module> {
+    module.scope(db, Some(from)).into_iter().find_map(|(it, def)| match def {
+        ScopeDef::ModuleDef(ModuleDef::Module(child)) if it.to_string() == name => Some(child),
+        _ => None,
     })
 }
 
+/// Searches for the traits importable into `krate` that have a method named exactly
+/// `method_name`, to import for a call of that method. Each trait is returned once, even if it's
+/// reachable through several paths, and the local traits have to be visible from the root of
+/// `krate`.
+pub fn find_traits_for_method(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    method_name: &str,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let _p = profile::span("find_traits_for_method");
+    let db = sema.db;
+    let mut local_query = symbol_index::Query::new(method_name.to_string());
+    local_query.exact();
+    local_query.limit(40);
+    let external_query = import_map::Query::new(method_name.to_string())
+        .search_mode(import_map::SearchMode::Equals)
+        .name_only()
+        .case_sensitive()
+        .limit(40);
+
+    let crate_root = krate.root_module(db);
+    let mut traits = find_imports(
+        sema,
+        krate,
+        local_query,
+        external_query,
+        None,
+        None,
+        &ImportSearchOptions {
+            namespace: ImportNamespace::Values,
+            ..ImportSearchOptions::default()
+        },
+    )
+    .filter_map(|candidate| match candidate {
+        Either::Left(ModuleDef::Function(function)) if function.self_param(db).is_some() => {
+            match function.as_assoc_item(db)?.container(db) {
+                AssocItemContainer::Trait(it) => Some(it),
+                AssocItemContainer::Impl(_) => None,
+            }
+        }
+        _ => None,
+    })
+    .filter(|trait_| {
+        trait_.module(db).krate() != krate
+            || crate_root.find_use_path(db, ModuleDef::Trait(*trait_)).is_some()
+    })
+    .collect::<FxHashSet<_>>()
+    .into_iter()
+    .collect::<Vec<_>>();
+    traits.sort_by_cached_key(|it| (it.name(db).to_string(), module_path(db, it.module(db))));
+    traits.into_iter().map(|it| Either::Left(ModuleDef::Trait(it))).collect()
+}
+
+/// Looks for traits that are not in scope yet, but would provide a method fuzzy matching `query`
+/// for `receiver_ty` once imported.
+///
+/// Returns the traits to import paired with the names of the matching methods. The traits that
+/// are already used in the file of `current_module`, like in another module, come first.
+pub fn find_method_completions_requiring_import(
+    sema: &Semantics<RootDatabase>,
+    current_module: Module,
+    receiver_ty: &Type,
+    query: &str,
+) -> Vec<(Trait, Name)> {
+    let _p = profile::span("find_method_completions_requiring_import");
+    let db = sema.db;
+    let krate = current_module.krate();
+
+    let mut local_query = symbol_index::Query::new(query.to_string());
+    local_query.limit(40);
+    let external_query = import_map::Query::new(query.to_string())
+        .search_mode(import_map::SearchMode::Fuzzy)
+        .name_only()
+        .limit(40);
+
+    let mut res = Vec::new();
+    let mut trait_candidates = FxHashSet::default();
+    for candidate in find_imports(
+        sema,
+        krate,
+        local_query,
+        external_query,
+        None,
+        None,
+        &ImportSearchOptions::default(),
+    ) {
+        let function = match candidate {
+            Either::Left(ModuleDef::Function(function)) if function.self_param(db).is_some() => {
+                function
+            }
+            _ => continue,
+        };
+        let trait_ = match function.as_assoc_item(db).map(|assoc| assoc.container(db)) {
+            Some(AssocItemContainer::Trait(trait_)) => trait_,
+            _ => continue,
+        };
+
+        // Resolving the method with only this trait in scope checks that one of its impls
+        // applies to the receiver, including the bounds of blanket impls like
+        // `impl<T: Bound> Trait for T`.
+        trait_candidates.clear();
+        trait_candidates.insert(trait_.into());
+        let method_name = function.name(db);
+        let applicable = receiver_ty
+            .iterate_method_candidates(
+                db,
+                krate,
+                &trait_candidates,
+                Some(&method_name),
+                |_, method| Some(method),
+            )
+            .is_some();
+        if applicable && !res.contains(&(trait_, method_name.clone())) {
+            res.push((trait_, method_name));
+        }
+    }
+
+    let file_id = current_module.definition_source(db).file_id.original_file(db);
+    let is_used_in_file = |trait_: Trait| {
+        let item_defs = trait_.items(db).into_iter().map(|item| match item {
+            AssocItem::Function(it) => ModuleDef::Function(it),
+            AssocItem::Const(it) => ModuleDef::Const(it),
+            AssocItem::TypeAlias(it) => ModuleDef::TypeAlias(it),
+        });
+        iter::once(ModuleDef::Trait(trait_)).chain(item_defs).any(|def| {
+            Definition::ModuleDef(def)
+                .usages(sema)
+                .in_scope(SearchScope::single_file(file_id))
+                .at_least_one()
+        })
+    };
+    let mut used_traits = FxHashSet::default();
+    for (trait_, _) in &res {
+        if is_used_in_file(*trait_) {
+            used_traits.insert(*trait_);
+        }
+    }
+    res.sort_by_key(|(trait_, _)| !used_traits.contains(trait_));
+    res
+}
+
+/// Searches the importable functions that take arguments of the `params` types, in this order,
+/// and return a value of the `ret` type, like a `fn(&str) -> String`. The arguments and the
+/// returned value may go through deref coercions, a function taking a `&str` accepts a
+/// `&String`. Methods count as functions taking their receiver first.
+///
+/// Any function can match, so this has to look at all of them. Only the first few hundred
+/// functions of the local crate and of the dependencies are checked.
+pub fn find_by_signature(
+    sema: &Semantics<RootDatabase>,
+    krate: Crate,
+    params: &[Type],
+    ret: Type,
+) -> Vec<Function> {
+    let _p = profile::span("find_by_signature");
+    let db = sema.db;
+
+    let mut local_query = symbol_index::Query::new(String::new());
+    local_query.limit(500);
+    let external_query = [
+        import_map::ImportKind::Module,
+        import_map::ImportKind::Adt,
+        import_map::ImportKind::EnumVariant,
+        import_map::ImportKind::Const,
+        import_map::ImportKind::Static,
+        import_map::ImportKind::Trait,
+        import_map::ImportKind::TypeAlias,
+        import_map::ImportKind::BuiltinType,
+    ]
+    .iter()
+    .fold(import_map::Query::new(String::new()), |query, kind| {
+        query.exclude_import_kind(kind.clone())
+    })
+    .limit(500);
+
+    let mut res = find_imports(
+        sema,
+        krate,
+        local_query,
+        external_query,
+        None,
+        None,
+        &ImportSearchOptions::default(),
+    )
+    .filter_map(|candidate| match candidate {
+        Either::Left(ModuleDef::Function(it)) => Some(it),
+        _ => None,
+    })
+    .filter(|function| {
+        let function_params = function.assoc_fn_params(db);
+        function_params.len() == params.len()
+            && params
+                .iter()
+                .zip(function_params)
+                .all(|(param, function_param)| param.could_coerce_to(db, function_param.ty()))
+            && function.ret_type(db).could_coerce_to(db, &ret)
+    })
+    .collect::<Vec<_>>();
+    res.sort_by_cached_key(|it| it.name(db).to_string());
+    res
+}
+
+/// A run of `use` items of the same [`ImportGroup`], not separated by a blank line or another item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseGroup {
+    pub kind: ImportGroup,
+    pub range: TextRange,
+}
+
+/// Where the `use` items of a file are, and where new ones go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportInsertionPoints {
+    /// The top-level `use` groups of the file, in order.
+    pub groups: Vec<UseGroup>,
+    /// Where the imports go in a file without any, after its inner attributes and doc comments.
+    pub first_offset: TextSize,
+}
+
+impl ImportInsertionPoints {
+    /// The offset to insert a `use` of the `kind` group at: the end of the first group of that
+    /// kind, otherwise before the first group of a kind that comes after it, or after the last
+    /// group.
+    pub fn offset_for(&self, kind: ImportGroup) -> TextSize {
+        if let Some(group) = self.groups.iter().find(|it| it.kind == kind) {
+            return group.range.end();
+        }
+        match self.groups.iter().find(|it| it.kind > kind) {
+            Some(group) => group.range.start(),
+            None => self.groups.last().map_or(self.first_offset, |it| it.range.end()),
+        }
+    }
+}
+
+/// Finds the `use` groups at the top level of the file and where new imports can go, keeping the
+/// `std`, external crate, and local imports apart in that order.
+pub fn import_insertion_points(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+) -> ImportInsertionPoints {
+    let source_file = sema.parse(file_id);
+    let mut groups: Vec<UseGroup> = Vec::new();
+    let mut is_continued = false;
+    for element in source_file.syntax().children_with_tokens() {
+        let use_ = match element {
+            NodeOrToken::Token(token) => {
+                if token.kind() == WHITESPACE && token.text().matches('\n').count() > 1 {
+                    is_continued = false;
+                }
+                continue;
+            }
+            NodeOrToken::Node(node) => match ast::Use::cast(node) {
+                Some(it) => it,
+                None => {
+                    is_continued = false;
+                    continue;
+                }
+            },
+        };
+        let kind = match use_.use_tree().and_then(|it| it.path()) {
+            Some(path) => ImportGroup::new(&path),
+            None => continue,
+        };
+        let range = use_.syntax().text_range();
+        match groups.last_mut() {
+            Some(group) if is_continued && group.kind == kind => {
+                group.range = group.range.cover(range)
+            }
+            _ => groups.push(UseGroup { kind, range }),
+        }
+        is_continued = true;
+    }
+
+    let first_offset = match ImportScope::File(source_file).insert_pos_after_last_inner_element().0
+    {
+        InsertPosition::After(element) => element.text_range().end(),
+        _ => 0.into(),
+    };
+    ImportInsertionPoints { groups, first_offset }
+}
+
+/// Searches the dependencies of `krate` with `external_query` and `krate` itself with
+/// `local_query`, keeping the candidates the `options` allow. The options limiting the queries
+/// are expected to be applied to them already.
 fn find_imports<'a>(
     sema: &Semantics<'a, RootDatabase>,
     krate: Crate,
     local_query: symbol_index::Query,
     external_query: import_map::Query,
+    exclude: Option<Definition>,
+    file_id: Option<FileId>,
+    options: &ImportSearchOptions,
 ) -> impl Iterator<Item = Either<ModuleDef, MacroDef>> {
     let _p = profile::span("find_similar_imports");
     let db = sema.db;
+    let ImportSearchOptions { deadline, namespace, include_hidden, max_per_crate, .. } = *options;
+    let excluded_crates = &options.excluded_crates;
+    let is_excluded = |candidate: &Either<ModuleDef, MacroDef>| {
+        candidate_crate(db, candidate).map_or(false, |it| excluded_crates.contains(&it))
+    };
+    // The candidates left out are dropped before `max_per_crate` is applied, so that they don't
+    // take the places of the others.
+    let is_kept = |candidate: &Either<ModuleDef, MacroDef>| {
+        namespace.contains(db, candidate)
+            && !is_excluded(candidate)
+            && (include_hidden || !is_doc_hidden(db, candidate))
+    };
+    let before_deadline = || deadline.map_or(true, |deadline| Instant::now() < deadline);
+
+    let mut candidates = FxHashSet::default();
+
+    // Query dependencies first, unless only the items of a local file are wanted.
+    if file_id.is_none() && before_deadline() {
+        let external_candidates =
+            krate.query_external_importables(db, external_query.clone()).filter(|it| is_kept(it));
+        match max_per_crate {
+            Some(max_per_crate) => candidates.extend(cap_per_crate(
+                db,
+                external_candidates,
+                external_query.text(),
+                max_per_crate,
+            )),
+            None => candidates.extend(external_candidates),
+        }
 
-    // Query dependencies first.
-    let mut candidates: FxHashSet<_> =
-        krate.query_external_importables(db, external_query).collect();
+        // The symbol index knows nothing about `#[doc(alias)]`, so look the local aliases up
+        // separately, if requested.
+        candidates.extend(krate.query_doc_aliases(db, external_query).filter(|it| is_kept(it)));
+    }
 
     // Query the local crate using the symbol index.
-    let local_results = symbol_index::crate_symbols(db, krate.into(), local_query);
+    if before_deadline() && !excluded_crates.contains(&krate) {
+        let local_results = symbol_index::crate_symbols(db, krate.into(), local_query);
 
-    candidates.extend(
-        local_results
-            .into_iter()
-            .filter_map(|import_candidate| get_name_definition(sema, &import_candidate))
-            .filter_map(|name_definition_to_import| match name_definition_to_import {
-                Definition::ModuleDef(module_def) => Some(Either::Left(module_def)),
-                Definition::Macro(macro_def) => Some(Either::Right(macro_def)),
-                _ => None,
-            }),
-    );
+        candidates.extend(
+            local_results
+                .into_iter()
+                .take_while(|_| before_deadline())
+                .inspect(|_| db.check_canceled())
+                .filter(|import_candidate| {
+                    file_id.map_or(true, |file_id| import_candidate.file_id == file_id)
+                })
+                .filter_map(|import_candidate| get_name_definition(sema, &import_candidate))
+                .filter_map(|name_definition_to_import| match name_definition_to_import {
+                    Definition::ModuleDef(module_def) => Some(Either::Left(module_def)),
+                    Definition::Macro(macro_def) => Some(Either::Right(macro_def)),
+                    _ => None,
+                })
+                .filter(|it| is_kept(it)),
+        );
+    }
+
+    if let Some(exclude) = exclude {
+        candidates.retain(|candidate| {
+            let definition = match *candidate {
+                Either::Left(module_def) => Definition::ModuleDef(module_def),
+                Either::Right(macro_def) => Definition::Macro(macro_def),
+            };
+            definition != exclude
+        });
+    }
 
     candidates.into_iter()
 }
 
+/// The number of candidates a single dependency contributes to the results of the fuzzy
+/// searches, so that a large crate with many similarly named items doesn't push out the others.
+pub const DEFAULT_MAX_CANDIDATES_PER_CRATE: usize = 10;
+
+/// Keeps at most `max_per_crate` of the `candidates` defined in each crate, the ones best
+/// matching `query`, see [`MatchTier`].
+fn cap_per_crate(
+    db: &RootDatabase,
+    candidates: impl Iterator<Item = Either<ModuleDef, MacroDef>>,
+    query: &str,
+    max_per_crate: usize,
+) -> Vec<Either<ModuleDef, MacroDef>> {
+    let mut by_crate: FxHashMap<Option<Crate>, Vec<_>> = FxHashMap::default();
+    for candidate in candidates {
+        // Unit and tuple structs are found once in each namespace.
+        let candidates = by_crate.entry(candidate_crate(db, &candidate)).or_default();
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    by_crate
+        .into_iter()
+        .flat_map(|(_, mut candidates)| {
+            candidates.sort_by_cached_key(|candidate| {
+                let name = match candidate {
+                    Either::Left(module_def) => module_def.name(db),
+                    Either::Right(macro_def) => macro_def.name(db),
+                }
+                .map(|it| it.to_string())
+                .unwrap_or_default();
+                (MatchTier::of(&name, query), is_deprecated(db, candidate), name)
+            });
+            candidates.truncate(max_per_crate);
+            candidates
+        })
+        .collect()
+}
+
+/// The crate defining `candidate`, built-in types have none.
+fn candidate_crate(db: &RootDatabase, candidate: &Either<ModuleDef, MacroDef>) -> Option<Crate> {
+    match candidate {
+        Either::Left(ModuleDef::Module(module)) => Some(module.krate()),
+        Either::Left(module_def) => module_def.module(db).map(|it| it.krate()),
+        Either::Right(macro_def) => macro_def.module(db).map(|it| it.krate()),
+    }
+}
+
 fn get_name_definition<'a>(
     sema: &Semantics<'a, RootDatabase>,
     import_candidate: &FileSymbol,
@@ -121,3 +2123,6 @@ fn get_name_definition<'a>(
     let name = ast::Name::cast(candidate_name_node)?;
     NameClass::classify(sema, &name)?.defined(sema.db)
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1987 @@
+use std::{
+    sync::{atomic::Ordering, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use base_db::{
+    fixture::ChangeFixture,
+    salsa::{Database, ParallelDatabase},
+    CheckCanceled, FilePosition, SourceDatabaseExt,
+};
+use either::Either;
+use expect_test::{expect, Expect};
+use hir::{ItemInNs, Module, ModuleDef, Semantics};
+use rustc_hash::FxHashMap;
+use syntax::{ast, AstNode};
+use test_utils::RangeOrOffset;
+
+use crate::{
+    defs::NameClass,
+    helpers::insert_use::ImportGroup,
+    imports_locator::{self, ImportNamespace, ImportSearchOptions, ItemCategory},
+    RootDatabase,
+};
+
+/// Creates an analysis from a multi-file fixture and returns the position marked with `$0`.
+fn position(ra_fixture: &str) -> (RootDatabase, FilePosition) {
+    let change_fixture = ChangeFixture::parse(ra_fixture);
+    let mut database = RootDatabase::default();
+    database.apply_change(change_fixture.change);
+    let (file_id, range_or_offset) = change_fixture.file_position.expect("expected a marker ($0)");
+    let offset = match range_or_offset {
+        RangeOrOffset::Range(_) => panic!(),
+        RangeOrOffset::Offset(it) => it,
+    };
+    (database, FilePosition { file_id, offset })
+}
+
+/// Creates an analysis from a multi-file fixture and returns the module of its first file,
+/// the one import candidates are searched for.
+fn module(ra_fixture: &str) -> (RootDatabase, Module) {
+    let change_fixture = ChangeFixture::parse(ra_fixture);
+    let mut database = RootDatabase::default();
+    database.apply_change(change_fixture.change);
+    let file_id = change_fixture.files[0];
+    let module = Semantics::new(&database).to_module_def(file_id).unwrap();
+    (database, module)
+}
+
+fn render_candidates(
+    db: &RootDatabase,
+    module: Module,
+    candidates: impl Iterator<Item = Either<ModuleDef, hir::MacroDef>>,
+) -> String {
+    let mut paths = candidates
+        .filter_map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(db, item)
+        })
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.join("\n")
+}
+
+fn check_similar(ra_fixture: &str, query: &str, expect: Expect) {
+    let (db, module) = module(ra_fixture);
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_similar_imports(
+        &sema,
+        module.krate(),
+        query.to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    );
+    expect.assert_eq(&render_candidates(&db, module, candidates));
+}
+
+#[test]
+fn finds_items_by_doc_alias() {
+    check_similar(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub mod collections {
+    #[doc(alias = "array")]
+    pub struct Vec;
+    pub struct VecDeque;
+}
+"#,
+        "array",
+        expect![[r#"dep::collections::Vec"#]],
+    );
+}
+
+#[test]
+fn finds_local_items_by_doc_alias() {
+    check_similar(
+        r#"
+//- /main.rs crate:main
+pub mod table {
+    #[doc(alias("dict", "map"))]
+    pub struct HashTable;
+}
+"#,
+        "dict",
+        expect![[r#"table::HashTable"#]],
+    );
+}
+
+#[test]
+fn filters_candidates_by_namespace() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub mod shape_utils {}
+pub struct ShapeRecord { pub sides: u32 }
+pub struct ShapeTuple(pub u32);
+pub struct ShapeUnit;
+pub enum ShapeKind { ShapeVariant }
+pub use ShapeKind::ShapeVariant;
+pub trait ShapeTrait {}
+pub type ShapeAlias = u32;
+pub fn shape_fn() {}
+pub const SHAPE_CONST: u32 = 0;
+pub static SHAPE_STATIC: u32 = 0;
+#[macro_export]
+macro_rules! shape_macro { () => {} }
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let find = |namespace| {
+        let candidates = imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            "shape".to_string(),
+            &ImportSearchOptions {
+                ignore_assoc_items: true,
+                namespace,
+                ..ImportSearchOptions::default()
+            },
+        );
+        render_candidates(&db, module, candidates)
+    };
+
+    expect![[r#"
+        dep::ShapeAlias
+        dep::ShapeKind
+        dep::ShapeRecord
+        dep::ShapeTrait
+        dep::ShapeTuple
+        dep::ShapeUnit
+        dep::shape_utils"#]]
+    .assert_eq(&find(ImportNamespace::Types));
+    expect![[r#"
+        dep::SHAPE_CONST
+        dep::SHAPE_STATIC
+        dep::ShapeKind::ShapeVariant
+        dep::ShapeTuple
+        dep::ShapeUnit
+        dep::shape_fn
+        dep::shape_utils"#]]
+    .assert_eq(&find(ImportNamespace::Values));
+}
+
+#[test]
+fn finds_only_macros_for_macro_imports() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub mod local {
+    #[macro_export]
+    macro_rules! print_shape { () => {} }
+    pub fn print_shape() {}
+}
+//- /dep.rs crate:dep
+pub mod fmt {
+    pub use crate::print_line;
+    pub struct PrintLine;
+}
+#[macro_export]
+macro_rules! print_line { () => {} }
+pub fn print_line() {}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let find = |name: &str, fuzzy| {
+        let candidates =
+            imports_locator::find_macro_imports(&sema, module.krate(), name.to_string(), fuzzy);
+        render_candidates(&db, module, candidates)
+    };
+
+    expect![[r#"print_shape"#]].assert_eq(&find("print_shape", false));
+    expect![[r#"dep::print_line"#]].assert_eq(&find("print_line", false));
+    expect![[r#""#]].assert_eq(&find("PrintLine", false));
+    expect![[r#"
+        dep::print_line
+        print_shape"#]]
+    .assert_eq(&find("prnt", true));
+}
+
+#[test]
+fn leaves_out_hidden_items_and_puts_deprecated_ones_last() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+#[deprecated]
+pub struct ParserOld;
+pub struct Parser;
+#[doc(hidden)]
+pub struct ParserInternals;
+#[doc(hidden)]
+pub mod __private {
+    pub struct ParserState;
+    pub struct ParserCache;
+}
+pub use __private::ParserState;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let find = |include_hidden| {
+        imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            "parser".to_string(),
+            &ImportSearchOptions {
+                ignore_assoc_items: true,
+                include_hidden,
+                ..ImportSearchOptions::default()
+            },
+        )
+        .filter_map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(&db, item)
+        })
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>()
+    };
+
+    let candidates = find(false);
+    assert_eq!(candidates.last().map(String::as_str), Some("dep::ParserOld"));
+    let mut candidates = candidates;
+    candidates.sort();
+    expect![[r#"
+        dep::Parser
+        dep::ParserOld
+        dep::ParserState"#]]
+    .assert_eq(&candidates.join("\n"));
+
+    let mut candidates = find(true);
+    candidates.sort();
+    expect![[r#"
+        dep::Parser
+        dep::ParserInternals
+        dep::ParserOld
+        dep::ParserState
+        dep::__private::ParserCache"#]]
+    .assert_eq(&candidates.join("\n"));
+}
+
+#[test]
+fn flags_functions_usable_as_fn_pointers() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main
+pub struct Counter;
+impl Counter {
+    pub fn count_new() -> Counter { Counter }
+    pub fn count_up(&mut self) {}
+}
+pub fn count_all() {}
+pub struct CountError;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let mut actual = imports_locator::find_similar_imports_flagging_fn_pointers(
+        &sema,
+        module.krate(),
+        "count".to_string(),
+        &ImportSearchOptions::default(),
+    )
+    .into_iter()
+    .filter_map(|(is_fn_pointer, candidate)| {
+        let name = candidate.left()?.name(&db)?;
+        Some(format!("{} {}", name, is_fn_pointer))
+    })
+    .collect::<Vec<_>>();
+    actual.sort();
+    expect![[r#"
+        CountError false
+        Counter false
+        count_all true
+        count_new true
+        count_up false"#]]
+    .assert_eq(&actual.join("\n"));
+}
+
+#[test]
+fn finds_traits_providing_a_method() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub trait LocalBytes { fn bytes(&self) {} }
+mod outer {
+    mod inner {
+        pub trait UnreachableBytes { fn bytes(&self) {} }
+    }
+}
+//- /dep.rs crate:dep
+pub mod io {
+    pub trait Read { fn bytes(self) -> u32; }
+    pub trait Constructor { fn bytes() -> u32; }
+    pub struct Buffer;
+    impl Buffer { pub fn bytes(&self) {} }
+}
+pub mod prelude { pub use crate::io::Read; }
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let actual =
+        imports_locator::find_traits_for_method(&sema, module.krate(), "bytes").into_iter();
+    expect![[r#"
+        LocalBytes
+        dep::io::Read"#]]
+    .assert_eq(&render_candidates(&db, module, actual));
+}
+
+#[test]
+fn finds_exact_name_of_requested_category() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub mod local {
+    pub struct Foo;
+}
+//- /dep.rs crate:dep
+pub trait Foo {}
+pub trait FooBar {}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let find = |category| {
+        let candidates =
+            imports_locator::find_exact_typed(&sema, module.krate(), "Foo".to_string(), category);
+        render_candidates(&db, module, candidates)
+    };
+    expect![[r#"dep::Foo"#]].assert_eq(&find(ItemCategory::Trait));
+    expect![[r#"local::Foo"#]].assert_eq(&find(ItemCategory::Adt));
+    expect![[r#""#]].assert_eq(&find(ItemCategory::Function));
+}
+
+#[test]
+fn excludes_the_item_being_defined() {
+    let (db, position) = position(
+        r#"
+//- /main.rs crate:main deps:dep
+struct Foo$0;
+//- /dep.rs crate:dep
+pub struct Foo;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let file = sema.parse(position.file_id);
+    let name: ast::Name =
+        sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+    let definition = NameClass::classify(&sema, &name).unwrap().referenced_or_defined(&db);
+    let module = sema.scope(name.syntax()).module().unwrap();
+    let find = |exclude| {
+        let candidates =
+            imports_locator::find_exact_imports(&sema, module.krate(), "Foo".to_string(), exclude);
+        render_candidates(&db, module, candidates)
+    };
+
+    expect![[r#"
+        Foo
+        dep::Foo"#]]
+    .assert_eq(&find(None));
+    expect![[r#"dep::Foo"#]].assert_eq(&find(Some(definition)));
+}
+
+#[test]
+fn tags_ranked_candidates_with_match_tiers() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct Map;
+//- /dep.rs crate:dep
+pub struct MapEntry;
+pub struct HashMap;
+pub struct mapper;
+pub struct Unrelated;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let actual = imports_locator::find_ranked_imports(
+        &sema,
+        module.krate(),
+        "Map",
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    )
+    .into_iter()
+    .map(|(tier, candidate)| {
+        let item: ItemInNs = candidate.either(Into::into, Into::into);
+        format!("{:?} {}", tier, module.find_use_path(&db, item).unwrap())
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+    expect![[r#"
+        Exact Map
+        Prefix dep::MapEntry
+        Prefix dep::mapper
+        Fuzzy dep::HashMap"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn sorts_candidates_by_relevance() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct Strand;
+//- /std.rs crate:std
+pub mod io { pub struct Stream; }
+pub mod text { pub struct String; }
+pub mod collections { pub mod hash_map { pub struct Strategy; } }
+pub mod prelude { pub use crate::text::String; }
+pub struct Str;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_relevant_imports(
+        &sema,
+        module,
+        "Str",
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    );
+    let actual = candidates
+        .into_iter()
+        .map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(&db, item).unwrap().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        std::Str
+        Strand
+        std::prelude::String
+        std::io::Stream
+        std::collections::hash_map::Strategy"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn matches_full_paths() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub mod sync {
+    pub struct Local;
+}
+//- /std.rs crate:std
+pub mod sync {
+    pub struct Arc;
+    pub struct Mutex;
+}
+pub mod cell {
+    pub struct Cell;
+}
+pub struct Synchronizer;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let search = |match_full_path| {
+        imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            "sync".to_string(),
+            &ImportSearchOptions {
+                ignore_assoc_items: true,
+                match_full_path,
+                ..ImportSearchOptions::default()
+            },
+        )
+        .filter_map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(&db, item)
+        })
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>()
+    };
+
+    let sorted = |paths: &[String]| {
+        let mut paths = paths.to_vec();
+        paths.sort();
+        paths.join("\n")
+    };
+
+    expect![[r#"
+        std::Synchronizer
+        std::sync
+        sync"#]]
+    .assert_eq(&sorted(&search(false)));
+
+    // Matches of the names come first.
+    let paths = search(true);
+    let (name_matches, path_matches) = paths.split_at(3);
+    expect![[r#"
+        std::Synchronizer
+        std::sync
+        sync"#]]
+    .assert_eq(&sorted(name_matches));
+    expect![[r#"
+        std::sync::Arc
+        std::sync::Mutex
+        sync::Local"#]]
+    .assert_eq(&sorted(path_matches));
+}
+
+#[test]
+fn counts_generic_params_of_candidates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+//- /std.rs crate:std
+pub mod collections {
+    pub struct RandomState;
+    pub struct HashMap<'a, K, V, S = RandomState>(&'a (K, V, S));
+}
+pub mod vec {
+    pub struct Vec<T>(T);
+}
+pub mod array {
+    pub struct Array<T, const N: usize>([T; N]);
+}
+pub mod iter {
+    pub trait Extend<A> {}
+    pub fn once(_: impl Extend<u8>) {}
+}
+pub mod option {
+    pub enum Option<T> { Some(T), None }
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let actual = ["HashMap", "Vec", "Array", "Extend", "once", "Option", "RandomState"]
+        .iter()
+        .flat_map(|name| {
+            imports_locator::find_exact_imports(&sema, module.krate(), name.to_string(), None)
+        })
+        .map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            let path = module.find_use_path(&db, item).unwrap();
+            format!("{} {}", path, imports_locator::generic_param_count(&db, &candidate))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        std::collections::HashMap 3
+        std::vec::Vec 1
+        std::array::Array 2
+        std::iter::Extend 1
+        std::iter::once 0
+        std::option::Option 1
+        std::collections::RandomState 0"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn expands_queries_with_synonyms() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct DictEntry;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+    pub struct BTreeMap;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let mut synonyms = FxHashMap::default();
+    synonyms.insert("dict".to_string(), vec!["HashMap".to_string()]);
+    let candidates = imports_locator::find_similar_imports_with_synonyms(
+        &sema,
+        module.krate(),
+        "dict".to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        &synonyms,
+    );
+    expect![[r#"
+        DictEntry
+        std::collections::HashMap"#]]
+    .assert_eq(&render_candidates(&db, module, candidates.into_iter()));
+}
+
+#[test]
+fn finds_typo_corrections() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct HasMapper;
+pub struct Vek;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+    pub struct HashSet;
+}
+pub mod vec {
+    pub struct Vec;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let corrections = |name| {
+        let candidates = imports_locator::find_typo_corrections(&sema, module.krate(), name);
+        render_candidates(&db, module, candidates.into_iter())
+    };
+    expect![[r#"std::collections::HashMap"#]].assert_eq(&corrections("HasMap"));
+    expect![[r#"
+        Vek
+        std::vec
+        std::vec::Vec"#]]
+    .assert_eq(&corrections("Vex"));
+}
+
+#[test]
+fn typo_corrections_look_at_every_item_of_a_dependency() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+//- /std.rs crate:std
+pub struct Arc;
+pub struct BTreeMap;
+pub struct BTreeSet;
+pub struct BinaryHeap;
+pub struct Box;
+pub struct Cell;
+pub struct Cow;
+pub struct Duration;
+pub struct Entry;
+pub struct Error;
+pub struct File;
+pub struct HashMap;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_typo_corrections(&sema, module.krate(), "HasMap");
+    expect![[r#"std::HashMap"#]].assert_eq(&render_candidates(&db, module, candidates.into_iter()));
+}
+
+#[test]
+fn finds_only_reexportable_items() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub mod shapes {
+    pub struct Circle;
+    pub(crate) struct CircleCache;
+    struct CirclePrivate;
+}
+mod hidden {
+    pub struct CircleHidden;
+}
+//- /dep.rs crate:dep
+pub struct CircleExt;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_reexportable_imports(
+        &sema,
+        module.krate(),
+        "circle".to_string(),
+        &ImportSearchOptions::default(),
+    );
+    expect![[r#"
+        dep::CircleExt
+        shapes::Circle"#]]
+    .assert_eq(&render_candidates(&db, module, candidates.into_iter()));
+}
+
+#[test]
+fn finds_only_items_with_fully_public_paths() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub mod api {
+    pub struct Handle;
+    pub(crate) mod internal {
+        pub struct HandleInner;
+    }
+}
+pub(crate) mod detail {
+    pub struct HandleDetail;
+}
+//- /dep.rs crate:dep
+pub struct HandleExt;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_fully_public_imports(
+        &sema,
+        module.krate(),
+        "handle".to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    );
+    expect![[r#"
+        api::Handle
+        dep::HandleExt"#]]
+    .assert_eq(&render_candidates(&db, module, candidates.into_iter()));
+}
+
+#[test]
+fn refining_a_query_matches_a_fresh_search() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct HashLocal;
+pub struct HasBrown;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+    pub struct HashSet;
+    #[doc(alias = "hashtable")]
+    pub struct Table;
+}
+pub mod marker {
+    pub struct PhantomHas;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let krate = module.krate();
+    let options = ImportSearchOptions::default();
+    let search = |query: &str, options: &ImportSearchOptions| {
+        imports_locator::find_similar_imports(&sema, krate, query.to_string(), options)
+            .collect::<Vec<_>>()
+    };
+    let refine = |previous_query: &str, query: &str, options: &ImportSearchOptions| {
+        let previous = search(previous_query, options);
+        let refined = imports_locator::refine_similar_imports(
+            &sema,
+            krate,
+            previous_query,
+            &previous,
+            query.to_string(),
+            options,
+        );
+        render_candidates(&db, module, refined.into_iter())
+    };
+
+    let fresh = render_candidates(&db, module, search("hash", &options).into_iter());
+    assert_eq!(refine("has", "hash", &options), fresh);
+    expect![[r#"
+        HashLocal
+        std::collections::HashMap
+        std::collections::HashSet
+        std::collections::Table"#]]
+    .assert_eq(&fresh);
+
+    // Not an extension of the previous query, so this searches from scratch.
+    assert_eq!(
+        refine("has", "table", &options),
+        render_candidates(&db, module, search("table", &options).into_iter()),
+    );
+
+    // Only two candidates of `std` are kept for `h`, so `PhantomHas` is only found searching
+    // from scratch.
+    let capped = ImportSearchOptions { max_per_crate: Some(2), ..ImportSearchOptions::default() };
+    let fresh = render_candidates(&db, module, search("ht", &capped).into_iter());
+    assert_eq!(refine("h", "ht", &capped), fresh);
+    expect![[r#"
+        std::collections::HashSet
+        std::marker::PhantomHas"#]]
+    .assert_eq(&fresh);
+
+    // The limit applies before the hidden items are left out, so the search for `has` is cut
+    // short even though it returns less than two candidates.
+    let (db, module) = self::module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+#[doc(hidden)]
+pub struct Hasa;
+#[doc(hidden)]
+pub struct Hasb;
+pub struct Hashc;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let limited = ImportSearchOptions { limit: Some(2), ..ImportSearchOptions::default() };
+    let previous =
+        imports_locator::find_similar_imports(&sema, module.krate(), "has".to_string(), &limited)
+            .collect::<Vec<_>>();
+    let refined = imports_locator::refine_similar_imports(
+        &sema,
+        module.krate(),
+        "has",
+        &previous,
+        "hash".to_string(),
+        &limited,
+    );
+    assert_eq!(render_candidates(&db, module, refined.into_iter()), "dep::Hashc");
+}
+
+#[test]
+fn prefers_candidates_present_in_the_file() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub mod a {
+    pub struct Widget;
+}
+pub mod b {
+    pub struct Widget;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let search = |present: &[&str]| {
+        let present = present.iter().map(|it| it.to_string()).collect();
+        imports_locator::find_similar_imports_preferring(
+            &sema,
+            module.krate(),
+            "Widget".to_string(),
+            &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+            &present,
+        )
+        .into_iter()
+        .map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(&db, item).unwrap().to_string()
+        })
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(search(&["dep::a::Widget"])[0], "dep::a::Widget");
+    assert_eq!(search(&["dep::b::Widget"])[0], "dep::b::Widget");
+    assert_eq!(search(&["b::Widget", "HashMap"])[0], "dep::b::Widget");
+}
+
+#[test]
+fn reports_stable_since_versions() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct Local;
+//- /std.rs crate:std
+pub mod task {
+    #[stable(feature = "futures_api", since = "1.36.0")]
+    pub struct Poll;
+    #[unstable(feature = "poll_next", issue = "none")]
+    pub struct PollNext;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let actual = ["Poll", "PollNext", "Local"]
+        .iter()
+        .flat_map(|name| {
+            imports_locator::find_exact_imports(&sema, module.krate(), name.to_string(), None)
+        })
+        .map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            let path = module.find_use_path(&db, item).unwrap();
+            format!("{} {:?}", path, imports_locator::stable_since(&db, &candidate))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        std::task::Poll Some("1.36.0")
+        std::task::PollNext None
+        Local None"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn reports_first_private_module_on_the_path() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main
+mod other {
+    pub fn f() {}
+}
+pub mod outer {
+    mod inner {
+        pub mod deep {
+            pub struct Hidden;
+        }
+    }
+    pub struct Visible;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let other = module.children(&db).find(|it| it.name(&db).unwrap().to_string() == "other");
+    let other = other.unwrap();
+    let private_module = |name: &str| {
+        let candidate =
+            imports_locator::find_exact_imports(&sema, module.krate(), name.to_string(), None)
+                .next()
+                .unwrap();
+        imports_locator::first_private_module(&db, other, &candidate)
+            .map(|it| it.name(&db).unwrap().to_string())
+    };
+    assert_eq!(private_module("Hidden"), Some("inner".to_string()));
+    assert_eq!(private_module("Visible"), None);
+}
+
+#[test]
+fn excludes_blanket_impls_the_receiver_does_not_satisfy() {
+    let (db, position) = position(
+        r#"
+//- /main.rs crate:main deps:dep
+use dep::Vec;
+fn main() {
+    let vec = Vec;
+    vec.describ$0
+}
+//- /dep.rs crate:dep
+pub struct Vec;
+impl Printable for Vec {}
+
+pub trait Printable {}
+pub trait Hashable {}
+
+pub trait Describe {
+    fn describe(&self);
+}
+impl<T: Printable> Describe for T {
+    fn describe(&self) {}
+}
+
+pub trait DescribeHash {
+    fn describe_hash(&self);
+}
+impl<T: Hashable> DescribeHash for T {
+    fn describe_hash(&self) {}
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let file = sema.parse(position.file_id);
+    let field_expr: ast::FieldExpr =
+        sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+    let receiver_ty = sema.type_of_expr(&field_expr.expr().unwrap()).unwrap();
+    let current_module = sema.scope(field_expr.syntax()).module().unwrap();
+
+    let actual = imports_locator::find_method_completions_requiring_import(
+        &sema,
+        current_module,
+        &receiver_ty,
+        "describ",
+    )
+    .into_iter()
+    .map(|(trait_, method)| format!("{}::{}", trait_.name(&db), method))
+    .collect::<Vec<_>>()
+    .join("\n");
+    expect![[r#"Describe::describe"#]].assert_eq(&actual);
+}
+
+#[test]
+fn prefers_traits_used_in_the_file_for_method_completion() {
+    let (db, position) = position(
+        r#"
+//- /main.rs crate:main deps:dep
+use dep::Vec;
+mod other {
+    fn f(vec: dep::Vec) {
+        dep::b::VecExt::iter_other(&vec);
+    }
+}
+fn main() {
+    let vec = Vec;
+    vec.iter_mut_s$0
+}
+//- /dep.rs crate:dep
+pub struct Vec;
+pub mod a {
+    pub trait VecExt {
+        fn iter_mut_something(&self);
+    }
+    impl VecExt for super::Vec {
+        fn iter_mut_something(&self) {}
+    }
+}
+pub mod b {
+    pub trait VecExt {
+        fn iter_mut_something(&self);
+        fn iter_other(&self);
+    }
+    impl VecExt for super::Vec {
+        fn iter_mut_something(&self) {}
+        fn iter_other(&self) {}
+    }
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let file = sema.parse(position.file_id);
+    let field_expr: ast::FieldExpr =
+        sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+    let receiver_ty = sema.type_of_expr(&field_expr.expr().unwrap()).unwrap();
+    let current_module = sema.scope(field_expr.syntax()).module().unwrap();
+
+    let actual = imports_locator::find_method_completions_requiring_import(
+        &sema,
+        current_module,
+        &receiver_ty,
+        "iter_mut_s",
+    )
+    .into_iter()
+    .map(|(trait_, method)| {
+        let path = current_module.find_use_path(&db, hir::ModuleDef::Trait(trait_)).unwrap();
+        format!("{}::{}", path, method)
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+    expect![[r#"
+        dep::b::VecExt::iter_mut_something
+        dep::a::VecExt::iter_mut_something"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn checks_that_a_name_is_importable() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+struct Local;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let krate = module.krate();
+    assert!(imports_locator::is_importable(&sema, krate, "HashMap"));
+    assert!(imports_locator::is_importable(&sema, krate, "Local"));
+    assert!(!imports_locator::is_importable(&sema, krate, "HashMa"));
+    assert!(!imports_locator::is_importable(&sema, krate, "NoSuchThing"));
+}
+
+#[test]
+fn finds_traits_to_import_for_method_completion() {
+    let (db, position) = position(
+        r#"
+//- /main.rs crate:main deps:dep
+use dep::Vec;
+fn main() {
+    let vec = Vec;
+    vec.iter_mut_s$0
+}
+//- /dep.rs crate:dep
+pub struct Vec;
+pub struct Other;
+
+pub trait VecExt {
+    fn iter_mut_something(&self);
+}
+impl VecExt for Vec {
+    fn iter_mut_something(&self) {}
+}
+
+pub trait OtherExt {
+    fn iter_mut_something_else(&self);
+}
+impl OtherExt for Other {
+    fn iter_mut_something_else(&self) {}
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let file = sema.parse(position.file_id);
+    let field_expr: ast::FieldExpr =
+        sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+    let receiver_ty = sema.type_of_expr(&field_expr.expr().unwrap()).unwrap();
+    let current_module = sema.scope(field_expr.syntax()).module().unwrap();
+
+    let actual = imports_locator::find_method_completions_requiring_import(
+        &sema,
+        current_module,
+        &receiver_ty,
+        "iter_mut_s",
+    )
+    .into_iter()
+    .map(|(trait_, method)| format!("{}::{}", trait_.name(&db), method))
+    .collect::<Vec<_>>()
+    .join("\n");
+    expect![[r#"VecExt::iter_mut_something"#]].assert_eq(&actual);
+}
+
+#[test]
+fn stops_searching_at_deadline() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct FmtLocal;
+//- /dep.rs crate:dep
+pub struct Fmt;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let search = |deadline| {
+        let candidates = imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            "fmt".to_string(),
+            &ImportSearchOptions {
+                ignore_assoc_items: true,
+                deadline: Some(deadline),
+                ..ImportSearchOptions::default()
+            },
+        );
+        render_candidates(&db, module, candidates)
+    };
+
+    let generous = Instant::now() + Duration::from_secs(60);
+    expect![[r#"
+        FmtLocal
+        dep::Fmt"#]]
+    .assert_eq(&search(generous));
+    expect![[r#""#]].assert_eq(&search(Instant::now()));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_import_records() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct Map;
+//- /dep.rs crate:dep
+pub mod collections {
+    pub struct HashMap;
+}
+pub fn map() {}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let records = imports_locator::find_import_records(
+        &sema,
+        module,
+        "Map",
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        false,
+    );
+    let actual = serde_json::to_string_pretty(&records).unwrap();
+    expect![[r#"
+        [
+          {
+            "name": "Map",
+            "path": "Map",
+            "kind": "adt",
+            "crate": "main",
+            "score": 2,
+            "requires_generic_args": false
+          },
+          {
+            "name": "map",
+            "path": "dep::map",
+            "kind": "function",
+            "crate": "dep",
+            "score": 1,
+            "requires_generic_args": false
+          },
+          {
+            "name": "HashMap",
+            "path": "dep::collections::HashMap",
+            "kind": "adt",
+            "crate": "dep",
+            "score": 0,
+            "requires_generic_args": false
+          }
+        ]"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn post_processes_import_records() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+//- /std.rs crate:std
+pub mod fmt {
+    pub struct Formatter;
+    pub struct FormatterInternal;
+}
+pub struct FormatSpec;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let records = imports_locator::find_import_records_with(
+        &sema,
+        module,
+        "format",
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        false,
+        |record| {
+            if record.name.ends_with("Internal") {
+                return None;
+            }
+            let path = record.path.replacen("std::", "facade::", 1);
+            Some(imports_locator::ImportRecord { path, ..record })
+        },
+    );
+    let actual = records.into_iter().map(|record| record.path).collect::<Vec<_>>().join("\n");
+    expect![[r#"
+        facade::FormatSpec
+        facade::fmt::Formatter"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn flags_type_constructors_requiring_generic_args() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:alloc
+//- /alloc.rs crate:alloc
+pub struct Global;
+pub struct Vec<T, A = Global>(T, A);
+pub struct String;
+pub type VecOfStrings = Vec<String>;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let mut actual = ["Vec", "String"]
+        .iter()
+        .flat_map(|query| {
+            imports_locator::find_import_records(
+                &sema,
+                module,
+                query,
+                &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+                false,
+            )
+        })
+        .map(|record| format!("{} {}", record.path, record.requires_generic_args))
+        .collect::<Vec<_>>();
+    actual.sort();
+    actual.dedup();
+    expect![[r#"
+        alloc::String false
+        alloc::Vec true
+        alloc::VecOfStrings false"#]]
+    .assert_eq(&actual.join("\n"));
+}
+
+#[test]
+fn suggests_replacements_of_deprecated_candidates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub mod old {
+    #[deprecated(since = "1.2.0", note = "use `fmt::Formatter` instead")]
+    pub struct LegacyFormatter;
+}
+pub mod fmt {
+    pub struct Formatter;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_similar_imports_with_replacements(
+        &sema,
+        module.krate(),
+        "LegacyFormatter".to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    );
+    let paths = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            module.find_use_path(&db, item)
+        })
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>();
+    expect![[r#"
+        dep::fmt::Formatter
+        dep::old::LegacyFormatter"#]]
+    .assert_eq(&paths.join("\n"));
+}
+
+#[test]
+fn flags_candidates_available_through_a_prelude() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub mod io {
+    pub trait Read {}
+    pub trait ReadExt {}
+}
+pub mod prelude {
+    pub use crate::io::Read;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let (in_prelude, others) = imports_locator::find_similar_imports_by_prelude(
+        &sema,
+        module.krate(),
+        "Read".to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    );
+    let in_prelude = render_candidates(&db, module, in_prelude.into_iter());
+    expect![[r#"dep::io::Read"#]].assert_eq(&in_prelude);
+    let others = render_candidates(&db, module, others.into_iter());
+    expect![[r#"dep::io::ReadExt"#]].assert_eq(&others);
+}
+
+#[test]
+fn describes_kind_and_container_of_candidates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct Vec;
+impl Vec {
+    pub fn swap_remove(&mut self) {}
+}
+//- /std.rs crate:std
+pub mod mem {
+    pub fn swap() {}
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let describe = |query: &str| {
+        imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            query.to_string(),
+            &ImportSearchOptions { ..ImportSearchOptions::default() },
+        )
+        .filter_map(|candidate| imports_locator::kind_and_container(&db, &candidate))
+        .collect::<Vec<_>>()
+        .join("\n")
+    };
+    expect![[r#"
+        method in Vec
+        fn in std::mem"#]]
+    .assert_eq(&describe("swa"));
+}
+
+#[test]
+fn renders_signatures_of_function_candidates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+pub struct Connection;
+pub fn connect(host: &str, port: u16) -> Connection { Connection }
+pub fn connect_default() {}
+pub struct ConnectOptions;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let actual = imports_locator::find_import_records(
+        &sema,
+        module,
+        "connect",
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        true,
+    )
+    .into_iter()
+    .map(|record| format!("{} {:?}", record.path, record.signature))
+    .collect::<Vec<_>>()
+    .join("\n");
+    expect![[r#"
+        dep::connect Some("fn connect(host: &str, port: u16) -> Connection")
+        dep::ConnectOptions None
+        dep::Connection None
+        dep::connect_default Some("fn connect_default()")"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn completes_the_first_unresolved_path_segment() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+mod local {}
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+}
+pub mod collect_utils {}
+pub mod cell {}
+mod collections_impl {}
+"#,
+    );
+    let complete = |partial_path| {
+        imports_locator::complete_path_segment(&db, module, partial_path)
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    expect![[r#"collect_utils, collections"#]].assert_eq(&complete("std::collec"));
+    expect![[r#"HashMap"#]].assert_eq(&complete("std::collections::Ha"));
+    expect![[r#"collect_utils, collections"#]].assert_eq(&complete("std::collec::Ha"));
+    expect![[r#"std"#]].assert_eq(&complete("s"));
+}
+
+#[test]
+fn finds_functions_by_signature() {
+    let (db, position) = position(
+        r#"
+//- /main.rs crate:main deps:std
+fn main(name: &str, owned: std::string::String) {
+    $0(name, owned);
+}
+fn shout(name: &str) -> std::string::String { loop {} }
+//- /std.rs crate:std
+pub mod string {
+    pub struct String;
+    pub fn from_str(s: &str) -> String { String }
+    pub fn from_string(s: &String) -> String { String }
+    pub fn len(s: &str) -> usize { 0 }
+    pub fn concat(a: &str, b: &str) -> String { String }
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let file = sema.parse(position.file_id);
+    let tuple_expr: ast::TupleExpr =
+        sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+    let types = sema.type_of_expr(&tuple_expr.into()).unwrap().tuple_fields(&db);
+    let krate = sema.to_module_def(position.file_id).unwrap().krate();
+
+    let actual = imports_locator::find_by_signature(&sema, krate, &types[..1], types[1].clone())
+        .into_iter()
+        .map(|function| function.name(&db).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        from_str
+        shout"#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn finds_only_items_defined_in_the_given_file() {
+    let change_fixture = ChangeFixture::parse(
+        r#"
+//- /main.rs crate:main deps:dep
+mod shapes;
+pub struct Circle;
+//- /shapes.rs
+pub struct Circle;
+pub struct Square;
+//- /dep.rs crate:dep
+pub struct Circle;
+"#,
+    );
+    let mut db = RootDatabase::default();
+    db.apply_change(change_fixture.change);
+    let sema = Semantics::new(&db);
+    let module = sema.to_module_def(change_fixture.files[0]).unwrap();
+
+    let candidates = imports_locator::find_similar_imports_in_file(
+        &sema,
+        module.krate(),
+        change_fixture.files[1],
+        "Circle".to_string(),
+    );
+
+    expect![["shapes::Circle"]].assert_eq(&render_candidates(&db, module, candidates));
+}
+
+#[test]
+fn finds_import_insertion_points_of_use_groups() {
+    let (db, position) = position(
+        r#"
+//- /main.rs
+//! Docs.
+
+use std::fmt;
+use std::io;
+
+use dep::Thing;
+use crate::local;
+mod local {}
+use self::local::*;
+$0
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let points = imports_locator::import_insertion_points(&sema, position.file_id);
+
+    let text = sema.parse(position.file_id).syntax().text().to_string();
+    let actual = points
+        .groups
+        .iter()
+        .map(|group| format!("{:?}: {}", group.kind, &text[group.range].replace('\n', " ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        Std: use std::fmt; use std::io;
+        ExternCrate: use dep::Thing;
+        ThisCrate: use crate::local;
+        ThisModule: use self::local::*;"#]]
+    .assert_eq(&actual);
+    assert_eq!(&text[..usize::from(points.first_offset)], "//! Docs.");
+    assert_eq!(points.offset_for(ImportGroup::Std), points.groups[0].range.end());
+    assert_eq!(points.offset_for(ImportGroup::SuperModule), points.groups[3].range.end());
+}
+
+#[test]
+fn finds_candidates_matching_any_query() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct LocalReader;
+//- /dep.rs crate:dep
+pub struct BufReader;
+pub struct BufWriter;
+pub struct LineWriter;
+pub struct Cursor;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let queries = vec!["reader".to_string(), "writer".to_string()];
+    let candidates = imports_locator::find_similar_imports_matching_any(
+        &sema,
+        module.krate(),
+        &queries,
+        &ImportSearchOptions::default(),
+    );
+    let mut candidates = candidates
+        .into_iter()
+        .filter_map(|(query_index, candidate)| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            Some(format!("{} {}", queries[query_index], module.find_use_path(&db, item)?))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort();
+    expect![[r#"
+        reader LocalReader
+        reader dep::BufReader
+        writer dep::BufWriter
+        writer dep::LineWriter"#]]
+    .assert_eq(&candidates.join("\n"));
+}
+
+#[test]
+fn repeated_searches_are_answered_from_the_cache() {
+    let change_fixture = ChangeFixture::parse(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct LocalReader;
+//- /dep.rs crate:dep
+pub struct BufReader;
+"#,
+    );
+    let mut db = RootDatabase::default();
+    db.apply_change(change_fixture.change);
+    let file_id = change_fixture.files[0];
+    let search = |db: &RootDatabase, query: &str| {
+        let sema = Semantics::new(db);
+        let module = sema.to_module_def(file_id).unwrap();
+        let candidates = imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            query.to_string(),
+            &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        );
+        render_candidates(db, module, candidates)
+    };
+    let scans = |db: &RootDatabase| db.import_search_cache.scans.load(Ordering::Relaxed);
+
+    assert_eq!(search(&db, "reader"), "LocalReader\ndep::BufReader");
+    assert_eq!(scans(&db), 1);
+    assert_eq!(search(&db, "Reader"), "LocalReader\ndep::BufReader");
+    assert_eq!(scans(&db), 1);
+    assert_eq!(search(&db, "buf"), "dep::BufReader");
+    assert_eq!(scans(&db), 2);
+
+    db.set_file_text(
+        file_id,
+        Arc::new("pub struct LocalReader;\npub struct FileReader;".to_string()),
+    );
+    assert_eq!(search(&db, "reader"), "FileReader\nLocalReader\ndep::BufReader");
+    assert_eq!(scans(&db), 3);
+
+    // The def map doesn't change with the function bodies, the symbols of the file do.
+    db.set_file_text(
+        file_id,
+        Arc::new(
+            "pub struct LocalReader;\npub struct FileReader;\nfn f() { struct BodyReader; }"
+                .to_string(),
+        ),
+    );
+    assert_eq!(search(&db, "reader"), "FileReader\nLocalReader\ndep::BufReader");
+    assert_eq!(scans(&db), 4);
+}
+
+#[test]
+fn pending_changes_cancel_the_search() {
+    let change_fixture = ChangeFixture::parse(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct LocalReader;
+//- /dep.rs crate:dep
+pub struct BufReader;
+"#,
+    );
+    let mut db = RootDatabase::default();
+    db.apply_change(change_fixture.change);
+    let file_id = change_fixture.files[0];
+    let search = |db: &RootDatabase, query: &str| {
+        let sema = Semantics::new(db);
+        let module = sema.to_module_def(file_id).unwrap();
+        let candidates = imports_locator::find_similar_imports(
+            &sema,
+            module.krate(),
+            query.to_string(),
+            &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+        );
+        render_candidates(db, module, candidates)
+    };
+    // Computes everything the search needs, so that only the search itself is left to cancel.
+    assert_eq!(search(&db, "reader"), "LocalReader\ndep::BufReader");
+
+    let snapshot = db.snapshot();
+    let change = thread::spawn(move || {
+        db.set_file_text(file_id, Arc::new("pub struct FileReader;".to_string()));
+    });
+    while !snapshot.salsa_runtime().is_current_revision_canceled() {
+        thread::yield_now();
+    }
+    assert!(snapshot.catch_canceled(|db| search(db, "buf")).is_err());
+
+    drop(snapshot);
+    change.join().unwrap();
+}
+
+#[test]
+fn leaves_out_excluded_crates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:generated,util
+pub struct LocalWidget;
+//- /generated.rs crate:generated
+pub struct GeneratedWidget;
+pub struct WidgetProto;
+//- /util.rs crate:util
+pub struct Widget;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let krate = module.krate();
+    let generated = krate
+        .dependencies(&db)
+        .into_iter()
+        .find(|dep| dep.name.to_string() == "generated")
+        .unwrap()
+        .krate;
+    let find = |excluded_crates: &[hir::Crate]| {
+        let candidates = imports_locator::find_similar_imports(
+            &sema,
+            krate,
+            "widget".to_string(),
+            &ImportSearchOptions {
+                ignore_assoc_items: true,
+                max_per_crate: Some(1),
+                excluded_crates: excluded_crates.to_vec(),
+                ..ImportSearchOptions::default()
+            },
+        );
+        render_candidates(&db, module, candidates)
+    };
+
+    expect![[r#"
+        LocalWidget
+        generated::WidgetProto
+        util::Widget"#]]
+    .assert_eq(&find(&[]));
+    expect![[r#"
+        LocalWidget
+        util::Widget"#]]
+    .assert_eq(&find(&[generated]));
+    expect![[r#"util::Widget"#]].assert_eq(&find(&[generated, krate]));
+}
+
+#[test]
+fn caps_the_candidates_of_each_dependency_after_leaving_some_out() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:big
+//- /big.rs crate:big
+pub const PARSER_LIMIT: u32 = 0;
+#[doc(hidden)]
+pub struct ParserA;
+pub struct ParserB;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_similar_imports(
+        &sema,
+        module.krate(),
+        "parser".to_string(),
+        &ImportSearchOptions {
+            namespace: ImportNamespace::Types,
+            max_per_crate: Some(2),
+            ..ImportSearchOptions::default()
+        },
+    );
+    expect![[r#"big::ParserB"#]].assert_eq(&render_candidates(&db, module, candidates));
+}
+
+#[test]
+fn caps_the_candidates_of_each_dependency() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:big,small
+pub struct LocalParser;
+pub struct LocalParserConfig;
+pub struct LocalParserError;
+//- /big.rs crate:big
+pub struct MyParsers;
+pub struct ParserError;
+pub struct Parser;
+pub struct ParserConfig;
+//- /small.rs crate:small
+pub struct SmallParser;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_similar_imports(
+        &sema,
+        module.krate(),
+        "parser".to_string(),
+        &ImportSearchOptions {
+            ignore_assoc_items: true,
+            max_per_crate: Some(2),
+            ..ImportSearchOptions::default()
+        },
+    );
+    expect![[r#"
+        LocalParser
+        LocalParserConfig
+        LocalParserError
+        big::Parser
+        big::ParserConfig
+        small::SmallParser"#]]
+    .assert_eq(&render_candidates(&db, module, candidates));
+}
+
+#[test]
+fn pairs_exact_candidates_with_their_import_paths() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+mod local {
+    pub struct Span;
+    pub trait Spanned {
+        fn Span();
+    }
+}
+//- /dep.rs crate:dep
+mod inner {
+    pub struct Span;
+}
+pub use inner::Span;
+pub mod spans {
+    pub use crate::inner::Span;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_exact_imports_with_paths(
+        &sema,
+        module,
+        "Span".to_string(),
+        None,
+        None,
+    )
+    .into_iter()
+    .map(|it| it.import_path.to_string())
+    .collect::<Vec<_>>();
+    expect![[r#"
+        dep::Span
+        local::Span
+        local::Spanned::Span"#]]
+    .assert_eq(&candidates.join("\n"));
+}
+
+#[test]
+fn imports_items_reexported_at_several_depths_with_the_shortest_path() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:serde,facade
+//- /serde.rs crate:serde
+pub mod de {
+    pub trait Deserialize {}
+}
+pub use de::Deserialize;
+//- /facade.rs crate:facade deps:serde
+pub mod api {
+    pub mod de {
+        pub use serde::de::Deserialize;
+    }
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let candidates = imports_locator::find_exact_imports_with_paths(
+        &sema,
+        module,
+        "Deserialize".to_string(),
+        None,
+        None,
+    )
+    .into_iter()
+    .map(|it| it.import_path.to_string())
+    .collect::<Vec<_>>();
+    expect![[r#"serde::Deserialize"#]].assert_eq(&candidates.join("\n"));
+}
+
+#[test]
+fn labels_same_named_candidates_with_disambiguating_paths() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct HashMap;
+mod text {
+    pub struct HashMap;
+    pub struct HashMapExt;
+}
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let mut labels = imports_locator::find_similar_imports_disambiguated(
+        &sema,
+        module,
+        "hashm".to_string(),
+        &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+    )
+    .into_iter()
+    .map(|(_, label)| label.to_string())
+    .collect::<Vec<_>>();
+    labels.sort();
+    expect![[r#"
+        HashMapExt
+        collections::HashMap
+        crate::HashMap
+        text::HashMap"#]]
+    .assert_eq(&labels.join("\n"));
+}
+
+#[test]
+fn groups_feature_gated_candidates_by_feature() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub struct LocalReader;
+//- /dep.rs crate:dep cfg:feature=std
+#[cfg(feature = "std")]
+pub struct StdReader;
+#[cfg(feature = "serde")]
+pub struct SerdeReader;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub struct ReaderVisitor;
+#[cfg(feature = "serde")]
+pub(crate) struct InternalReader;
+#[cfg(feature = "serde")]
+pub struct Writer;
+#[cfg(feature = "tokio")]
+pub struct AsyncReader;
+#[cfg(all(feature = "tokio", feature = "serde"))]
+pub struct AsyncSerdeReader;
+pub mod io {
+    #[cfg(feature = "serde")]
+    pub struct JsonReader;
+}
+"#,
+    );
+    let groups = imports_locator::find_feature_gated_imports(&db, module.krate(), "reader");
+    let rendered = groups
+        .iter()
+        .map(|group| {
+            let krate = group.krate.display_name(&db).unwrap();
+            let paths = group.paths.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+            format!("{} {}: {}", krate, group.feature, paths.join(", "))
+        })
+        .collect::<Vec<_>>();
+    expect![[r#"
+        dep serde: dep::ReaderVisitor, dep::SerdeReader, dep::io::JsonReader
+        dep tokio: dep::AsyncReader"#]]
+    .assert_eq(&rendered.join("\n"));
+}
+
+#[test]
+fn exact_search_ignores_case_only_when_asked_to() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:std
+pub struct LocalHashMap;
+pub struct HashMapBuilder;
+pub struct Hashmap;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+    pub struct HashMapExt;
+}
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let find = |case_insensitive| {
+        let name = "hashmap".to_string();
+        let candidates = if case_insensitive {
+            imports_locator::find_exact_imports_case_insensitive(&sema, module.krate(), name, None)
+                .collect::<Vec<_>>()
+        } else {
+            imports_locator::find_exact_imports(&sema, module.krate(), name, None)
+                .collect::<Vec<_>>()
+        };
+        render_candidates(&db, module, candidates.into_iter())
+    };
+
+    expect![[r#""#]].assert_eq(&find(false));
+    expect![[r#"
+        Hashmap
+        std::collections::HashMap"#]]
+    .assert_eq(&find(true));
+}
+
+#[test]
+fn ranking_weights_reorder_candidates() {
+    let (db, module) = module(
+        r#"
+//- /main.rs crate:main deps:dep
+pub mod a {
+    pub mod b {
+        pub struct Map;
+    }
+}
+//- /dep.rs crate:dep
+pub struct MapExt;
+#[deprecated]
+pub struct MapOld;
+"#,
+    );
+    let sema = Semantics::new(&db);
+    let rank = |weights| {
+        imports_locator::find_weighted_imports(
+            &sema,
+            module,
+            "Map",
+            &ImportSearchOptions { ignore_assoc_items: true, ..ImportSearchOptions::default() },
+            &weights,
+        )
+        .into_iter()
+        .map(|(score, candidate)| {
+            let item: ItemInNs = candidate.either(Into::into, Into::into);
+            format!("{} {}", score, module.find_use_path(&db, item).unwrap())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    };
+
+    expect![[r#"
+        17 a::b::Map
+        6 dep::MapExt
+        -14 dep::MapOld"#]]
+    .assert_eq(&rank(imports_locator::RankingWeights::default()));
+    expect![[r#"
+        -10 dep::MapExt
+        -10 dep::MapOld
+        -15 a::b::Map"#]]
+    .assert_eq(&rank(imports_locator::RankingWeights {
+        fuzzy_score: 0,
+        path_depth_penalty: 5,
+        crate_distance_penalty: 0,
+        deprecation_penalty: 0,
+    }));
+}
@@ -25,7 +25,9 @@ use base_db::{
 use hir::db::{AstDatabase, DefDatabase, HirDatabase};
 use rustc_hash::FxHashSet;
 
-use crate::{line_index::LineIndex, symbol_index::SymbolsDatabase};
+use crate::{
+    imports_locator::ImportSearchCache, line_index::LineIndex, symbol_index::SymbolsDatabase,
+};
 
 /// `base_db` is normally also needed in places where `ide_db` is used, so this re-export is for convenience.
 pub use base_db;
@@ -42,6 +44,9 @@ pub use base_db;
 )]
 pub struct RootDatabase {
     storage: salsa::Storage<RootDatabase>,
+    /// Shared with the snapshots, so that the searches done while typing reuse each other's
+    /// results.
+    pub(crate) import_search_cache: Arc<ImportSearchCache>,
 }
 
 impl fmt::Debug for RootDatabase {
@@ -103,7 +108,10 @@ impl Default for RootDatabase {
 
 impl RootDatabase {
     pub fn new(lru_capacity: Option<usize>) -> RootDatabase {
-        let mut db = RootDatabase { storage: salsa::Storage::default() };
+        let mut db = RootDatabase {
+            storage: salsa::Storage::default(),
+            import_search_cache: Default::default(),
+        };
         db.set_crate_graph_with_durability(Default::default(), Durability::HIGH);
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_library_roots_with_durability(Default::default(), Durability::HIGH);
@@ -121,7 +129,10 @@ impl RootDatabase {
 
 impl salsa::ParallelDatabase for RootDatabase {
     fn snapshot(&self) -> salsa::Snapshot<RootDatabase> {
-        salsa::Snapshot::new(RootDatabase { storage: self.storage.snapshot() })
+        salsa::Snapshot::new(RootDatabase {
+            storage: self.storage.snapshot(),
+            import_search_cache: self.import_search_cache.clone(),
+        })
     }
 }
 
@@ -52,6 +52,8 @@ pub struct Query {
     only_types: bool,
     libs: bool,
     exact: bool,
+    case_insensitive: bool,
+    max_edit_distance: Option<usize>,
     limit: usize,
 }
 
@@ -64,6 +66,8 @@ impl Query {
             only_types: false,
             libs: false,
             exact: false,
+            case_insensitive: false,
+            max_edit_distance: None,
             limit: usize::max_value(),
         }
     }
@@ -80,6 +84,17 @@ impl Query {
         self.exact = true;
     }
 
+    /// Makes the `exact` matching ignore the case of the symbol names.
+    pub fn case_insensitive(&mut self) {
+        self.case_insensitive = true;
+    }
+
+    /// Matches the symbols whose names are at most `max_edit_distance` single letter edits away
+    /// from the query, ignoring the case, instead of the ones containing its letters.
+    pub fn max_edit_distance(&mut self, max_edit_distance: usize) {
+        self.max_edit_distance = Some(max_edit_distance);
+    }
+
     pub fn limit(&mut self, limit: usize) {
         self.limit = limit
     }
@@ -189,15 +204,7 @@ pub fn crate_symbols(db: &RootDatabase, krate: CrateId, query: Query) -> Vec<Fil
     // FIXME(#4842): This now depends on CrateDefMap, why not build the entire symbol index from
     // that instead?
 
-    let def_map = db.crate_def_map(krate);
-    let mut files = Vec::new();
-    let mut modules = vec![def_map.root];
-    while let Some(module) = modules.pop() {
-        let data = &def_map[module];
-        files.extend(data.origin.file_id());
-        modules.extend(data.children.values());
-    }
-
+    let files = crate_files(db, krate);
     let snap = Snap(db.snapshot());
 
     let buf = files
@@ -209,6 +216,19 @@ pub fn crate_symbols(db: &RootDatabase, krate: CrateId, query: Query) -> Vec<Fil
     query.search(&buf)
 }
 
+/// The files of the modules of `krate`.
+pub(crate) fn crate_files(db: &RootDatabase, krate: CrateId) -> Vec<FileId> {
+    let def_map = db.crate_def_map(krate);
+    let mut files = Vec::new();
+    let mut modules = vec![def_map.root];
+    while let Some(module) = modules.pop() {
+        let data = &def_map[module];
+        files.extend(data.origin.file_id());
+        modules.extend(data.children.values());
+    }
+    files
+}
+
 pub fn index_resolve(db: &RootDatabase, name: &SmolStr) -> Vec<FileSymbol> {
     let mut query = Query::new(name.to_string());
     query.exact();
@@ -313,11 +333,27 @@ impl Query {
         let mut op = fst::map::OpBuilder::new();
         for file_symbols in indices.iter() {
             let automaton = fst::automaton::Subsequence::new(&self.lowercased);
-            op = op.add(file_symbols.map.search(automaton))
+            op = match self.max_edit_distance {
+                Some(_) => op.add(file_symbols.map.stream()),
+                None => op.add(file_symbols.map.search(automaton)),
+            };
         }
         let mut stream = op.union();
         let mut res = Vec::new();
-        while let Some((_, indexed_values)) = stream.next() {
+        while let Some((key, indexed_values)) = stream.next() {
+            if let Some(max_edit_distance) = self.max_edit_distance {
+                // The keys are the lowercased names.
+                let name = String::from_utf8_lossy(key);
+                // Each edit changes the length by one at most, so skip computing the distance for
+                // the names whose length is out of reach.
+                let (name_len, query_len) = (name.chars().count(), self.lowercased.chars().count());
+                let length_difference = name_len.max(query_len) - name_len.min(query_len);
+                if length_difference > max_edit_distance
+                    || stdx::edit_distance(&name, &self.lowercased) > max_edit_distance
+                {
+                    continue;
+                }
+            }
             for indexed_value in indexed_values {
                 let symbol_index = &indices[indexed_value.index];
                 let (start, end) = SymbolIndex::map_value_to_range(indexed_value.value);
@@ -326,8 +362,14 @@ impl Query {
                     if self.only_types && !symbol.kind.is_type() {
                         continue;
                     }
-                    if self.exact && symbol.name != self.query {
-                        continue;
+                    if self.exact {
+                        let matches = match self.case_insensitive {
+                            true => symbol.name.to_lowercase() == self.lowercased,
+                            false => symbol.name == self.query,
+                        };
+                        if !matches {
+                            continue;
+                        }
                     }
 
                     res.push(symbol.clone());
@@ -32,6 +32,12 @@ config_data! {
         assist_importMergeBehaviour: MergeBehaviorDef  = "\"full\"",
         /// The path structure for newly inserted paths to use.
         assist_importPrefix: ImportPrefixDef           = "\"plain\"",
+        /// Check that the code inlined by the "Inline function" assists still resolves at the
+        /// call site, and don't offer them otherwise.
+        assist_verifyInlining: bool                    = "false",
+        /// Fold the `if`s on parameters given a `true` or `false` argument to the branch that is
+        /// taken when inlining a function.
+        assist_foldInlinedConditions: bool             = "false",
 
         /// Show function name and docs in parameter hints.
         callInfo_full: bool = "true",
@@ -582,6 +588,8 @@ impl Config {
                     ImportPrefixDef::BySelf => PrefixKind::BySelf,
                 },
             },
+            verify_inlining: self.data.assist_verifyInlining,
+            fold_inlined_conditions: self.data.assist_foldInlinedConditions,
         }
     }
     pub fn call_info_full(&self) -> bool {
@@ -1044,8 +1044,11 @@ pub(crate) fn handle_code_action_resolve(
     let index = index.parse::<usize>().unwrap();
     let assist = &assists[index];
     assert!(assist.id.0 == id);
-    let edit = to_proto::resolved_code_action(&snap, assist.clone())?.edit;
-    code_action.edit = edit;
+    let resolved = to_proto::resolved_code_action(&snap, assist.clone())?;
+    code_action.edit = resolved.edit;
+    code_action.title = resolved.title;
+    // Whether the assist is preferred is only worked out along with its edit.
+    code_action.is_preferred = resolved.is_preferred;
     Ok(code_action)
 }
 
@@ -810,12 +810,17 @@ pub(crate) fn resolved_code_action(
     assist: Assist,
 ) -> Result<lsp_ext::CodeAction> {
     let change = assist.source_change.unwrap();
+    // There is no other place for the warning in a code action, the title is what users see.
+    let title = match &assist.reason {
+        Some(reason) => format!("{} ({})", assist.label, reason),
+        None => assist.label.to_string(),
+    };
     let res = lsp_ext::CodeAction {
         edit: Some(snippet_workspace_edit(snap, change)?),
-        title: assist.label.to_string(),
+        title,
         group: assist.group.filter(|_| snap.config.code_action_group()).map(|gr| gr.0),
         kind: Some(code_action_kind(assist.id.1)),
-        is_preferred: None,
+        is_preferred: Some(assist.is_preferred).filter(|&it| it),
         data: None,
     };
     Ok(res)
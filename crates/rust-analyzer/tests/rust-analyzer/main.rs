@@ -25,7 +25,9 @@ use lsp_types::{
     PartialResultParams, Position, Range, RenameFilesParams, TextDocumentItem,
     TextDocumentPositionParams, WorkDoneProgressParams,
 };
-use rust_analyzer::lsp_ext::{OnEnter, Runnables, RunnablesParams};
+use rust_analyzer::lsp_ext::{
+    CodeAction, CodeActionResolveRequest, OnEnter, Runnables, RunnablesParams,
+};
 use serde_json::json;
 use test_utils::skip_slow_tests;
 
@@ -356,6 +358,49 @@ fn main() {}
     );
 }
 
+#[test]
+fn resolved_code_action_tells_whether_it_is_preferred() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- /src/lib.rs
+fn add(a: u32, b: u32) -> u32 { a + b }
+fn main() { let x = add(1, 2); }
+"#,
+    )
+    .with_code_action_resolve(true)
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let actions = server.send_request::<CodeActionRequest>(CodeActionParams {
+        text_document: server.doc_id("src/lib.rs"),
+        range: Range::new(Position::new(1, 20), Position::new(1, 20)),
+        context: CodeActionContext::default(),
+        partial_result_params: PartialResultParams::default(),
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    });
+    let inline = serde_json::from_value::<Vec<CodeAction>>(actions)
+        .unwrap()
+        .into_iter()
+        .find(|it| it.title == "Inline `add`")
+        .unwrap();
+    assert_eq!(inline.is_preferred, None);
+
+    // Inlining the only call of `add` is preferred, which is only known once resolved.
+    let resolved = server.send_request::<CodeActionResolveRequest>(inline);
+    let resolved = serde_json::from_value::<CodeAction>(resolved).unwrap();
+    assert!(resolved.edit.is_some());
+    assert_eq!(resolved.is_preferred, Some(true));
+}
+
 #[test]
 fn test_missing_module_code_action_in_json_project() {
     if skip_slow_tests() {
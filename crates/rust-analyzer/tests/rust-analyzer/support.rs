@@ -24,6 +24,7 @@ use crate::testdir::TestDir;
 pub(crate) struct Project<'a> {
     fixture: &'a str,
     with_sysroot: bool,
+    code_action_resolve: bool,
     tmp_dir: Option<TestDir>,
     roots: Vec<PathBuf>,
     config: serde_json::Value,
@@ -36,6 +37,7 @@ impl<'a> Project<'a> {
             tmp_dir: None,
             roots: vec![],
             with_sysroot: false,
+            code_action_resolve: false,
             config: serde_json::Value::Null,
         }
     }
@@ -55,6 +57,12 @@ impl<'a> Project<'a> {
         self
     }
 
+    /// Lets the client resolve the edits of the code actions lazily, like VS Code does.
+    pub(crate) fn with_code_action_resolve(mut self, yes: bool) -> Project<'a> {
+        self.code_action_resolve = yes;
+        self
+    }
+
     pub(crate) fn with_config(mut self, config: serde_json::Value) -> Project<'a> {
         self.config = config;
         self
@@ -97,6 +105,12 @@ impl<'a> Project<'a> {
                         code_action_literal_support: Some(
                             lsp_types::CodeActionLiteralSupport::default(),
                         ),
+                        resolve_support: match self.code_action_resolve {
+                            true => Some(lsp_types::CodeActionCapabilityResolveSupport {
+                                properties: vec!["edit".to_string()],
+                            }),
+                            false => None,
+                        },
                         ..Default::default()
                     }),
                     hover: Some(lsp_types::HoverClientCapabilities {
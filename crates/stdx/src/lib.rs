@@ -66,6 +66,21 @@ fn to_snake_case<F: Fn(&char) -> char>(s: &str, change_case: F) -> String {
     buf
 }
 
+/// The Levenshtein distance between `a` and `b`, counted in chars.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + if a_char == *b_char { 0 } else { 1 };
+            row.push(substitution.min(previous_row[j + 1] + 1).min(row[j] + 1));
+        }
+        previous_row = row;
+    }
+    previous_row[b.len()]
+}
+
 pub fn replace(buf: &mut String, from: char, to: &str) {
     if !buf.contains(from) {
         return;
@@ -201,6 +216,17 @@ impl Drop for JodChild {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("hashmap", "hashmap"), 0);
+        assert_eq!(edit_distance("hasmap", "hashmap"), 1);
+        assert_eq!(edit_distance("hashmap", "hasmap"), 1);
+        assert_eq!(edit_distance("vex", "vec"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "box"), 3);
+    }
+
     #[test]
     fn test_trim_indent() {
         assert_eq!(trim_indent(""), "");
@@ -61,6 +61,43 @@ impl ast::Fn {
     }
 }
 
+impl ast::BlockExpr {
+    /// Collapses a block whose only content is another block, like `{ { foo(); bar } }`, into
+    /// the inner block. Blocks with anything else, even comments, are returned as is.
+    #[must_use]
+    pub fn flatten_nested_block(&self) -> ast::BlockExpr {
+        let mut block = self.clone();
+        loop {
+            let inner = match block.tail_expr() {
+                Some(ast::Expr::BlockExpr(it)) => it,
+                _ => return block,
+            };
+            let has_anything_else =
+                block.syntax().children_with_tokens().any(|it| match it.kind() {
+                    WHITESPACE | T!['{'] | T!['}'] => false,
+                    _ => it.as_node() != Some(inner.syntax()),
+                });
+            if has_anything_else {
+                return block;
+            }
+            // An inner block on lines of its own is indented one level deeper than the outer.
+            let shift = match (closing_indent(&inner), closing_indent(&block)) {
+                (Some(inner_level), Some(outer_level)) => inner_level.saturating_sub(outer_level),
+                _ => 0,
+            };
+            block = inner.dedent(IndentLevel(shift));
+        }
+    }
+}
+
+/// The indent level of the line with the closing brace of `block`, if it starts that line.
+fn closing_indent(block: &ast::BlockExpr) -> Option<u8> {
+    let ws = block.r_curly_token()?.prev_token().and_then(ast::Whitespace::cast)?;
+    let text = ws.text();
+    let pos = text.rfind('\n')?;
+    Some((text[pos + 1..].chars().count() / 4) as u8)
+}
+
 fn make_multiline<N>(node: N) -> N
 where
     N: AstNode + Clone,
@@ -756,3 +793,60 @@ fn test_increase_indent() {
         }"
     );
 }
+
+#[test]
+fn test_flatten_nested_block() {
+    let nth_block = |text: &str, n: usize| {
+        let file = crate::SourceFile::parse(text).tree();
+        file.syntax().descendants().filter_map(ast::BlockExpr::cast).nth(n).unwrap()
+    };
+    let nested = nth_block(
+        "fn f() {
+    {
+        {
+            foo();
+            bar
+        }
+    }
+}",
+        0,
+    );
+    assert_eq!(
+        nested.flatten_nested_block().syntax().to_string(),
+        "{
+    foo();
+    bar
+}"
+    );
+
+    let same_line = nth_block(
+        "fn f() {
+    let x = { {
+        foo();
+        bar
+    } };
+}",
+        1,
+    );
+    assert_eq!(
+        same_line.flatten_nested_block().syntax().to_string(),
+        "{
+        foo();
+        bar
+    }"
+    );
+
+    let with_statement = nth_block(
+        "fn f() {
+    foo();
+    {
+        bar
+    }
+}",
+        0,
+    );
+    assert_eq!(
+        with_statement.flatten_nested_block().syntax().to_string(),
+        with_statement.syntax().to_string()
+    );
+}